@@ -5,4 +5,4 @@ set -e;
 OUT_PATH="bin";
 GAME_PATH="$OUT_PATH/bevy_pong";
 
-exec $GAME_PATH;
+exec $GAME_PATH "$@";
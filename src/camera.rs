@@ -0,0 +1,117 @@
+use core::time::Duration;
+
+use bevy::{prelude::*, render::camera::CameraUpdateSystem, window::PrimaryWindow};
+
+use crate::{
+    game::layout::ArenaLayout,
+    settings::{
+        camera::{letterbox_viewport, orthographic_projection, UI_REFERENCE_HEIGHT},
+        window::WindowSettings,
+    },
+};
+
+pub fn spawn_camera(layout: Res<ArenaLayout>, mut commands: Commands) {
+    commands.spawn((
+        Camera2d,
+        orthographic_projection(layout.size),
+        IsDefaultUiCamera,
+    ));
+}
+
+/// Size of the resized window, saved to the [`WindowSettings`] once it stops changing.
+#[derive(Resource)]
+pub struct WindowResize {
+    size: WindowSettings,
+    timer: Timer,
+}
+
+impl WindowResize {
+    pub const SETTLE: Duration = Duration::from_millis(500);
+
+    pub fn new(size: WindowSettings) -> Self {
+        Self {
+            size,
+            timer: Timer::new(Self::SETTLE, TimerMode::Once),
+        }
+    }
+}
+
+/// Zooms the camera to show the whole arena of the current layout.
+pub fn fit_camera_to_arena(
+    projection: Single<&mut OrthographicProjection, With<Camera2d>>,
+    layout: Res<ArenaLayout>,
+) {
+    let mut projection = projection.into_inner();
+    *projection = orthographic_projection(layout.size);
+}
+
+/// Letterboxes the camera to show the whole arena, scaling the UI with it.
+pub fn fit_camera_to_window(
+    window: Single<&Window, (With<PrimaryWindow>, Changed<Window>)>,
+    camera: Single<&mut Camera, With<Camera2d>>,
+    mut ui_scale: ResMut<UiScale>,
+    window_settings: Res<WindowSettings>,
+    resize: Option<Res<WindowResize>>,
+    mut commands: Commands,
+) {
+    let window = window.into_inner();
+    let mut camera = camera.into_inner();
+
+    let Some(viewport) = letterbox_viewport(window.physical_size()) else {
+        return;
+    };
+
+    let scale = viewport.physical_size.y as f32 / window.scale_factor() / UI_REFERENCE_HEIGHT;
+    if ui_scale.0 != scale {
+        ui_scale.0 = scale;
+    }
+
+    let unchanged = camera.viewport.as_ref().is_some_and(|current| {
+        current.physical_position == viewport.physical_position
+            && current.physical_size == viewport.physical_size
+    });
+    if !unchanged {
+        camera.viewport = Some(viewport);
+    }
+
+    let size = WindowSettings {
+        width: window.width(),
+        height: window.height(),
+    };
+    if resize.as_ref().is_some_and(|resize| resize.size == size) {
+        return;
+    }
+    if *window_settings != size {
+        commands.insert_resource(WindowResize::new(size));
+    } else if resize.is_some() {
+        commands.remove_resource::<WindowResize>();
+    }
+}
+
+/// Saves the window size after [`WindowResize::SETTLE`] without resizing.
+pub fn settle_window_size(
+    mut resize: ResMut<WindowResize>,
+    mut window_settings: ResMut<WindowSettings>,
+    time: Res<Time<Real>>,
+    mut commands: Commands,
+) {
+    if resize.timer.tick(time.delta()).finished() {
+        window_settings.set_if_neq(resize.size);
+        commands.remove_resource::<WindowResize>();
+    }
+}
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(Startup, spawn_camera);
+    app.add_systems(PostUpdate, fit_camera_to_window.before(CameraUpdateSystem));
+    app.add_systems(
+        Update,
+        settle_window_size.run_if(resource_exists::<WindowResize>),
+    );
+    app.add_systems(
+        PostUpdate,
+        fit_camera_to_arena
+            .run_if(resource_changed::<ArenaLayout>)
+            .before(CameraUpdateSystem),
+    );
+}
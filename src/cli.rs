@@ -0,0 +1,134 @@
+use std::path::PathBuf;
+
+use crate::{game::resource::MultiBall, network::Netcode};
+
+/// Options given in the command line when launching the game.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct LaunchOptions {
+    /// Seed of the gameplay randomness, random when not given.
+    pub seed: Option<u64>,
+    /// Simulate a match without a window or renderer.
+    pub headless: bool,
+    /// Replay file to watch on launch.
+    pub replay: Option<PathBuf>,
+    /// Play in the four players mode.
+    pub four_players: bool,
+    /// Serve extra balls during the points: `off`, `timer` or `rally`.
+    pub multi_ball: Option<MultiBall>,
+    /// Spawn power-ups in the arena.
+    pub power_ups: bool,
+    /// Arena layout file to play in, instead of the classic arena.
+    pub layout: Option<PathBuf>,
+    /// UDP port to host the online matches on.
+    pub port: Option<u16>,
+    /// Address of the host to join, such as `127.0.0.1:7878`.
+    pub join: Option<String>,
+    /// Netcode of the hosted matches: `server` or `rollback`.
+    pub netcode: Option<Netcode>,
+    /// Ticks the local inputs are delayed by, in the rollback matches.
+    pub input_delay: Option<u32>,
+    /// Milliseconds added before sending each network message.
+    pub simulate_latency: Option<u64>,
+    /// Percentage of the network messages dropped.
+    pub simulate_loss: Option<u8>,
+    /// Address to stream the game to spectators on, such as `127.0.0.1:7879`.
+    pub stream: Option<String>,
+    /// Address of the stream to show, instead of playing.
+    pub spectate: Option<String>,
+}
+
+impl LaunchOptions {
+    pub fn from_args() -> Self {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    /// Parses the launch options, ignoring unknown or invalid arguments.
+    ///
+    /// The values can be given as `--option value` or `--option=value`.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Self {
+        let mut options = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+
+            match name.as_str() {
+                "--seed" => {
+                    let value = inline_value.or_else(|| args.next());
+                    options.seed = parse_value(&name, value);
+                }
+                "--headless" => options.headless = true,
+                "--four-players" => options.four_players = true,
+                "--power-ups" => options.power_ups = true,
+                "--multi-ball" => {
+                    let value = inline_value.or_else(|| args.next());
+                    options.multi_ball = parse_value(&name, value);
+                }
+                "--layout" => {
+                    let value = inline_value.or_else(|| args.next());
+                    options.layout = parse_value(&name, value);
+                }
+                "--port" => {
+                    let value = inline_value.or_else(|| args.next());
+                    options.port = parse_value(&name, value);
+                }
+                "--join" => {
+                    let value = inline_value.or_else(|| args.next());
+                    options.join = parse_value(&name, value);
+                }
+                "--netcode" => {
+                    let value = inline_value.or_else(|| args.next());
+                    options.netcode = parse_value(&name, value);
+                }
+                "--input-delay" => {
+                    let value = inline_value.or_else(|| args.next());
+                    options.input_delay = parse_value(&name, value);
+                }
+                "--simulate-latency" => {
+                    let value = inline_value.or_else(|| args.next());
+                    options.simulate_latency = parse_value(&name, value);
+                }
+                "--simulate-loss" => {
+                    let value = inline_value.or_else(|| args.next());
+                    options.simulate_loss = parse_value(&name, value);
+                }
+                "--stream" => {
+                    let value = inline_value.or_else(|| args.next());
+                    options.stream = parse_value(&name, value);
+                }
+                "--spectate" => {
+                    let value = inline_value.or_else(|| args.next());
+                    options.spectate = parse_value(&name, value);
+                }
+                "--replay" => {
+                    let value = inline_value.or_else(|| args.next());
+                    options.replay = parse_value(&name, value);
+                }
+                _ => eprintln!("ignoring unknown argument \"{name}\""),
+            }
+        }
+
+        options
+    }
+}
+
+fn parse_value<T: core::str::FromStr>(name: &str, value: Option<String>) -> Option<T> {
+    let value = match value {
+        Some(value) => value,
+        None => {
+            eprintln!("missing value for argument \"{name}\"");
+            return None;
+        }
+    };
+
+    match value.parse() {
+        Ok(value) => Some(value),
+        Err(_) => {
+            eprintln!("invalid value \"{value}\" for argument \"{name}\"");
+            None
+        }
+    }
+}
@@ -1,25 +1,69 @@
 use bevy::prelude::*;
 
-use event::{GameDataUpdated, PointMarked};
-use resource::{CommonMesh, GameActiveData, StartMatchTimer};
-use state::{GameActiveState, InGame};
+use event::{GameDataUpdated, MatchOver, PointMarked};
+use layout::ArenaLayout;
+use player::AIDifficulty;
+use resource::{
+    ExtraBallProgress, FourPlayers, GameActiveData, GameRng, MainPlayer, MatchRules, MultiBall,
+    PaddleBounce, PlayerDevices, PowerUpTimer, PowerUps, SecondPlayer, StartMatchTimer,
+};
+use state::{GameActiveState, GameState, InGame};
+use stats::MatchStats;
+
+use crate::settings::input::InputSettings;
 
 pub mod arena;
 pub mod event;
+pub mod layout;
 pub mod physics;
 pub mod player;
+pub mod power_up;
+pub mod render;
 pub mod resource;
 pub mod state;
+pub mod stats;
 pub mod system;
 
+/// Order of the game systems in each fixed update.
+///
+/// The order is fixed so a match can be replayed from its seed and player inputs.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, SystemSet)]
+pub enum GameSystems {
+    /// Reads the paddle inputs of the players.
+    Input,
+    /// Moves the paddles and the balls, and applies the power-ups.
+    Simulation,
+    /// Registers the points and the end of the match.
+    Scoring,
+}
+
 pub fn plugin(app: &mut App) {
     use system::*;
 
+    app.init_state::<GameState>();
+    app.add_computed_state::<GameActiveState>();
+    app.add_computed_state::<InGame>();
+
     app.init_resource::<GameActiveData>();
-    app.init_resource::<CommonMesh>();
+    app.init_resource::<MatchStats>();
+    app.init_resource::<ArenaLayout>();
+    app.init_resource::<MainPlayer>();
+    app.init_resource::<SecondPlayer>();
+    app.init_resource::<FourPlayers>();
+    app.init_resource::<AIDifficulty>();
+    app.init_resource::<PlayerDevices>();
+    app.init_resource::<MatchRules>();
+    app.init_resource::<PaddleBounce>();
+    app.init_resource::<MultiBall>();
+    app.init_resource::<ExtraBallProgress>();
+    app.init_resource::<PowerUps>();
+    app.init_resource::<PowerUpTimer>();
+    app.init_resource::<GameRng>();
+    app.init_resource::<InputSettings>();
 
     app.add_event::<PointMarked>();
     app.add_event::<GameDataUpdated>();
+    app.add_event::<MatchOver>();
 
     app.add_systems(
         OnEnter(InGame),
@@ -34,30 +78,55 @@ pub fn plugin(app: &mut App) {
     );
     app.add_systems(
         OnExit(InGame),
-        (despawn_arena, despawn_players, despawn_ball),
+        (
+            despawn_arena,
+            despawn_players,
+            despawn_balls,
+            next_match_seed.run_if(not(in_state(InGame))),
+        ),
     );
 
-    app.add_systems(
+    app.configure_sets(
         FixedUpdate,
-        start_match
-            .run_if(in_state(GameActiveState::Playing).and(resource_exists::<StartMatchTimer>)),
+        (
+            GameSystems::Input,
+            GameSystems::Simulation,
+            GameSystems::Scoring,
+        )
+            .chain()
+            .run_if(in_state(GameActiveState::Playing)),
     );
 
+    app.add_systems(FixedUpdate, read_player_input.in_set(GameSystems::Input));
     app.add_systems(
         FixedUpdate,
         (
+            advance_match_time,
+            start_match.run_if(resource_exists::<StartMatchTimer>),
+            spawn_extra_ball.run_if(not(resource_exists::<StartMatchTimer>)),
+            spawn_power_up.run_if(power_ups_enabled.and(not(resource_exists::<StartMatchTimer>))),
+            update_paddle_effects,
+            update_shields,
             move_paddle_by_player,
             move_paddle_by_ai,
             move_ball,
+            update_match_stats,
+            collect_power_ups,
             check_ball_leaved_arena,
         )
             .chain()
-            .run_if(in_state(GameActiveState::Playing)),
+            .in_set(GameSystems::Simulation),
     );
     app.add_systems(
         FixedUpdate,
-        (register_score_point, init_match)
+        (
+            register_score_point,
+            register_stats_point,
+            check_match_over,
+            init_match.run_if(match_in_progress.and(round_over)),
+        )
             .chain()
-            .run_if(in_state(GameActiveState::Playing).and(on_event::<PointMarked>)),
+            .run_if(on_event::<PointMarked>)
+            .in_set(GameSystems::Scoring),
     );
 }
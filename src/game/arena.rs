@@ -1,5 +1,5 @@
 use bevy::{
-    math::bounding::{Aabb2d, BoundingCircle},
+    math::bounding::{Aabb2d, BoundingCircle, BoundingVolume},
     prelude::*,
 };
 use rand::{
@@ -7,39 +7,41 @@ use rand::{
     Rng,
 };
 
-use super::physics::LinearVelocity;
+use super::{
+    layout::ArenaLayout,
+    physics::{LinearVelocity, TrajectoryPredictor},
+    player::PlayerSide,
+    power_up::{BallCurve, PaddleEffects},
+};
 
 #[derive(Default, Component)]
 #[require(Transform, Visibility)]
 pub struct Arena;
 
 impl Arena {
+    /// Size of the classic arena layout.
     pub const SIZE: Vec2 = Vec2::new(100.0, 50.0);
 
     pub const COLOR: Color = Color::WHITE;
 
     const COLLIDER_HALF_THICKNESS: f32 = 0.5;
 
-    #[expect(unused)]
     pub fn left_collider() -> Aabb2d {
         Aabb2d::new(
             Vec2::new(-Arena::SIZE.x / 2.0 + -Self::COLLIDER_HALF_THICKNESS, 0.0),
             Vec2::new(Self::COLLIDER_HALF_THICKNESS, Arena::SIZE.y),
         )
     }
-
-    pub fn right_collider() -> Aabb2d {
-        Aabb2d::new(
-            Vec2::new(Arena::SIZE.x / 2.0 + Self::COLLIDER_HALF_THICKNESS, 0.0),
-            Vec2::new(Self::COLLIDER_HALF_THICKNESS, Arena::SIZE.y),
-        )
-    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum ArenaDirection {
     Left,
     Right,
+    /// Top edge, only defended in the four players mode.
+    Up,
+    /// Bottom edge, only defended in the four players mode.
+    Down,
 }
 
 impl ArenaDirection {
@@ -47,10 +49,13 @@ impl ArenaDirection {
         match *self {
             Self::Left => Self::Right,
             Self::Right => Self::Left,
+            Self::Up => Self::Down,
+            Self::Down => Self::Up,
         }
     }
 }
 
+/// Samples the left or right edge, defended in every match.
 impl Distribution<ArenaDirection> for Standard {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> ArenaDirection {
         if rng.gen::<bool>() {
@@ -61,12 +66,10 @@ impl Distribution<ArenaDirection> for Standard {
     }
 }
 
-#[derive(PartialEq, Eq, Component)]
+/// Wall of the arena layout, or post beside a goal opening.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Component)]
 #[require(Transform)]
-pub enum Wall {
-    Top = 1,
-    Bottom = 2,
-}
+pub struct Wall;
 
 impl Wall {
     pub const THICKNESS: f32 = 2.0;
@@ -82,30 +85,74 @@ impl Wall {
             Vec3::new(Arena::SIZE.x + Self::THICKNESS, Self::THICKNESS, 1.0),
         )
     }
-
-    #[expect(unused)]
-    pub const fn transform(&self) -> Transform {
-        match *self {
-            Wall::Top => Self::top_transform(),
-            Wall::Bottom => Self::bottom_transform(),
-        }
-    }
 }
 
+/// Rectangular obstacle inside the arena, from the arena layout.
 #[derive(Clone, Copy, Default, PartialEq, Eq, Component)]
-#[require(Transform, Visibility)]
+#[require(Transform)]
+pub struct Obstacle;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Component)]
+#[require(Transform, Visibility, PaddleEffects)]
 pub struct Paddle;
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum PaddleDirection {
     Up = 1,
     Down = 2,
+    /// Only for the top and bottom paddles.
+    Left = 3,
+    /// Only for the top and bottom paddles.
+    Right = 4,
+}
+
+/// Axis along which a paddle moves.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PaddleAxis {
+    /// Paddles of the left and right sides.
+    Vertical,
+    /// Paddles of the top and bottom sides, in the four players mode.
+    Horizontal,
+}
+
+impl PaddleAxis {
+    pub const fn of(side: PlayerSide) -> Self {
+        match side {
+            PlayerSide::Main | PlayerSide::Other => Self::Vertical,
+            PlayerSide::Top | PlayerSide::Bottom => Self::Horizontal,
+        }
+    }
+
+    /// Coordinate of the `point` along the axis.
+    pub const fn along(&self, point: Vec2) -> f32 {
+        match *self {
+            Self::Vertical => point.y,
+            Self::Horizontal => point.x,
+        }
+    }
+
+    /// Coordinate of the `point` across the axis, towards the paddle face.
+    pub const fn across(&self, point: Vec2) -> f32 {
+        match *self {
+            Self::Vertical => point.x,
+            Self::Horizontal => point.y,
+        }
+    }
+
+    const fn along_mut<'a>(&self, vector: &'a mut Vec3) -> &'a mut f32 {
+        match *self {
+            Self::Vertical => &mut vector.y,
+            Self::Horizontal => &mut vector.x,
+        }
+    }
 }
 
 impl Paddle {
     pub const Z_INDEX: f32 = 1.0;
 
     pub const LENGTH: f32 = 10.0;
+    /// Length of the top and bottom paddles, covering the same share of the longer edges.
+    pub const HORIZONTAL_LENGTH: f32 = Self::LENGTH * Arena::SIZE.x / Arena::SIZE.y;
     pub const THICKNESS: f32 = 1.2;
     pub const EDGE_MARGIN: f32 = 2.0;
     pub const VELOCITY: f32 = 50.0;
@@ -113,25 +160,27 @@ impl Paddle {
 
     pub const COLOR: Color = Color::WHITE;
 
-    pub const fn new_main_transform() -> Transform {
-        Transform::from_xyz(
-            -Arena::SIZE.x / 2.0 + Self::EDGE_MARGIN + Self::THICKNESS,
-            0.0,
-            Self::Z_INDEX,
-        )
-        .with_scale(Vec3::new(Self::THICKNESS, Self::LENGTH, 1.0))
+    pub const fn length(axis: PaddleAxis) -> f32 {
+        match axis {
+            PaddleAxis::Vertical => Self::LENGTH,
+            PaddleAxis::Horizontal => Self::HORIZONTAL_LENGTH,
+        }
     }
 
-    pub const fn new_second_transform() -> Transform {
-        Transform::from_xyz(
-            Arena::SIZE.x / 2.0 + -Self::EDGE_MARGIN + -Self::THICKNESS,
-            0.0,
-            Self::Z_INDEX,
-        )
-        .with_scale(Vec3::new(Self::THICKNESS, Self::LENGTH, 1.0))
+    /// Transform of the paddle of the `side`, centered on its edge at the distance given by the `layout`.
+    pub fn new_transform(side: PlayerSide, layout: &ArenaLayout) -> Transform {
+        let edge = layout.edge(side);
+        let across = edge - edge.signum() * layout.paddle_distance(side);
+
+        match PaddleAxis::of(side) {
+            PaddleAxis::Vertical => Transform::from_xyz(across, 0.0, Self::Z_INDEX)
+                .with_scale(Vec3::new(Self::THICKNESS, Self::LENGTH, 1.0)),
+            PaddleAxis::Horizontal => Transform::from_xyz(0.0, across, Self::Z_INDEX)
+                .with_scale(Vec3::new(Self::HORIZONTAL_LENGTH, Self::THICKNESS, 1.0)),
+        }
     }
 
-    pub const fn move_vertically(
+    pub const fn move_in_direction(
         direction: PaddleDirection,
         transform: &mut Transform,
         delta_time: f32,
@@ -143,29 +192,103 @@ impl Paddle {
             PaddleDirection::Down => {
                 transform.translation.y -= Self::VELOCITY * delta_time;
             }
+            PaddleDirection::Left => {
+                transform.translation.x -= Self::VELOCITY * delta_time;
+            }
+            PaddleDirection::Right => {
+                transform.translation.x += Self::VELOCITY * delta_time;
+            }
         };
     }
 
-    pub const fn min_y_position() -> f32 {
-        -Arena::SIZE.y / 2.0 + Self::LENGTH / 2.0
+    /// Moves the paddle by a player input, from `-1.0` (full speed down or left) to `1.0` (full speed up or right).
+    ///
+    /// The `speed_scale` multiplies the default velocity, changed by the paddle effects.
+    pub fn move_by_input(
+        transform: &mut Transform,
+        axis: PaddleAxis,
+        input: f32,
+        speed_scale: f32,
+        delta_time: f32,
+    ) {
+        *axis.along_mut(&mut transform.translation) +=
+            input.clamp(-1.0, 1.0) * Self::VELOCITY * speed_scale * delta_time;
+    }
+
+    /// Moves the paddle to `target` along its `axis`, limited by `max_speed`.
+    pub fn move_towards(
+        transform: &mut Transform,
+        axis: PaddleAxis,
+        target: f32,
+        max_speed: f32,
+        delta_time: f32,
+    ) {
+        let position = axis.along_mut(&mut transform.translation);
+        let distance = target - *position;
+        let max_distance = max_speed * delta_time;
+
+        *position += distance.clamp(-max_distance, max_distance);
+    }
+
+    pub const fn min_y_position(arena: Vec2, length: f32) -> f32 {
+        -arena.y / 2.0 + length / 2.0
+    }
+
+    pub const fn max_y_position(arena: Vec2, length: f32) -> f32 {
+        arena.y / 2.0 - length / 2.0
+    }
+
+    /// Highest position of the top and bottom paddles, kept out of the left and right paddle lanes.
+    pub const fn max_x_position(arena: Vec2, length: f32) -> f32 {
+        arena.x / 2.0 - Self::EDGE_MARGIN - Self::THICKNESS * 2.0 - length / 2.0
+    }
+
+    pub const fn min_x_position(arena: Vec2, length: f32) -> f32 {
+        -Self::max_x_position(arena, length)
     }
 
-    pub const fn max_y_position() -> f32 {
-        Arena::SIZE.y / 2.0 - Self::LENGTH / 2.0
+    /// Sets the paddle length to its default length multiplied by `scale`.
+    pub const fn set_length_scale(transform: &mut Transform, axis: PaddleAxis, scale: f32) {
+        *axis.along_mut(&mut transform.scale) = Self::length(axis) * scale;
     }
 
-    pub fn clamp_position(transform: &mut Transform) {
-        transform.translation.y = transform
-            .translation
-            .y
-            .clamp(Paddle::min_y_position(), Paddle::max_y_position());
+    /// Where `point` is along the paddle length, from `-1.0` (bottom or left edge) to `1.0` (top or right edge).
+    pub fn contact_offset(paddle: &Aabb2d, axis: PaddleAxis, point: Vec2) -> f32 {
+        let half_length = axis.along(paddle.half_size());
+        if half_length <= 0.0 {
+            return 0.0;
+        }
+
+        ((axis.along(point) - axis.along(paddle.center())) / half_length).clamp(-1.0, 1.0)
+    }
+
+    /// Keeps the paddle inside the `arena` size, by its current length.
+    pub fn clamp_position(transform: &mut Transform, axis: PaddleAxis, arena: Vec2) {
+        let length = axis.along(transform.scale.truncate());
+        let (min, max) = match axis {
+            PaddleAxis::Vertical => (
+                Paddle::min_y_position(arena, length),
+                Paddle::max_y_position(arena, length),
+            ),
+            PaddleAxis::Horizontal => (
+                Paddle::min_x_position(arena, length),
+                Paddle::max_x_position(arena, length),
+            ),
+        };
+
+        let position = axis.along_mut(&mut transform.translation);
+        *position = position.clamp(min, max);
     }
 }
 
 #[derive(Default, Component)]
-#[require(Transform, Visibility)]
+#[require(Transform, Visibility, LastHit, BallCurve)]
 pub struct Ball;
 
+/// Side of the last paddle the ball bounced off, since it was served.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Component)]
+pub struct LastHit(pub Option<PlayerSide>);
+
 impl Ball {
     pub const Z_INDEX: f32 = 1.0;
     pub const RADIUS: f32 = 0.5;
@@ -183,18 +306,46 @@ impl Ball {
         BoundingCircle::new(transform.translation.truncate(), Ball::RADIUS * scale)
     }
 
-    pub const fn start_velocity_x(direction: ArenaDirection) -> f32 {
+    /// Start velocity towards the `direction`, across the edge.
+    pub const fn start_velocity(direction: ArenaDirection) -> f32 {
         match direction {
-            ArenaDirection::Left => -Ball::START_VELOCITY,
-            ArenaDirection::Right => Ball::START_VELOCITY,
+            ArenaDirection::Left | ArenaDirection::Down => -Ball::START_VELOCITY,
+            ArenaDirection::Right | ArenaDirection::Up => Ball::START_VELOCITY,
         }
     }
 
-    pub fn random_linear_velocity(direction: ArenaDirection) -> Vec2 {
-        Vec2::new(
-            Self::start_velocity_x(direction),
-            rand::thread_rng().gen_range(-1.0..1.0) * Ball::START_VELOCITY * 0.5,
-        )
+    pub fn random_linear_velocity<R: Rng + ?Sized>(direction: ArenaDirection, rng: &mut R) -> Vec2 {
+        let across = Self::start_velocity(direction);
+        let along = rng.gen_range(-1.0..1.0) * Ball::START_VELOCITY * 0.5;
+
+        match direction {
+            ArenaDirection::Left | ArenaDirection::Right => Vec2::new(across, along),
+            ArenaDirection::Up | ArenaDirection::Down => Vec2::new(along, across),
+        }
+    }
+
+    /// Predictor of the ball trajectory inside the top and bottom walls of an `arena` size.
+    ///
+    /// Without `walls`, in the four players mode, the ball is predicted in a straight line.
+    /// The obstacles of the arena layout are not predicted.
+    pub fn trajectory_predictor(
+        transform: &Transform,
+        arena: Vec2,
+        walls: bool,
+    ) -> TrajectoryPredictor {
+        let radius = Self::bounding_circle(transform).radius();
+        let (min_y, max_y) = if walls {
+            (-arena.y / 2.0 + radius, arena.y / 2.0 - radius)
+        } else {
+            (f32::NEG_INFINITY, f32::INFINITY)
+        };
+
+        TrajectoryPredictor {
+            min_y,
+            max_y,
+            acceleration: Self::ACCELERATION_PERCENT,
+            max_speed: Self::MAX_SPEED,
+        }
     }
 
     pub fn limit_velocity(velocity: &mut LinearVelocity) {
@@ -213,20 +364,4 @@ impl Ball {
         transform.translation = Vec3::ZERO;
         velocity.0 = Vec2::ZERO;
     }
-
-    pub const fn current_arena_direction(transform: &Transform) -> ArenaDirection {
-        if transform.translation.x > 0.0 {
-            ArenaDirection::Right
-        } else {
-            ArenaDirection::Left
-        }
-    }
-
-    pub const fn moving_to_arena_direction(velocity: &LinearVelocity) -> ArenaDirection {
-        if velocity.0.x > 0.0 {
-            ArenaDirection::Right
-        } else {
-            ArenaDirection::Left
-        }
-    }
 }
@@ -15,3 +15,14 @@ impl PointMarked {
 
 #[derive(Clone, Copy, PartialEq, Eq, Event)]
 pub struct GameDataUpdated;
+
+#[derive(Clone, Copy, PartialEq, Eq, Event)]
+pub struct MatchOver {
+    pub winner: PlayerSide,
+}
+
+impl MatchOver {
+    pub const fn new(winner: PlayerSide) -> Self {
+        Self { winner }
+    }
+}
@@ -0,0 +1,413 @@
+use core::fmt;
+use std::{fs, io, path::Path};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext, LoadedFolder},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    arena::{Arena, Paddle, PaddleAxis, Wall},
+    player::PlayerSide,
+};
+
+/// Rectangle of a wall or obstacle, in arena units, centered on the arena origin.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct LayoutRect {
+    pub center: Vec2,
+    pub size: Vec2,
+}
+
+impl LayoutRect {
+    pub const fn new(center: Vec2, size: Vec2) -> Self {
+        Self { center, size }
+    }
+
+    /// Transform of a collider covering the rectangle, sized by its scale.
+    pub const fn transform(&self) -> Transform {
+        Transform::from_xyz(self.center.x, self.center.y, 0.0).with_scale(Vec3::new(
+            self.size.x,
+            self.size.y,
+            1.0,
+        ))
+    }
+}
+
+/// Opening in the middle of the edge of a side, where the balls leave the arena to score.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct GoalOpening {
+    pub side: PlayerSide,
+    pub width: f32,
+}
+
+/// Distance between the edge of a side and the center of its paddle.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct PaddlePosition {
+    pub side: PlayerSide,
+    pub distance: f32,
+}
+
+/// Layout of the arena, loaded from the `.layout.ron` files of the `layouts` assets directory.
+///
+/// The arena is centered on the origin, where the balls are served. The `walls` close the top and bottom
+/// edges of a classic match, and are not spawned in the four players mode. The `obstacles` are spawned in
+/// every match. A side without a goal opening or a paddle position uses its whole edge as the goal,
+/// or the default paddle position.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Asset, TypePath, Resource)]
+pub struct ArenaLayout {
+    pub name: String,
+    pub size: Vec2,
+    #[serde(default)]
+    pub walls: Vec<LayoutRect>,
+    #[serde(default)]
+    pub obstacles: Vec<LayoutRect>,
+    #[serde(default)]
+    pub goals: Vec<GoalOpening>,
+    #[serde(default)]
+    pub paddles: Vec<PaddlePosition>,
+}
+
+impl ArenaLayout {
+    pub const CLASSIC_NAME: &str = "Classic";
+    pub const EXTENSION: &str = "layout.ron";
+    /// Assets directory of the layout files.
+    pub const DIRECTORY: &str = "layouts";
+
+    /// The built-in layout, always available even without the assets.
+    pub fn classic() -> Self {
+        let wall = |transform: Transform| {
+            LayoutRect::new(transform.translation.truncate(), transform.scale.truncate())
+        };
+
+        Self {
+            name: Self::CLASSIC_NAME.to_string(),
+            size: Arena::SIZE,
+            walls: vec![wall(Wall::top_transform()), wall(Wall::bottom_transform())],
+            obstacles: Vec::new(),
+            goals: Vec::new(),
+            paddles: Vec::new(),
+        }
+    }
+
+    /// Reads a layout file, without the asset system.
+    pub fn load(path: &Path) -> Result<Self, LayoutError> {
+        Self::parse(&fs::read(path)?)
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, LayoutError> {
+        let layout: Self = ron::de::from_bytes(bytes)?;
+        layout.validate()?;
+        Ok(layout)
+    }
+
+    fn validate(&self) -> Result<(), LayoutError> {
+        let positive = |value: Vec2| value.cmpgt(Vec2::ZERO).all() && value.is_finite();
+
+        if !positive(self.size) {
+            return Err(LayoutError::Invalid("the arena size must be positive"));
+        }
+        if !self
+            .walls
+            .iter()
+            .chain(&self.obstacles)
+            .all(|rect| positive(rect.size) && rect.center.is_finite())
+        {
+            return Err(LayoutError::Invalid(
+                "the walls and obstacles must have a positive size",
+            ));
+        }
+        if !self
+            .goals
+            .iter()
+            .all(|goal| goal.width > 0.0 && goal.width <= self.edge_length(goal.side))
+        {
+            return Err(LayoutError::Invalid(
+                "the goal openings must be positive and fit their edge",
+            ));
+        }
+        if self.goals.iter().enumerate().any(|(index, goal)| {
+            self.goals[..index]
+                .iter()
+                .any(|other| other.side == goal.side)
+        }) {
+            return Err(LayoutError::Invalid(
+                "each side can only have one goal opening",
+            ));
+        }
+        if !self.paddles.iter().all(|paddle| {
+            paddle.distance > Paddle::THICKNESS / 2.0
+                && paddle.distance < self.half_size(PaddleAxis::of(paddle.side))
+        }) {
+            return Err(LayoutError::Invalid(
+                "the paddles must be inside the arena, between their edge and the center",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Half of the arena size across the paddles moving along `axis`.
+    const fn half_size(&self, axis: PaddleAxis) -> f32 {
+        axis.across(self.size) / 2.0
+    }
+
+    /// Coordinate of the edge of the `side`, across its paddle axis.
+    pub const fn edge(&self, side: PlayerSide) -> f32 {
+        let half_size = self.half_size(PaddleAxis::of(side));
+        match side {
+            PlayerSide::Main | PlayerSide::Bottom => -half_size,
+            PlayerSide::Other | PlayerSide::Top => half_size,
+        }
+    }
+
+    /// Length of the edge of the `side`.
+    pub const fn edge_length(&self, side: PlayerSide) -> f32 {
+        PaddleAxis::of(side).along(self.size)
+    }
+
+    pub fn goal_width(&self, side: PlayerSide) -> f32 {
+        self.goals
+            .iter()
+            .find(|goal| goal.side == side)
+            .map_or(self.edge_length(side), |goal| goal.width)
+    }
+
+    pub fn paddle_distance(&self, side: PlayerSide) -> f32 {
+        self.paddles
+            .iter()
+            .find(|paddle| paddle.side == side)
+            .map_or(Paddle::EDGE_MARGIN + Paddle::THICKNESS, |paddle| {
+                paddle.distance
+            })
+    }
+
+    /// Walls closing the edge of the `side` on both ends of its goal opening,
+    /// none when the goal is the whole edge.
+    pub fn goal_posts(&self, side: PlayerSide) -> Vec<LayoutRect> {
+        let axis = PaddleAxis::of(side);
+        let edge_length = self.edge_length(side);
+        let post_length = (edge_length - self.goal_width(side)) / 2.0;
+        if post_length <= 0.0 {
+            return Vec::new();
+        }
+
+        // the posts stop the balls before the line where they leave the arena.
+        let across = self.edge(side) - self.edge(side).signum() * Wall::THICKNESS / 2.0;
+        let along = (edge_length - post_length) / 2.0;
+
+        [-along, along]
+            .into_iter()
+            .map(|along| match axis {
+                PaddleAxis::Vertical => LayoutRect::new(
+                    Vec2::new(across, along),
+                    Vec2::new(Wall::THICKNESS, post_length),
+                ),
+                PaddleAxis::Horizontal => LayoutRect::new(
+                    Vec2::new(along, across),
+                    Vec2::new(post_length, Wall::THICKNESS),
+                ),
+            })
+            .collect()
+    }
+}
+
+impl Default for ArenaLayout {
+    fn default() -> Self {
+        Self::classic()
+    }
+}
+
+#[derive(Debug)]
+pub enum LayoutError {
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    Invalid(&'static str),
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Parse(err) => write!(f, "invalid layout file: {err}"),
+            Self::Invalid(reason) => write!(f, "invalid layout: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for LayoutError {}
+
+impl From<io::Error> for LayoutError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<ron::error::SpannedError> for LayoutError {
+    fn from(err: ron::error::SpannedError) -> Self {
+        Self::Parse(err)
+    }
+}
+
+#[derive(Default)]
+pub struct ArenaLayoutLoader;
+
+impl AssetLoader for ArenaLayoutLoader {
+    type Asset = ArenaLayout;
+    type Settings = ();
+    type Error = LayoutError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        ArenaLayout::parse(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[ArenaLayout::EXTENSION]
+    }
+}
+
+/// Layouts available to choose in the main menu.
+#[derive(Resource)]
+pub struct ArenaLayouts {
+    folder: Handle<LoadedFolder>,
+}
+
+impl ArenaLayouts {
+    /// The classic layout followed by the loaded layout files, sorted by name.
+    pub fn get(
+        &self,
+        folders: &Assets<LoadedFolder>,
+        layouts: &Assets<ArenaLayout>,
+    ) -> Vec<ArenaLayout> {
+        let mut loaded: Vec<ArenaLayout> = folders
+            .get(&self.folder)
+            .map(|folder| {
+                folder
+                    .handles
+                    .iter()
+                    .filter_map(|handle| handle.clone().try_typed::<ArenaLayout>().ok())
+                    .filter_map(|handle| layouts.get(&handle).cloned())
+                    .collect()
+            })
+            .unwrap_or_default();
+        loaded.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut all = vec![ArenaLayout::classic()];
+        all.extend(loaded);
+        all
+    }
+
+    /// Layout after the `current` one, going back to the first after the last.
+    pub fn next(
+        &self,
+        current: &ArenaLayout,
+        folders: &Assets<LoadedFolder>,
+        layouts: &Assets<ArenaLayout>,
+    ) -> ArenaLayout {
+        let all = self.get(folders, layouts);
+        let index = all.iter().position(|layout| layout.name == current.name);
+
+        match index {
+            Some(index) => all[(index + 1) % all.len()].clone(),
+            None => all[0].clone(),
+        }
+    }
+}
+
+pub fn load_layouts(asset_server: Res<AssetServer>, mut commands: Commands) {
+    commands.insert_resource(ArenaLayouts {
+        folder: asset_server.load_folder(ArenaLayout::DIRECTORY),
+    });
+}
+
+/// Loads the layout files with the asset system.
+///
+/// The game logic in [`super::plugin`] only uses the [`ArenaLayout`] resource,
+/// so this plugin is only added when the game has a window.
+pub fn plugin(app: &mut App) {
+    app.init_asset::<ArenaLayout>();
+    app.init_asset_loader::<ArenaLayoutLoader>();
+
+    app.add_systems(Startup, load_layouts);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn parse(content: &str) -> Result<ArenaLayout, LayoutError> {
+        ArenaLayout::parse(content.as_bytes())
+    }
+
+    #[test]
+    fn layout_assets_are_valid() {
+        let directory = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("assets")
+            .join(ArenaLayout::DIRECTORY);
+        let mut layouts = 0;
+
+        for entry in fs::read_dir(directory).unwrap() {
+            let path = entry.unwrap().path();
+            if !path.to_string_lossy().ends_with(ArenaLayout::EXTENSION) {
+                continue;
+            }
+
+            if let Err(err) = ArenaLayout::load(&path) {
+                panic!("{}: {err}", path.display());
+            }
+            layouts += 1;
+        }
+
+        assert!(layouts > 0);
+    }
+
+    #[test]
+    fn classic_layout_is_valid() {
+        assert!(ArenaLayout::classic().validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_sizes() {
+        for content in [
+            r#"(name: "Empty", size: (0.0, 50.0))"#,
+            r#"(name: "Negative", size: (100.0, -50.0))"#,
+            r#"(name: "Flat wall", size: (100.0, 50.0), walls: [(center: (0.0, 26.0), size: (102.0, 0.0))])"#,
+            r#"(name: "Far paddle", size: (100.0, 50.0), paddles: [(side: Main, distance: 60.0)])"#,
+        ] {
+            assert!(
+                matches!(parse(content), Err(LayoutError::Invalid(_))),
+                "{content}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_overlapping_goals() {
+        for content in [
+            r#"(name: "Wide goal", size: (100.0, 50.0), goals: [(side: Main, width: 60.0)])"#,
+            r#"(name: "Two goals", size: (100.0, 50.0), goals: [(side: Main, width: 20.0), (side: Main, width: 30.0)])"#,
+        ] {
+            assert!(
+                matches!(parse(content), Err(LayoutError::Invalid(_))),
+                "{content}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        assert!(matches!(
+            parse(r#"(name: "No size")"#),
+            Err(LayoutError::Parse(_))
+        ));
+    }
+}
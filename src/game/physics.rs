@@ -5,7 +5,7 @@ use bevy::{
     ecs::component::Component,
     math::{
         bounding::{Aabb2d, BoundingCircle, BoundingVolume, IntersectsVolume},
-        Vec2,
+        Vec2, Vec2Swizzles,
     },
     transform::components::Transform,
 };
@@ -14,6 +14,16 @@ use bevy::{
 #[require(Transform)]
 pub struct Collider;
 
+impl Collider {
+    /// Bounding box of a collider, sized by its transform scale.
+    pub fn bounding_box(transform: &Transform) -> Aabb2d {
+        Aabb2d::new(
+            transform.translation.truncate(),
+            transform.scale.truncate() * 0.5,
+        )
+    }
+}
+
 #[derive(Clone, Copy, Component)]
 #[require(Transform)]
 pub struct LinearVelocity(pub Vec2);
@@ -104,6 +114,322 @@ pub fn resolve_ball_collision(
     }
 }
 
+/// Maximum number of bounces resolved for a single ball movement step.
+pub const MAX_BALL_BOUNCES: usize = 4;
+
+/// Contact found by sweeping a circle against an axis-aligned bounding box.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SweepHit {
+    /// Fraction of the displacement travelled before the contact, in `[0, 1]`.
+    pub time: f32,
+    /// Normal of the box surface at the contact point.
+    pub normal: Vec2,
+}
+
+/// Contact between the ball and one of the colliders during [`sweep_ball`].
+#[derive(Clone, Copy, Debug)]
+pub struct BallContact<T> {
+    /// Identifier of the collider that was hit.
+    pub collider: T,
+    /// Bounding box of the collider that was hit.
+    pub bounding_box: Aabb2d,
+    /// Ball center at the moment of the contact.
+    pub position: Vec2,
+    /// Normal of the collider surface at the contact point.
+    pub normal: Vec2,
+}
+
+/// Sweeps a circle moving by `displacement` against `aabb`.
+///
+/// Returns the time of impact and the contact normal of the first contact.
+/// A circle that already overlaps the box, or that moves away from it, does not produce a hit.
+pub fn sweep_circle_aabb(
+    center: Vec2,
+    radius: f32,
+    displacement: Vec2,
+    aabb: &Aabb2d,
+) -> Option<SweepHit> {
+    // Sweeping a circle against a box is the same as casting a ray
+    // against the box expanded by the circle radius (with rounded corners).
+    let min = aabb.min - Vec2::splat(radius);
+    let max = aabb.max + Vec2::splat(radius);
+
+    let mut entry = f32::NEG_INFINITY;
+    let mut exit = f32::INFINITY;
+    let mut normal = Vec2::ZERO;
+
+    for axis in 0..2 {
+        let origin = center[axis];
+        let delta = displacement[axis];
+
+        if delta.abs() <= f32::EPSILON {
+            if origin <= min[axis] || origin >= max[axis] {
+                return None;
+            }
+            continue;
+        }
+
+        let mut near = (min[axis] - origin) / delta;
+        let mut far = (max[axis] - origin) / delta;
+        let mut axis_normal = Vec2::ZERO;
+        axis_normal[axis] = -delta.signum();
+
+        if near > far {
+            core::mem::swap(&mut near, &mut far);
+        }
+
+        if near > entry {
+            entry = near;
+            normal = axis_normal;
+        }
+        exit = exit.min(far);
+    }
+
+    // already overlapping, missing the box or hitting it after this step.
+    if entry < 0.0 || entry > exit || entry > 1.0 {
+        return None;
+    }
+
+    let contact = center + displacement * entry;
+    let inside_x = contact.x >= aabb.min.x && contact.x <= aabb.max.x;
+    let inside_y = contact.y >= aabb.min.y && contact.y <= aabb.max.y;
+
+    if inside_x || inside_y {
+        return Some(SweepHit {
+            time: entry,
+            normal,
+        });
+    }
+
+    // the contact is in one of the rounded corners of the expanded box.
+    let corner = Vec2::new(
+        if contact.x < aabb.min.x {
+            aabb.min.x
+        } else {
+            aabb.max.x
+        },
+        if contact.y < aabb.min.y {
+            aabb.min.y
+        } else {
+            aabb.max.y
+        },
+    );
+
+    sweep_circle_point(center, radius, displacement, corner)
+}
+
+/// Sweeps a circle moving by `displacement` against a single `point`.
+fn sweep_circle_point(
+    center: Vec2,
+    radius: f32,
+    displacement: Vec2,
+    point: Vec2,
+) -> Option<SweepHit> {
+    let offset = center - point;
+    let a = displacement.length_squared();
+    let b = 2.0 * offset.dot(displacement);
+    let c = offset.length_squared() - radius * radius;
+
+    if a <= f32::EPSILON || c < 0.0 {
+        return None;
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    let time = (-b - discriminant.sqrt()) / (2.0 * a);
+    if !(0.0..=1.0).contains(&time) {
+        return None;
+    }
+
+    let normal = (center + displacement * time - point).normalize_or_zero();
+
+    Some(SweepHit { time, normal })
+}
+
+/// Reflects `velocity` on a surface with the given `normal`.
+///
+/// The velocity is only reflected when moving against the surface.
+pub fn reflect_velocity(velocity: Vec2, normal: Vec2) -> Vec2 {
+    let approach = velocity.dot(normal);
+    if approach < 0.0 {
+        velocity - 2.0 * approach * normal
+    } else {
+        velocity
+    }
+}
+
+/// Moves the ball by `velocity * delta_time`, bouncing off every collider in its way.
+///
+/// The movement is split at each time of impact, so a fast ball can not pass through thin colliders,
+/// and up to [`MAX_BALL_BOUNCES`] contacts are resolved in the same step.
+/// `on_contact` is called after the velocity is reflected, allowing the caller to change the bounce.
+pub fn sweep_ball<T: Copy>(
+    transform: &mut Transform,
+    velocity: &mut LinearVelocity,
+    radius: f32,
+    delta_time: f32,
+    colliders: &[(T, Aabb2d)],
+    mut on_contact: impl FnMut(&BallContact<T>, &mut LinearVelocity),
+) {
+    let mut remaining_time = delta_time;
+
+    for _ in 0..MAX_BALL_BOUNCES {
+        let center = transform.translation.truncate();
+        let displacement = velocity.0 * remaining_time;
+
+        let closest_hit = colliders
+            .iter()
+            .filter_map(|(collider, bounding_box)| {
+                sweep_circle_aabb(center, radius, displacement, bounding_box)
+                    .map(|hit| (*collider, *bounding_box, hit))
+            })
+            .min_by(|(_, _, a), (_, _, b)| a.time.total_cmp(&b.time));
+
+        let Some((collider, bounding_box, hit)) = closest_hit else {
+            transform.translation += displacement.extend(0.0);
+            return;
+        };
+
+        let position = center + displacement * hit.time;
+        transform.translation = position.extend(transform.translation.z);
+        remaining_time *= 1.0 - hit.time;
+
+        velocity.0 = reflect_velocity(velocity.0, hit.normal);
+
+        let contact = BallContact {
+            collider,
+            bounding_box,
+            position,
+            normal: hit.normal,
+        };
+        on_contact(&contact, velocity);
+    }
+
+    // out of bounces for this step, keep the ball at the last contact.
+}
+
+/// Predicted crossing of the ball with a vertical or horizontal line.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Intercept {
+    /// Ball center when crossing the line.
+    pub point: Vec2,
+    /// Seconds until the ball crosses the line.
+    pub time: f32,
+}
+
+/// Predicts the ball trajectory, bouncing off horizontal walls.
+///
+/// The velocity grows exponentially by `acceleration` every second,
+/// with each component limited by `max_speed`, just like the ball movement.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TrajectoryPredictor {
+    /// Lowest position reachable by the ball center.
+    pub min_y: f32,
+    /// Highest position reachable by the ball center.
+    pub max_y: f32,
+    /// Velocity growth per second, as a fraction of the current velocity.
+    pub acceleration: f32,
+    /// Maximum speed of each velocity component.
+    pub max_speed: f32,
+}
+
+impl TrajectoryPredictor {
+    /// Predicts where and when a ball at `position`, moving with `velocity`, crosses the vertical line at `x`.
+    ///
+    /// Returns `None` if the ball is not moving to the line.
+    pub fn intercept_x(&self, position: Vec2, velocity: Vec2, x: f32) -> Option<Intercept> {
+        let distance = x - position.x;
+        if velocity.x == 0.0 || distance.signum() != velocity.x.signum() {
+            return None;
+        }
+
+        let time = self.time_to_travel(velocity.x.abs(), distance.abs());
+        let free_y = position.y + self.displacement(velocity.y, time);
+
+        Some(Intercept {
+            point: Vec2::new(x, self.fold_y(free_y)),
+            time,
+        })
+    }
+
+    /// Same as [`TrajectoryPredictor::intercept_x`], for the horizontal line at `y`.
+    ///
+    /// The walls are then on the sides, with `min_y` and `max_y` limiting the horizontal position.
+    pub fn intercept_y(&self, position: Vec2, velocity: Vec2, y: f32) -> Option<Intercept> {
+        self.intercept_x(position.yx(), velocity.yx(), y)
+            .map(|intercept| Intercept {
+                point: intercept.point.yx(),
+                time: intercept.time,
+            })
+    }
+
+    /// Seconds until the speed of a component starting at `speed` reaches `max_speed`.
+    fn time_to_max_speed(&self, speed: f32) -> f32 {
+        if speed >= self.max_speed {
+            0.0
+        } else if self.acceleration <= 0.0 {
+            f32::INFINITY
+        } else {
+            (self.max_speed / speed).ln() / self.acceleration
+        }
+    }
+
+    /// Displacement of a velocity component after `time` seconds.
+    fn displacement(&self, velocity: f32, time: f32) -> f32 {
+        let speed = velocity.abs().min(self.max_speed);
+        if speed == 0.0 {
+            return 0.0;
+        }
+
+        let accelerating_time = time.min(self.time_to_max_speed(speed));
+        let accelerating_distance = if self.acceleration > 0.0 {
+            speed * ((self.acceleration * accelerating_time).exp() - 1.0) / self.acceleration
+        } else {
+            speed * accelerating_time
+        };
+        let max_speed_distance = self.max_speed * (time - accelerating_time).max(0.0);
+
+        velocity.signum() * (accelerating_distance + max_speed_distance)
+    }
+
+    /// Seconds to travel `distance` with a component starting at `speed`.
+    fn time_to_travel(&self, speed: f32, distance: f32) -> f32 {
+        let speed = speed.min(self.max_speed);
+
+        if self.acceleration <= 0.0 {
+            return distance / speed;
+        }
+
+        let accelerating_distance = (self.max_speed - speed) / self.acceleration;
+        if distance <= accelerating_distance {
+            (1.0 + self.acceleration * distance / speed).ln() / self.acceleration
+        } else {
+            self.time_to_max_speed(speed) + (distance - accelerating_distance) / self.max_speed
+        }
+    }
+
+    /// Reflects a vertical position into the walls, as many times as needed.
+    fn fold_y(&self, y: f32) -> f32 {
+        let height = self.max_y - self.min_y;
+        if height <= 0.0 {
+            return self.min_y;
+        }
+        if height.is_infinite() {
+            return y;
+        }
+
+        let offset = (y - self.min_y).rem_euclid(2.0 * height);
+        if offset > height {
+            self.max_y - (offset - height)
+        } else {
+            self.min_y + offset
+        }
+    }
+}
+
 // Returns `Some` if `ball` collides with `bounding_box`.
 // The returned `Collision` is the side of `bounding_box` that `ball` hit.
 pub fn ball_collision(ball: &BoundingCircle, bounding_box: &Aabb2d) -> Option<Vec2> {
@@ -116,3 +442,185 @@ pub fn ball_collision(ball: &BoundingCircle, bounding_box: &Aabb2d) -> Option<Ve
 
     Some(offset)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RADIUS: f32 = 1.0;
+
+    fn paddle() -> Aabb2d {
+        Aabb2d::new(Vec2::new(45.0, 0.0), Vec2::new(0.5, 5.0))
+    }
+
+    #[test]
+    fn sweep_hits_the_near_face() {
+        let hit = sweep_circle_aabb(Vec2::ZERO, RADIUS, Vec2::new(100.0, 0.0), &paddle()).unwrap();
+
+        assert!((hit.time - 0.435).abs() < 1e-4);
+        assert_eq!(hit.normal, Vec2::NEG_X);
+    }
+
+    #[test]
+    fn sweep_misses_when_moving_away_or_overlapping() {
+        let away = sweep_circle_aabb(Vec2::ZERO, RADIUS, Vec2::new(-100.0, 0.0), &paddle());
+        let overlapping =
+            sweep_circle_aabb(Vec2::new(45.0, 0.0), RADIUS, Vec2::new(1.0, 0.0), &paddle());
+        let short = sweep_circle_aabb(Vec2::ZERO, RADIUS, Vec2::new(10.0, 0.0), &paddle());
+
+        assert_eq!(away, None);
+        assert_eq!(overlapping, None);
+        assert_eq!(short, None);
+    }
+
+    #[test]
+    fn sweep_hits_a_corner() {
+        let aabb = Aabb2d::new(Vec2::ONE, Vec2::ONE);
+        let hit = sweep_circle_aabb(Vec2::splat(-2.0), RADIUS, Vec2::splat(4.0), &aabb).unwrap();
+
+        // the center reaches the corner at the origin at the distance of the radius.
+        let expected_time = (2.0 - core::f32::consts::FRAC_1_SQRT_2) / 4.0;
+        assert!((hit.time - expected_time).abs() < 1e-4);
+        assert!((hit.normal - Vec2::splat(-core::f32::consts::FRAC_1_SQRT_2)).length() < 1e-4);
+    }
+
+    #[test]
+    fn sweep_passes_by_a_corner() {
+        let aabb = Aabb2d::new(Vec2::ONE, Vec2::ONE);
+        // crossing the corner of the box expanded by the radius, but outside the rounded corner.
+        let hit = sweep_circle_aabb(Vec2::new(-1.5, -0.3), RADIUS, Vec2::new(2.0, -2.0), &aabb);
+
+        assert_eq!(hit, None);
+    }
+
+    #[test]
+    fn fast_ball_bounces_off_a_thin_paddle() {
+        let mut transform = Transform::from_xyz(0.0, 0.0, 1.0);
+        let mut velocity = LinearVelocity(Vec2::new(1000.0, 0.0));
+        let mut contacts = Vec::new();
+
+        sweep_ball(
+            &mut transform,
+            &mut velocity,
+            RADIUS,
+            0.1,
+            &[(1, paddle())],
+            |contact, _| contacts.push(contact.collider),
+        );
+
+        assert_eq!(contacts, [1]);
+        assert_eq!(velocity.0, Vec2::new(-1000.0, 0.0));
+        assert!(transform.translation.x < 45.0 - 0.5 - RADIUS);
+        assert_eq!(transform.translation.z, 1.0);
+    }
+
+    #[test]
+    fn bounces_are_capped_in_a_step() {
+        let walls = [
+            (0, Aabb2d::new(Vec2::new(-2.0, 0.0), Vec2::new(0.5, 10.0))),
+            (1, Aabb2d::new(Vec2::new(2.0, 0.0), Vec2::new(0.5, 10.0))),
+        ];
+        let mut transform = Transform::default();
+        let mut velocity = LinearVelocity(Vec2::new(1000.0, 0.0));
+        let mut contacts = 0;
+
+        sweep_ball(
+            &mut transform,
+            &mut velocity,
+            RADIUS,
+            1.0,
+            &walls,
+            |_, _| contacts += 1,
+        );
+
+        assert_eq!(contacts, MAX_BALL_BOUNCES);
+        // kept at the last contact, between the walls.
+        assert!(transform.translation.x.abs() <= 0.5 + 1e-4);
+    }
+
+    #[test]
+    fn reflects_only_against_the_surface() {
+        assert_eq!(
+            reflect_velocity(Vec2::new(3.0, 1.0), Vec2::NEG_X),
+            Vec2::new(-3.0, 1.0)
+        );
+        assert_eq!(
+            reflect_velocity(Vec2::new(-3.0, 1.0), Vec2::NEG_X),
+            Vec2::new(-3.0, 1.0)
+        );
+    }
+
+    fn predictor(acceleration: f32) -> TrajectoryPredictor {
+        TrajectoryPredictor {
+            min_y: -10.0,
+            max_y: 10.0,
+            acceleration,
+            max_speed: 100.0,
+        }
+    }
+
+    #[test]
+    fn predicts_the_intercept_without_bounces() {
+        let intercept = predictor(0.0)
+            .intercept_x(Vec2::ZERO, Vec2::new(10.0, 2.0), 30.0)
+            .unwrap();
+
+        assert!((intercept.time - 3.0).abs() < 1e-4);
+        assert!((intercept.point - Vec2::new(30.0, 6.0)).length() < 1e-4);
+    }
+
+    #[test]
+    fn predicts_the_intercept_after_wall_bounces() {
+        let predictor = predictor(0.0);
+
+        // bounces off the top wall at 1 second, reaching the bottom wall at 3 seconds.
+        let intercept = predictor
+            .intercept_x(Vec2::ZERO, Vec2::new(10.0, 10.0), 30.0)
+            .unwrap();
+        assert!((intercept.point.y - -10.0).abs() < 1e-4);
+
+        // and the top wall again at 5 seconds, coming back to the center at 6 seconds.
+        let intercept = predictor
+            .intercept_x(Vec2::ZERO, Vec2::new(10.0, 10.0), 60.0)
+            .unwrap();
+        assert!(intercept.point.y.abs() < 1e-4);
+    }
+
+    #[test]
+    fn no_intercept_when_moving_away() {
+        let predictor = predictor(0.0);
+
+        assert_eq!(
+            predictor.intercept_x(Vec2::ZERO, Vec2::new(-10.0, 0.0), 30.0),
+            None
+        );
+        assert_eq!(
+            predictor.intercept_x(Vec2::ZERO, Vec2::new(0.0, 10.0), 30.0),
+            None
+        );
+    }
+
+    #[test]
+    fn accelerating_ball_travels_the_predicted_distance() {
+        let predictor = predictor(0.5);
+
+        for distance in [5.0, 50.0, 500.0] {
+            let time = predictor.time_to_travel(10.0, distance);
+            assert!((predictor.displacement(10.0, time) - distance).abs() < 1e-2);
+        }
+        // past the max speed, the ball moves at the max speed.
+        let time = predictor.time_to_max_speed(10.0);
+        let distance = predictor.displacement(10.0, time);
+        assert!((predictor.displacement(10.0, time + 1.0) - distance - 100.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn predicts_the_intercept_of_a_horizontal_line() {
+        let intercept = predictor(0.0)
+            .intercept_y(Vec2::ZERO, Vec2::new(2.0, 10.0), 30.0)
+            .unwrap();
+
+        assert!((intercept.time - 3.0).abs() < 1e-4);
+        assert!((intercept.point - Vec2::new(6.0, 30.0)).length() < 1e-4);
+    }
+}
@@ -2,141 +2,504 @@ use bevy::prelude::*;
 
 use core::fmt;
 
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
 use crate::{
-    game::arena::{ArenaDirection, Paddle},
-    settings::input::{
-        GamepadInputSettings, KeyboardInputSettings, GAMEPAD_SETTINGS, MAIN_PLAYER_KEYBOARD,
-        SECOND_PLAYER_KEYBOARD,
+    game::{
+        arena::{ArenaDirection, Paddle, PaddleAxis},
+        physics::Intercept,
+        resource::FourPlayers,
     },
+    settings::input::{GamepadInputSettings, InputSettings, KeyboardInputSettings},
 };
 
 /// All players of the game
-#[derive(Clone, Copy, Default, PartialEq, Eq)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum PlayerType {
     #[default]
     Main = 1,
     Second = 2,
     AI = 3,
+    /// Third human player, in the four players mode.
+    Third = 4,
+    /// Fourth human player, in the four players mode.
+    Fourth = 5,
+    /// Player of another game instance, connected over the network.
+    Remote = 6,
 }
 
 impl PlayerType {
-    pub const fn arena_direction(&self) -> ArenaDirection {
-        match *self {
-            Self::Main => ArenaDirection::Left,
-            Self::Second => ArenaDirection::Right,
-            Self::AI => ArenaDirection::Right,
-        }
-    }
-
     pub const fn as_str(&self) -> &'static str {
         match *self {
             Self::Main => "main",
             Self::Second => "second",
             Self::AI => "AI",
+            Self::Third => "third",
+            Self::Fourth => "fourth",
+            Self::Remote => "remote",
+        }
+    }
+
+    /// Side of the input settings and device of a human player.
+    pub const fn input_side(&self) -> Option<PlayerSide> {
+        match *self {
+            Self::Main => Some(PlayerSide::Main),
+            Self::Second => Some(PlayerSide::Other),
+            Self::Third => Some(PlayerSide::Top),
+            Self::Fourth => Some(PlayerSide::Bottom),
+            Self::AI | Self::Remote => None,
         }
     }
 }
 
+impl fmt::Display for PlayerSide {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 impl fmt::Display for PlayerType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str(self.as_str())
     }
 }
 
-#[derive(Clone, Copy, Default, PartialEq, Eq)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum PlayerSide {
     /// Main player.
     ///
     /// Is at the left side of the arena.
-    /// Always controlled by the main player.
+    /// Can be controlled by the main player or the AI.
     #[default]
     Main = 1,
     /// Other player.
     ///
     /// Is at the right side of the arena.
-    /// Can be controlled by the second player or the AI.
+    /// Can be controlled by the second player, the main player (playing against an AI on the left) or the AI.
     Other = 2,
+    /// Top player, in the four players mode.
+    ///
+    /// Can be controlled by the third player or the AI.
+    Top = 3,
+    /// Bottom player, in the four players mode.
+    ///
+    /// Can be controlled by the fourth player or the AI.
+    Bottom = 4,
 }
 
 impl PlayerSide {
-    #[expect(unused)]
+    /// Sides of a classic match.
+    pub const CLASSIC: [Self; 2] = [Self::Main, Self::Other];
+    /// Sides of a match in the four players mode.
+    pub const ALL: [Self; 4] = [Self::Main, Self::Other, Self::Top, Self::Bottom];
+
+    pub const fn as_str(&self) -> &'static str {
+        match *self {
+            Self::Main => "main",
+            Self::Other => "other",
+            Self::Top => "top",
+            Self::Bottom => "bottom",
+        }
+    }
+
+    /// Edge of the arena defended by the side.
     pub const fn arena_direction(&self) -> ArenaDirection {
         match *self {
             Self::Main => ArenaDirection::Left,
             Self::Other => ArenaDirection::Right,
+            Self::Top => ArenaDirection::Up,
+            Self::Bottom => ArenaDirection::Down,
+        }
+    }
+
+    /// Side defending the opposite edge of the arena.
+    pub const fn opponent(&self) -> Self {
+        match *self {
+            Self::Main => Self::Other,
+            Self::Other => Self::Main,
+            Self::Top => Self::Bottom,
+            Self::Bottom => Self::Top,
         }
     }
 
-    pub const fn to_player_type(self, opponent: SecondPlayerType) -> PlayerType {
-        match (self, opponent) {
-            (Self::Main, _) => PlayerType::Main,
-            (Self::Other, SecondPlayerType::Player) => PlayerType::Second,
-            (Self::Other, SecondPlayerType::AI) => PlayerType::AI,
+    pub const fn to_player_type(
+        self,
+        main: MainPlayerType,
+        opponent: SecondPlayerType,
+        four_players: FourPlayers,
+    ) -> PlayerType {
+        match (self, main, opponent) {
+            (Self::Main, MainPlayerType::Player, _) => PlayerType::Main,
+            (Self::Main, MainPlayerType::AI, _) => PlayerType::AI,
+            (Self::Other, MainPlayerType::Player, SecondPlayerType::Player) => PlayerType::Second,
+            // the main player plays on the right side against the AI.
+            (Self::Other, MainPlayerType::AI, SecondPlayerType::Player) => PlayerType::Main,
+            (Self::Other, _, SecondPlayerType::AI) => PlayerType::AI,
+            (Self::Other, _, SecondPlayerType::Remote) => PlayerType::Remote,
+            (Self::Top, _, _) if four_players.top.is_player() => PlayerType::Third,
+            (Self::Bottom, _, _) if four_players.bottom.is_player() => PlayerType::Fourth,
+            (Self::Top | Self::Bottom, _, _) => PlayerType::AI,
         }
     }
 }
 
-#[derive(Clone, Copy, Default, PartialEq, Eq)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum MainPlayerType {
+    #[default]
+    Player = 1,
+    AI = 3,
+}
+
+impl MainPlayerType {
+    pub fn is_ai(&self) -> bool {
+        *self == Self::AI
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum SecondPlayerType {
     #[default]
     Player = 2,
     AI = 3,
+    /// Player joining the match from another game instance, see [`crate::network`].
+    Remote = 6,
 }
 
 impl SecondPlayerType {
-    pub const fn change_opponent(self) -> Self {
-        match self {
-            Self::Player => Self::AI,
-            Self::AI => Self::Player,
-        }
-    }
-
-    #[expect(unused)]
     pub fn is_player(&self) -> bool {
         *self == Self::Player
     }
 
-    #[expect(unused)]
     pub fn is_ai(&self) -> bool {
         *self == Self::AI
     }
+
+    pub fn is_remote(&self) -> bool {
+        *self == Self::Remote
+    }
+}
+
+/// Player of the top or bottom paddle, in the four players mode.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum ExtraPlayerType {
+    Player = 4,
+    #[default]
+    AI = 3,
+}
+
+impl ExtraPlayerType {
+    pub const fn is_player(&self) -> bool {
+        matches!(*self, Self::Player)
+    }
+
+    pub const fn toggle(self) -> Self {
+        match self {
+            Self::Player => Self::AI,
+            Self::AI => Self::Player,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Default, Component)]
-#[require(Paddle)]
+#[require(Paddle, PaddleInput)]
 pub struct Player {
     kind: PlayerSide,
+    /// Side whose input settings control the paddle.
+    input: PlayerSide,
 }
 
 impl Player {
-    pub const fn new_main() -> Self {
-        Self {
-            kind: PlayerSide::Main,
+    pub const fn new(side: PlayerSide, player: PlayerType) -> Self {
+        let input = match player.input_side() {
+            Some(input) => input,
+            None => PlayerSide::Main,
+        };
+
+        Self { kind: side, input }
+    }
+
+    pub const fn gamepad_input<'a>(&self, settings: &'a InputSettings) -> &'a GamepadInputSettings {
+        settings.gamepad(self.input)
+    }
+
+    pub const fn get_side(&self) -> PlayerSide {
+        self.kind
+    }
+
+    pub const fn paddle_axis(&self) -> PaddleAxis {
+        PaddleAxis::of(self.kind)
+    }
+
+    pub const fn get_input_side(&self) -> PlayerSide {
+        self.input
+    }
+}
+
+/// Paddle movement requested by a player for the current fixed update.
+///
+/// Goes from `-1.0` (full speed down) to `1.0` (full speed up),
+/// or from left to right for the top and bottom paddles.
+#[derive(Clone, Copy, Default, PartialEq, Debug, Component)]
+pub struct PaddleInput(pub f32);
+
+impl PaddleInput {
+    pub const fn from_buttons(up: bool, down: bool) -> Self {
+        match (up, down) {
+            (true, false) => Self(1.0),
+            (false, true) => Self(-1.0),
+            _ => Self(0.0),
+        }
+    }
+
+    pub fn from_keyboard(
+        keyboard: &ButtonInput<KeyCode>,
+        settings: &KeyboardInputSettings,
+    ) -> Self {
+        Self::from_buttons(
+            keyboard.pressed(settings.paddle_up),
+            keyboard.pressed(settings.paddle_down),
+        )
+    }
+
+    /// The buttons move the paddle at full speed, and the axis only when no button is pressed.
+    pub fn from_gamepad(gamepad: &Gamepad, settings: &GamepadInputSettings) -> Self {
+        let input = Self::from_buttons(
+            gamepad.pressed(settings.paddle_up),
+            gamepad.pressed(settings.paddle_down),
+        );
+        if input.0 != 0.0 {
+            return input;
+        }
+
+        let axis = gamepad.get(settings.paddle_axis).unwrap_or_default();
+        Self(settings.axis_input(axis))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Component)]
+#[require(Player, AIController)]
+pub struct PlayerAI;
+
+/// Paddle moved by the inputs received from the remote player.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Component)]
+#[require(Player)]
+pub struct PlayerRemote;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize, Resource)]
+pub enum AIDifficulty {
+    Easy = 1,
+    #[default]
+    Normal = 2,
+    Hard = 3,
+    Perfect = 4,
+}
+
+impl AIDifficulty {
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Easy => Self::Normal,
+            Self::Normal => Self::Hard,
+            Self::Hard => Self::Perfect,
+            Self::Perfect => Self::Easy,
+        }
+    }
+
+    pub const fn profile(&self) -> AIProfile {
+        match *self {
+            Self::Easy => AIProfile {
+                reaction_time: 0.45,
+                aim_error: 6.0,
+                max_speed: 30.0,
+                return_to_center: false,
+            },
+            Self::Normal => AIProfile {
+                reaction_time: 0.25,
+                aim_error: 3.5,
+                max_speed: 40.0,
+                return_to_center: true,
+            },
+            Self::Hard => AIProfile {
+                reaction_time: 0.1,
+                aim_error: 1.5,
+                max_speed: Paddle::VELOCITY,
+                return_to_center: true,
+            },
+            Self::Perfect => AIProfile {
+                reaction_time: 0.0,
+                aim_error: 0.0,
+                max_speed: Paddle::VELOCITY * 1.5,
+                return_to_center: true,
+            },
+        }
+    }
+
+    pub const fn as_str(&self) -> &'static str {
+        match *self {
+            Self::Easy => "easy",
+            Self::Normal => "normal",
+            Self::Hard => "hard",
+            Self::Perfect => "perfect",
         }
     }
+}
+
+impl fmt::Display for AIDifficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Behaviour of the AI player for a difficulty level.
+#[derive(Clone, Copy, PartialEq)]
+pub struct AIProfile {
+    /// Seconds between each time the AI looks at the ball.
+    pub reaction_time: f32,
+    /// Maximum error, in arena units, added to the predicted ball position.
+    pub aim_error: f32,
+    /// Maximum paddle speed, in arena units per second.
+    pub max_speed: f32,
+    /// Move back to the center of the arena while the ball goes to the opponent.
+    pub return_to_center: bool,
+}
+
+/// State of the AI player decisions.
+#[derive(Clone, Copy, PartialEq, Component)]
+pub struct AIController {
+    profile: AIProfile,
+    /// Position the paddle is moving to.
+    target: Option<f32>,
+    /// Error applied to the predicted ball position while the ball approaches.
+    aim_offset: f32,
+    /// Seconds since the last time the AI looked at the ball.
+    since_reaction: f32,
+    /// Whether the ball was approaching the paddle when the AI last looked at it.
+    ball_approaching: bool,
+}
 
-    pub const fn new_second() -> Self {
+impl AIController {
+    /// Seconds to the intercept from which the aim error is fully applied.
+    ///
+    /// Closer balls are easier to predict, reducing the aim error.
+    pub const AIM_ERROR_HORIZON: f32 = 1.0;
+
+    pub const fn new(difficulty: AIDifficulty) -> Self {
         Self {
-            kind: PlayerSide::Other,
+            profile: difficulty.profile(),
+            target: None,
+            aim_offset: 0.0,
+            since_reaction: 0.0,
+            ball_approaching: false,
         }
     }
 
-    pub const fn keyboard_input(&self) -> KeyboardInputSettings {
-        match self.kind {
-            PlayerSide::Main => MAIN_PLAYER_KEYBOARD,
-            PlayerSide::Other => SECOND_PLAYER_KEYBOARD,
+    pub const fn profile(&self) -> &AIProfile {
+        &self.profile
+    }
+
+    pub const fn target(&self) -> Option<f32> {
+        self.target
+    }
+
+    /// Advances the reaction timer, returning `true` when the AI must look at the ball again.
+    pub fn react(&mut self, delta_time: f32) -> bool {
+        self.since_reaction += delta_time;
+        if self.since_reaction < self.profile.reaction_time {
+            return false;
         }
+
+        self.since_reaction = 0.0;
+        true
     }
 
-    pub const fn gamepad_input(&self) -> GamepadInputSettings {
-        GAMEPAD_SETTINGS
+    /// Updates the paddle target from what the AI sees of the ball.
+    ///
+    /// `intercept` is where the ball reaches the paddle moving along the `axis`, if the ball is approaching it.
+    pub fn update_target<R: Rng + ?Sized>(
+        &mut self,
+        intercept: Option<Intercept>,
+        axis: PaddleAxis,
+        rng: &mut R,
+    ) {
+        let approaching = intercept.is_some();
+
+        if approaching && !self.ball_approaching {
+            self.aim_offset = if self.profile.aim_error > 0.0 {
+                rng.gen_range(-self.profile.aim_error..=self.profile.aim_error)
+            } else {
+                0.0
+            };
+        }
+        self.ball_approaching = approaching;
+
+        self.target = match intercept {
+            Some(intercept) => {
+                let uncertainty = (intercept.time / Self::AIM_ERROR_HORIZON).min(1.0);
+                Some(axis.along(intercept.point) + self.aim_offset * uncertainty)
+            }
+            None if self.profile.return_to_center => Some(0.0),
+            None => None,
+        };
     }
+}
 
-    pub const fn get_side(&self) -> PlayerSide {
-        self.kind
+impl Default for AIController {
+    fn default() -> Self {
+        Self::new(AIDifficulty::default())
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Default, Component)]
-#[require(Player)]
-pub struct PlayerAI;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gamepad(stick: f32) -> Gamepad {
+        let mut gamepad = Gamepad::default();
+        gamepad.analog_mut().set(GamepadAxis::LeftStickY, stick);
+        gamepad
+    }
+
+    #[test]
+    fn stick_in_the_deadzone_does_not_move_the_paddle() {
+        let settings = GamepadInputSettings::default_settings();
+
+        assert_eq!(
+            PaddleInput::from_gamepad(&gamepad(0.1), &settings),
+            PaddleInput(0.0)
+        );
+        assert_eq!(
+            PaddleInput::from_gamepad(&gamepad(-0.1), &settings),
+            PaddleInput(0.0)
+        );
+    }
+
+    #[test]
+    fn full_stick_deflection_moves_the_paddle_at_full_speed() {
+        let settings = GamepadInputSettings::default_settings();
+        let mut transform = Transform::default();
+
+        let input = PaddleInput::from_gamepad(&gamepad(1.0), &settings);
+        Paddle::move_by_input(&mut transform, PaddleAxis::Vertical, input.0, 1.0, 1.0);
+
+        assert!((transform.translation.y - Paddle::VELOCITY).abs() < 1e-4);
+    }
+
+    #[test]
+    fn dpad_overrides_a_centred_stick() {
+        let settings = GamepadInputSettings::default_settings();
+        let mut gamepad = gamepad(0.0);
+
+        gamepad.digital_mut().press(GamepadButton::DPadDown);
+        assert_eq!(
+            PaddleInput::from_gamepad(&gamepad, &settings),
+            PaddleInput(-1.0)
+        );
+
+        // and the stick pushed the other way.
+        gamepad.analog_mut().set(GamepadAxis::LeftStickY, 0.5);
+        assert_eq!(
+            PaddleInput::from_gamepad(&gamepad, &settings),
+            PaddleInput(-1.0)
+        );
+    }
+}
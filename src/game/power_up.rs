@@ -0,0 +1,233 @@
+use bevy::{math::bounding::IntersectsVolume, prelude::*};
+use rand::{
+    distributions::{Distribution, Standard},
+    Rng,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    arena::{Ball, PaddleAxis, Wall},
+    layout::ArenaLayout,
+    physics::Collider,
+    player::PlayerSide,
+};
+
+/// Effect given to a paddle by a [`PowerUp`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum PaddleEffect {
+    /// Longer paddle.
+    Big = 0,
+    /// Faster paddle movement.
+    Fast = 1,
+    /// Slower paddle movement, given to the opponents of the player collecting it.
+    Slow = 2,
+    /// Wall behind the goal of the paddle.
+    Shield = 3,
+    /// The balls hit by the paddle bend back across their path.
+    Curve = 4,
+}
+
+impl PaddleEffect {
+    pub const ALL: [Self; 5] = [Self::Big, Self::Fast, Self::Slow, Self::Shield, Self::Curve];
+
+    pub const BIG_LENGTH_SCALE: f32 = 1.5;
+    pub const FAST_SPEED_SCALE: f32 = 1.5;
+    pub const SLOW_SPEED_SCALE: f32 = 0.6;
+
+    pub const fn seconds(&self) -> f32 {
+        match *self {
+            Self::Big | Self::Fast | Self::Curve => 10.0,
+            Self::Slow => 6.0,
+            Self::Shield => 8.0,
+        }
+    }
+
+    pub const fn as_str(&self) -> &'static str {
+        match *self {
+            Self::Big => "Big",
+            Self::Fast => "Fast",
+            Self::Slow => "Slow",
+            Self::Shield => "Shield",
+            Self::Curve => "Curve",
+        }
+    }
+
+    pub const fn color(&self) -> Color {
+        match *self {
+            Self::Big => Color::srgb(0.3, 0.8, 0.3),
+            Self::Fast => Color::srgb(0.3, 0.6, 1.0),
+            Self::Slow => Color::srgb(1.0, 0.4, 0.3),
+            Self::Shield => Color::srgb(1.0, 0.8, 0.2),
+            Self::Curve => Color::srgb(0.8, 0.4, 1.0),
+        }
+    }
+
+    /// Whether the effect goes to the opponents of the player collecting it.
+    pub const fn targets_opponents(&self) -> bool {
+        matches!(*self, Self::Slow)
+    }
+}
+
+impl Distribution<PaddleEffect> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> PaddleEffect {
+        PaddleEffect::ALL[rng.gen_range(0..PaddleEffect::ALL.len())]
+    }
+}
+
+/// Effects active on a paddle, with their remaining seconds.
+///
+/// Collecting an active effect again restarts its duration, and different effects combine,
+/// such as a fast paddle that is also slowed.
+#[derive(Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize, Component)]
+pub struct PaddleEffects {
+    remaining: [f32; PaddleEffect::ALL.len()],
+}
+
+impl PaddleEffects {
+    pub fn add(&mut self, effect: PaddleEffect) {
+        self.remaining[effect as usize] = effect.seconds();
+    }
+
+    pub fn tick(&mut self, delta_time: f32) {
+        for remaining in &mut self.remaining {
+            *remaining = (*remaining - delta_time).max(0.0);
+        }
+    }
+
+    pub const fn remaining(&self, effect: PaddleEffect) -> f32 {
+        self.remaining[effect as usize]
+    }
+
+    pub const fn is_active(&self, effect: PaddleEffect) -> bool {
+        self.remaining(effect) > 0.0
+    }
+
+    /// Active effects, with their remaining seconds.
+    pub fn active(&self) -> impl Iterator<Item = (PaddleEffect, f32)> + '_ {
+        PaddleEffect::ALL
+            .into_iter()
+            .filter(|effect| self.is_active(*effect))
+            .map(|effect| (effect, self.remaining(effect)))
+    }
+
+    pub const fn length_scale(&self) -> f32 {
+        if self.is_active(PaddleEffect::Big) {
+            PaddleEffect::BIG_LENGTH_SCALE
+        } else {
+            1.0
+        }
+    }
+
+    pub const fn speed_scale(&self) -> f32 {
+        let mut scale = 1.0;
+        if self.is_active(PaddleEffect::Fast) {
+            scale *= PaddleEffect::FAST_SPEED_SCALE;
+        }
+        if self.is_active(PaddleEffect::Slow) {
+            scale *= PaddleEffect::SLOW_SPEED_SCALE;
+        }
+        scale
+    }
+}
+
+/// Collectible in the middle of the arena, given to the last player who hit the ball passing through it.
+///
+/// It has no [`Collider`], so the balls are not deflected by it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Component)]
+#[require(Transform, Visibility)]
+pub struct PowerUp(pub PaddleEffect);
+
+impl PowerUp {
+    pub const Z_INDEX: f32 = 0.5;
+    pub const SIZE: f32 = 3.0;
+    /// Most power-ups in the arena at once.
+    pub const MAX_POWER_UPS: usize = 2;
+    pub const SPAWN_SECONDS: u32 = 6;
+
+    /// Half size of the area in the middle of the `arena` where the power-ups spawn.
+    pub const fn spawn_area(arena: Vec2) -> Vec2 {
+        Vec2::new(arena.x / 6.0, arena.y / 4.0)
+    }
+
+    pub fn random_transform<R: Rng + ?Sized>(rng: &mut R, arena: Vec2) -> Transform {
+        let spawn_area = Self::spawn_area(arena);
+        let x = rng.gen_range(-1.0..=1.0) * spawn_area.x;
+        let y = rng.gen_range(-1.0..=1.0) * spawn_area.y;
+
+        Self::transform(Vec2::new(x, y))
+    }
+
+    pub const fn transform(position: Vec2) -> Transform {
+        Transform::from_xyz(position.x, position.y, Self::Z_INDEX).with_scale(Vec3::new(
+            Self::SIZE,
+            Self::SIZE,
+            1.0,
+        ))
+    }
+}
+
+/// Wall behind the goal of a side, while its [`PaddleEffect::Shield`] is active.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Component)]
+#[require(Transform, Visibility)]
+pub struct Shield(pub PlayerSide);
+
+impl Shield {
+    pub const Z_INDEX: f32 = 0.5;
+    pub const THICKNESS: f32 = 1.0;
+
+    /// Covers the whole edge, just before the line where the ball leaves the arena.
+    pub fn transform(side: PlayerSide, layout: &ArenaLayout) -> Transform {
+        let edge = layout.edge(side);
+        let across = edge - edge.signum() * Wall::THICKNESS;
+
+        match PaddleAxis::of(side) {
+            PaddleAxis::Vertical => Transform::from_xyz(across, 0.0, Self::Z_INDEX)
+                .with_scale(Vec3::new(Self::THICKNESS, layout.size.y, 1.0)),
+            PaddleAxis::Horizontal => Transform::from_xyz(0.0, across, Self::Z_INDEX)
+                .with_scale(Vec3::new(layout.size.x, Self::THICKNESS, 1.0)),
+        }
+    }
+}
+
+/// Bend of a ball hit by a paddle with the [`PaddleEffect::Curve`] effect.
+#[derive(Clone, Copy, Default, PartialEq, Debug, Component)]
+pub struct BallCurve {
+    /// Rotation of the ball velocity, in radians per second.
+    pub rate: f32,
+    pub remaining: f32,
+}
+
+impl BallCurve {
+    pub const RATE: f32 = 1.0;
+    pub const SECONDS: f32 = 1.0;
+
+    /// Curve bending the ball `velocity` back along the paddle `axis`,
+    /// none for a ball hit straight.
+    pub fn for_shot(velocity: Vec2, axis: PaddleAxis) -> Self {
+        let along = axis.along(velocity) * axis.along(velocity.perp());
+        if along == 0.0 {
+            return Self::default();
+        }
+
+        Self {
+            rate: -Self::RATE * along.signum(),
+            remaining: Self::SECONDS,
+        }
+    }
+
+    /// Rotates the ball `velocity` while the curve lasts.
+    pub fn apply(&mut self, velocity: &mut Vec2, delta_time: f32) {
+        if self.remaining <= 0.0 {
+            return;
+        }
+
+        let time = delta_time.min(self.remaining);
+        *velocity = Vec2::from_angle(self.rate * time).rotate(*velocity);
+        self.remaining -= time;
+    }
+}
+
+/// Whether the ball passes through the power-up.
+pub fn ball_touches_power_up(ball: &Transform, power_up: &Transform) -> bool {
+    Ball::bounding_circle(ball).intersects(&Collider::bounding_box(power_up))
+}
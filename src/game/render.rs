@@ -0,0 +1,163 @@
+use bevy::prelude::*;
+
+use super::{
+    arena::{Arena, Ball, Obstacle, Paddle, Wall},
+    power_up::{PaddleEffect, PowerUp, Shield},
+};
+
+#[derive(Resource)]
+pub struct CommonMesh {
+    quad: Handle<Mesh>,
+    ball: Handle<Mesh>,
+}
+
+impl CommonMesh {
+    pub fn quad(&self) -> Handle<Mesh> {
+        self.quad.clone()
+    }
+
+    pub fn ball(&self) -> Handle<Mesh> {
+        self.ball.clone()
+    }
+}
+
+impl FromWorld for CommonMesh {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let quad = meshes.add(Rectangle::from_size(Vec2::ONE));
+        let ball = meshes.add(Ball::primitive());
+        Self { quad, ball }
+    }
+}
+
+#[derive(Resource)]
+pub struct CommonMaterial {
+    arena: Handle<ColorMaterial>,
+    paddle: Handle<ColorMaterial>,
+    ball: Handle<ColorMaterial>,
+    /// Colors of the power-ups, by [`PaddleEffect`].
+    effects: [Handle<ColorMaterial>; PaddleEffect::ALL.len()],
+}
+
+impl CommonMaterial {
+    pub fn arena(&self) -> Handle<ColorMaterial> {
+        self.arena.clone()
+    }
+
+    pub fn paddle(&self) -> Handle<ColorMaterial> {
+        self.paddle.clone()
+    }
+
+    pub fn ball(&self) -> Handle<ColorMaterial> {
+        self.ball.clone()
+    }
+
+    pub fn effect(&self, effect: PaddleEffect) -> Handle<ColorMaterial> {
+        self.effects[effect as usize].clone()
+    }
+}
+
+impl FromWorld for CommonMaterial {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+        let arena = materials.add(Arena::COLOR);
+        let paddle = materials.add(Paddle::COLOR);
+        let ball = materials.add(Ball::COLOR);
+        let effects = PaddleEffect::ALL.map(|effect| materials.add(effect.color()));
+        Self {
+            arena,
+            paddle,
+            ball,
+            effects,
+        }
+    }
+}
+
+pub fn insert_wall_mesh(
+    trigger: Trigger<OnAdd, Wall>,
+    meshes: Res<CommonMesh>,
+    materials: Res<CommonMaterial>,
+    mut commands: Commands,
+) {
+    commands
+        .entity(trigger.entity())
+        .insert((Mesh2d(meshes.quad()), MeshMaterial2d(materials.arena())));
+}
+
+pub fn insert_obstacle_mesh(
+    trigger: Trigger<OnAdd, Obstacle>,
+    meshes: Res<CommonMesh>,
+    materials: Res<CommonMaterial>,
+    mut commands: Commands,
+) {
+    commands
+        .entity(trigger.entity())
+        .insert((Mesh2d(meshes.quad()), MeshMaterial2d(materials.arena())));
+}
+
+pub fn insert_paddle_mesh(
+    trigger: Trigger<OnAdd, Paddle>,
+    meshes: Res<CommonMesh>,
+    materials: Res<CommonMaterial>,
+    mut commands: Commands,
+) {
+    commands
+        .entity(trigger.entity())
+        .insert((Mesh2d(meshes.quad()), MeshMaterial2d(materials.paddle())));
+}
+
+pub fn insert_ball_mesh(
+    trigger: Trigger<OnAdd, Ball>,
+    meshes: Res<CommonMesh>,
+    materials: Res<CommonMaterial>,
+    mut commands: Commands,
+) {
+    commands
+        .entity(trigger.entity())
+        .insert((Mesh2d(meshes.ball()), MeshMaterial2d(materials.ball())));
+}
+
+pub fn insert_power_up_mesh(
+    trigger: Trigger<OnAdd, PowerUp>,
+    power_ups: Query<&PowerUp>,
+    meshes: Res<CommonMesh>,
+    materials: Res<CommonMaterial>,
+    mut commands: Commands,
+) {
+    let Ok(power_up) = power_ups.get(trigger.entity()) else {
+        return;
+    };
+
+    commands.entity(trigger.entity()).insert((
+        Mesh2d(meshes.quad()),
+        MeshMaterial2d(materials.effect(power_up.0)),
+    ));
+}
+
+pub fn insert_shield_mesh(
+    trigger: Trigger<OnAdd, Shield>,
+    meshes: Res<CommonMesh>,
+    materials: Res<CommonMaterial>,
+    mut commands: Commands,
+) {
+    commands.entity(trigger.entity()).insert((
+        Mesh2d(meshes.quad()),
+        MeshMaterial2d(materials.effect(PaddleEffect::Shield)),
+    ));
+}
+
+/// Renders the game entities.
+///
+/// The game logic in [`super::plugin`] does not depend on rendering,
+/// so this plugin is only added when the game has a window.
+pub fn plugin(app: &mut App) {
+    app.init_resource::<CommonMesh>();
+    app.init_resource::<CommonMaterial>();
+
+    app.add_observer(insert_wall_mesh);
+    app.add_observer(insert_obstacle_mesh);
+    app.add_observer(insert_paddle_mesh);
+    app.add_observer(insert_ball_mesh);
+    app.add_observer(insert_power_up_mesh);
+    app.add_observer(insert_shield_mesh);
+}
@@ -1,91 +1,168 @@
-use core::time::Duration;
+use core::f32::consts::FRAC_PI_3;
+use core::{str::FromStr, time::Duration};
 
 use bevy::{
-    asset::{Assets, Handle},
-    ecs::{entity::Entity, system::Resource, world::FromWorld},
-    math::{primitives::Rectangle, Vec2},
-    render::mesh::Mesh,
+    ecs::{entity::Entity, system::Resource},
+    math::{Vec2, Vec2Swizzles},
     time::{Timer, TimerMode},
 };
+use rand::{rngs::StdRng, Error, Rng, RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::game::{
+    arena::PaddleAxis,
+    physics::LinearVelocity,
+    player::{ExtraPlayerType, MainPlayerType, Player, PlayerSide, SecondPlayerType},
+    power_up::PowerUp,
+};
 
-use crate::game::player::{Player, PlayerSide, PlayerType, SecondPlayerType};
+/// Device controlling a human player.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PlayerDevice {
+    /// Half of the keyboard, using the key bindings of the side.
+    Keyboard(PlayerSide),
+    Gamepad(Entity),
+}
 
-#[derive(Clone, PartialEq, Eq, Default, Resource)]
-pub struct UserGamepad {
-    main: Option<Entity>,
-    second: Option<Entity>,
+/// Devices claimed by the human players in the lobby, by the side of their input settings.
+///
+/// A disconnected gamepad keeps its player, so reconnecting it restores the assignment.
+#[derive(Clone, PartialEq, Eq, Debug, Resource)]
+pub struct PlayerDevices {
+    main: Option<PlayerDevice>,
+    second: Option<PlayerDevice>,
+    third: Option<PlayerDevice>,
+    fourth: Option<PlayerDevice>,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Default)]
-pub enum ActiveGamepad {
-    #[default]
-    Main = 1,
-    Second = 2,
+impl Default for PlayerDevices {
+    fn default() -> Self {
+        Self {
+            main: Some(PlayerDevice::Keyboard(PlayerSide::Main)),
+            second: Some(PlayerDevice::Keyboard(PlayerSide::Other)),
+            third: None,
+            fourth: None,
+        }
+    }
 }
 
-impl UserGamepad {
-    pub const fn add_gamepad(&mut self, gamepad: Entity) -> Option<ActiveGamepad> {
-        if self.main.is_none() {
-            self.main = Some(gamepad);
+impl PlayerDevices {
+    /// Device of the player using the input settings of the `side`.
+    pub const fn get(&self, side: PlayerSide) -> Option<PlayerDevice> {
+        match side {
+            PlayerSide::Main => self.main,
+            PlayerSide::Other => self.second,
+            PlayerSide::Top => self.third,
+            PlayerSide::Bottom => self.fourth,
+        }
+    }
 
-            Some(ActiveGamepad::Main)
-        } else if self.second.is_none() {
-            self.second = Some(gamepad);
+    pub const fn get_by_player(&self, player: &Player) -> Option<PlayerDevice> {
+        self.get(player.get_input_side())
+    }
 
-            Some(ActiveGamepad::Second)
-        } else {
-            None
+    pub const fn get_gamepad(&self, side: PlayerSide) -> Option<Entity> {
+        match self.get(side) {
+            Some(PlayerDevice::Gamepad(gamepad)) => Some(gamepad),
+            _ => None,
         }
     }
 
-    pub fn remove_gamepad(&mut self, gamepad: Entity) -> Option<ActiveGamepad> {
-        if self.main.is_some_and(|ent| ent == gamepad) {
-            self.main = None;
+    pub fn side_of(&self, device: PlayerDevice) -> Option<PlayerSide> {
+        PlayerSide::ALL
+            .into_iter()
+            .find(|side| self.get(*side) == Some(device))
+    }
 
-            let removed = if self.try_move_second_to_main() {
-                ActiveGamepad::Second
-            } else {
-                ActiveGamepad::Main
-            };
+    const fn get_mut(&mut self, side: PlayerSide) -> &mut Option<PlayerDevice> {
+        match side {
+            PlayerSide::Main => &mut self.main,
+            PlayerSide::Other => &mut self.second,
+            PlayerSide::Top => &mut self.third,
+            PlayerSide::Bottom => &mut self.fourth,
+        }
+    }
 
-            Some(removed)
-        } else if self.second.is_some_and(|ent| ent == gamepad) {
-            self.second = Some(gamepad);
+    /// Gives the player of the `side` to the `device`.
+    ///
+    /// The previous device of the player moves to the player left by the `device`, if any.
+    pub fn claim(&mut self, side: PlayerSide, device: PlayerDevice) {
+        let previous_side = self.side_of(device);
+        if previous_side == Some(side) {
+            return;
+        }
 
-            Some(ActiveGamepad::Second)
-        } else {
-            None
+        let previous_device = self.get_mut(side).replace(device);
+        if let Some(previous_side) = previous_side {
+            *self.get_mut(previous_side) = previous_device;
         }
     }
+}
 
-    const fn try_move_second_to_main(&mut self) -> bool {
-        if self.second.is_some() {
-            self.main = self.second;
-            self.second = None;
+/// Random number generator used by all the gameplay randomness.
+///
+/// It is reset to its seed at the start of every game,
+/// so the same seed with the same inputs replays the game identically.
+/// Each match gets the next seed of the sequence, starting from the launch seed.
+#[derive(Clone, Resource)]
+pub struct GameRng {
+    seed: u64,
+    rng: StdRng,
+}
 
-            true
-        } else {
-            false
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
         }
     }
 
-    pub const fn get_main(&self) -> Option<Entity> {
-        self.main
+    /// Creates the generator with a random seed.
+    pub fn from_entropy() -> Self {
+        Self::new(rand::random())
     }
 
-    pub const fn get_second(&self) -> Option<Entity> {
-        self.second
+    pub const fn seed(&self) -> u64 {
+        self.seed
     }
 
-    pub const fn get_by_player(&self, player: &Player) -> Option<Entity> {
-        match player.get_side() {
-            PlayerSide::Main => self.get_main(),
-            PlayerSide::Other => self.get_second(),
-        }
+    /// Restarts the sequence of random numbers from the seed.
+    pub fn reset(&mut self) {
+        self.rng = StdRng::seed_from_u64(self.seed);
+    }
+
+    /// Moves to the seed of the next match, derived from the current one.
+    pub fn next_match(&mut self) {
+        *self = Self::new(StdRng::seed_from_u64(self.seed).gen());
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::from_entropy()
     }
 }
 
-#[derive(Resource)]
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+#[derive(Clone, Resource)]
 pub struct StartMatchTimer(pub Timer);
 
 impl StartMatchTimer {
@@ -102,10 +179,11 @@ impl Default for StartMatchTimer {
     }
 }
 
-#[derive(Clone, Default, Resource)]
+#[derive(Clone, Default, PartialEq, Debug, Serialize, Deserialize, Resource)]
 pub struct GameActiveData {
-    last_winner: Option<PlayerType>,
+    last_winner: Option<PlayerSide>,
     score: GameScore,
+    match_winner: Option<PlayerSide>,
 }
 
 impl GameActiveData {
@@ -113,24 +191,39 @@ impl GameActiveData {
         self.score
     }
 
-    pub const fn last_winner(&self) -> Option<PlayerType> {
+    pub const fn last_winner(&self) -> Option<PlayerSide> {
         self.last_winner
     }
 
-    pub const fn register_point(&mut self, player: PlayerType) {
-        self.last_winner = Some(player);
-        match player {
-            PlayerType::Main => self.score.main += 1,
-            PlayerType::Second => self.score.second += 1,
-            PlayerType::AI => self.score.second += 1,
+    pub const fn match_winner(&self) -> Option<PlayerSide> {
+        self.match_winner
+    }
+
+    pub const fn is_match_over(&self) -> bool {
+        self.match_winner.is_some()
+    }
+
+    pub const fn finish_match(&mut self, winner: PlayerSide) {
+        self.match_winner = Some(winner);
+    }
+
+    pub const fn register_point(&mut self, side: PlayerSide) {
+        self.last_winner = Some(side);
+        match side {
+            PlayerSide::Main => self.score.main += 1,
+            PlayerSide::Other => self.score.second += 1,
+            PlayerSide::Top => self.score.top += 1,
+            PlayerSide::Bottom => self.score.bottom += 1,
         }
     }
 }
 
-#[derive(Clone, Copy, Default, PartialEq, Eq)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct GameScore {
     main: u8,
     second: u8,
+    top: u8,
+    bottom: u8,
 }
 
 impl GameScore {
@@ -142,16 +235,188 @@ impl GameScore {
         self.second
     }
 
-    #[expect(unused)]
-    pub const fn winning_player(&self) -> Option<PlayerSide> {
-        if self.main > self.second {
-            Some(PlayerSide::Main)
-        } else if self.main < self.second {
-            Some(PlayerSide::Other)
-        } else {
-            None
+    pub const fn points(&self, side: PlayerSide) -> u8 {
+        match side {
+            PlayerSide::Main => self.main,
+            PlayerSide::Other => self.second,
+            PlayerSide::Top => self.top,
+            PlayerSide::Bottom => self.bottom,
         }
     }
+
+    /// Side with the most points, if no other side has as many.
+    pub fn winning_player(&self) -> Option<PlayerSide> {
+        let leader = PlayerSide::ALL
+            .into_iter()
+            .max_by_key(|side| self.points(*side))?;
+
+        (self.runner_up_points(leader) < self.points(leader)).then_some(leader)
+    }
+
+    /// Most points of the sides other than the `leader`.
+    pub fn runner_up_points(&self, leader: PlayerSide) -> u8 {
+        PlayerSide::ALL
+            .into_iter()
+            .filter(|side| *side != leader)
+            .map(|side| self.points(side))
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Points of the `sides`, separated by dashes.
+    pub fn format(&self, sides: &[PlayerSide]) -> String {
+        sides
+            .iter()
+            .map(|side| self.points(*side).to_string())
+            .collect::<Vec<_>>()
+            .join(" - ")
+    }
+}
+
+/// Rules that decide when a match is over.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize, Resource)]
+pub struct MatchRules {
+    /// Points a player must reach to win the match.
+    pub points_to_win: u8,
+    /// Require a lead of at least two points to win the match.
+    pub win_by_two: bool,
+}
+
+impl MatchRules {
+    pub const POINTS_TO_WIN_OPTIONS: [u8; 4] = [3, 5, 7, 11];
+    pub const DEFAULT_POINTS_TO_WIN: u8 = 5;
+
+    pub const fn new(points_to_win: u8, win_by_two: bool) -> Self {
+        Self {
+            points_to_win,
+            win_by_two,
+        }
+    }
+
+    /// Returns the side that won the match with the given score, if any.
+    ///
+    /// Winning by two is counted against the closest of the other sides.
+    pub fn match_winner(&self, score: GameScore) -> Option<PlayerSide> {
+        let leader = score.winning_player()?;
+
+        let leader_points = score.points(leader);
+        let trailer_points = score.runner_up_points(leader);
+
+        if leader_points < self.points_to_win {
+            return None;
+        }
+
+        if self.win_by_two && leader_points - trailer_points < 2 {
+            return None;
+        }
+
+        Some(leader)
+    }
+
+    /// Cycles through [`MatchRules::POINTS_TO_WIN_OPTIONS`].
+    pub fn next_points_to_win(&self) -> u8 {
+        let options = Self::POINTS_TO_WIN_OPTIONS;
+        let index = options
+            .iter()
+            .position(|points| *points == self.points_to_win)
+            .map(|index| (index + 1) % options.len())
+            .unwrap_or(0);
+
+        options[index]
+    }
+}
+
+impl Default for MatchRules {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_POINTS_TO_WIN, false)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+pub enum PaddleBounceMode {
+    /// The outgoing angle depends on where the ball touches the paddle.
+    #[default]
+    ContactPoint = 1,
+    /// The outgoing vertical velocity is random.
+    Random = 2,
+}
+
+impl PaddleBounceMode {
+    pub const fn change_mode(self) -> Self {
+        match self {
+            Self::ContactPoint => Self::Random,
+            Self::Random => Self::ContactPoint,
+        }
+    }
+}
+
+/// How the ball bounces off the paddles.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize, Resource)]
+pub struct PaddleBounce {
+    pub mode: PaddleBounceMode,
+    /// Outgoing angle, in radians, when the ball touches the paddle edge.
+    pub max_angle: f32,
+    /// Speed increase per paddle hit, as a fraction of the current speed.
+    pub speed_gain: f32,
+}
+
+impl PaddleBounce {
+    pub const DEFAULT_MAX_ANGLE: f32 = FRAC_PI_3;
+    pub const DEFAULT_SPEED_GAIN: f32 = 0.05;
+
+    /// Changes the ball `velocity`, already reflected by the paddle.
+    ///
+    /// `contact_offset` is where the ball touched the paddle moving along the `axis`,
+    /// from `-1.0` (bottom or left edge) to `1.0` (top or right edge).
+    pub fn apply<R: Rng + ?Sized>(
+        &self,
+        axis: PaddleAxis,
+        contact_offset: f32,
+        velocity: &mut LinearVelocity,
+        rng: &mut R,
+    ) {
+        // the bounce of the horizontal paddles is the one of the vertical paddles, transposed.
+        if axis == PaddleAxis::Horizontal {
+            velocity.0 = velocity.0.yx();
+        }
+
+        match self.mode {
+            PaddleBounceMode::ContactPoint => {
+                let angle = contact_offset.clamp(-1.0, 1.0) * self.max_angle;
+                let speed = velocity.length() * (1.0 + self.speed_gain);
+                let direction_x = velocity.x.signum();
+
+                velocity.0 = Vec2::new(direction_x * angle.cos(), angle.sin()) * speed;
+            }
+            PaddleBounceMode::Random => {
+                let start = f32::min(-velocity.x, velocity.x);
+                let end = -start;
+
+                if start < end {
+                    velocity.y = rng.gen_range(start..end) * 0.8;
+                }
+            }
+        }
+
+        if axis == PaddleAxis::Horizontal {
+            velocity.0 = velocity.0.yx();
+        }
+    }
+}
+
+impl Default for PaddleBounce {
+    fn default() -> Self {
+        Self {
+            mode: PaddleBounceMode::default(),
+            max_angle: Self::DEFAULT_MAX_ANGLE,
+            speed_gain: Self::DEFAULT_SPEED_GAIN,
+        }
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct MainPlayer {
+    pub kind: MainPlayerType,
 }
 
 #[derive(Default, Resource)]
@@ -159,21 +424,285 @@ pub struct SecondPlayer {
     pub opponent: SecondPlayerType,
 }
 
-#[derive(Resource)]
-pub struct CommonMesh {
-    quad: Handle<Mesh>,
+/// Four players mode, with the top and bottom walls replaced by paddles.
+///
+/// The left and right players are still the [`MainPlayer`] and [`SecondPlayer`].
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize, Resource)]
+pub struct FourPlayers {
+    pub enabled: bool,
+    pub top: ExtraPlayerType,
+    pub bottom: ExtraPlayerType,
 }
 
-impl CommonMesh {
-    pub fn quad(&self) -> Handle<Mesh> {
-        self.quad.clone()
+impl FourPlayers {
+    /// Sides playing the match.
+    pub const fn sides(&self) -> &'static [PlayerSide] {
+        if self.enabled {
+            &PlayerSide::ALL
+        } else {
+            &PlayerSide::CLASSIC
+        }
     }
 }
 
-impl FromWorld for CommonMesh {
-    fn from_world(world: &mut bevy::prelude::World) -> Self {
-        let mut meshes = world.resource_mut::<Assets<Mesh>>();
-        let quad = meshes.add(Rectangle::from_size(Vec2::ONE));
-        Self { quad }
+/// When extra balls are served while a point is played, each of them scoring on its own.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize, Resource)]
+pub enum MultiBall {
+    /// A single ball.
+    #[default]
+    Off = 1,
+    /// An extra ball every [`MultiBall::SPAWN_SECONDS`] of play.
+    Timer = 2,
+    /// An extra ball every [`MultiBall::RALLY_HITS`] paddle hits.
+    Rally = 3,
+}
+
+impl MultiBall {
+    /// Most balls in the arena at once.
+    pub const MAX_BALLS: usize = 4;
+    pub const SPAWN_SECONDS: u32 = 8;
+    pub const RALLY_HITS: u32 = 4;
+
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Off => Self::Timer,
+            Self::Timer => Self::Rally,
+            Self::Rally => Self::Off,
+        }
+    }
+}
+
+impl FromStr for MultiBall {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "off" => Ok(Self::Off),
+            "timer" => Ok(Self::Timer),
+            "rally" => Ok(Self::Rally),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Progress of the current point towards the next extra ball.
+#[derive(Clone, Resource)]
+pub struct ExtraBallProgress {
+    pub timer: Timer,
+    /// Paddle hits since the last extra ball, or the serve.
+    pub paddle_hits: u32,
+}
+
+impl Default for ExtraBallProgress {
+    fn default() -> Self {
+        Self {
+            timer: Timer::new(
+                Duration::from_secs(MultiBall::SPAWN_SECONDS as u64),
+                TimerMode::Repeating,
+            ),
+            paddle_hits: 0,
+        }
+    }
+}
+
+/// Power-ups spawned in the middle of the arena, giving effects to the paddles.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize, Resource)]
+pub struct PowerUps {
+    pub enabled: bool,
+}
+
+/// Time of play until the next power-up.
+#[derive(Clone, Resource)]
+pub struct PowerUpTimer(pub Timer);
+
+impl Default for PowerUpTimer {
+    fn default() -> Self {
+        Self(Timer::new(
+            Duration::from_secs(PowerUp::SPAWN_SECONDS as u64),
+            TimerMode::Repeating,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(points: &[(PlayerSide, u8)]) -> GameScore {
+        let mut data = GameActiveData::default();
+        for (side, count) in points {
+            for _ in 0..*count {
+                data.register_point(*side);
+            }
+        }
+        data.score()
+    }
+
+    #[test]
+    fn winner_reaches_the_points_to_win() {
+        let rules = MatchRules::new(5, false);
+
+        assert_eq!(rules.match_winner(score(&[(PlayerSide::Main, 4)])), None);
+        assert_eq!(
+            rules.match_winner(score(&[(PlayerSide::Main, 5), (PlayerSide::Other, 4)])),
+            Some(PlayerSide::Main)
+        );
+        assert_eq!(
+            rules.match_winner(score(&[(PlayerSide::Main, 3), (PlayerSide::Other, 5)])),
+            Some(PlayerSide::Other)
+        );
+    }
+
+    #[test]
+    fn win_by_two_needs_a_lead_of_two_points() {
+        let rules = MatchRules::new(5, true);
+
+        assert_eq!(
+            rules.match_winner(score(&[(PlayerSide::Main, 5), (PlayerSide::Other, 4)])),
+            None
+        );
+        assert_eq!(
+            rules.match_winner(score(&[(PlayerSide::Main, 7), (PlayerSide::Other, 5)])),
+            Some(PlayerSide::Main)
+        );
+    }
+
+    #[test]
+    fn tied_score_has_no_winner() {
+        let rules = MatchRules::new(3, false);
+
+        assert_eq!(
+            rules.match_winner(score(&[(PlayerSide::Main, 3), (PlayerSide::Other, 3)])),
+            None
+        );
+    }
+
+    #[test]
+    fn win_by_two_counts_the_closest_side() {
+        let rules = MatchRules::new(5, true);
+        let points = [
+            (PlayerSide::Main, 6),
+            (PlayerSide::Other, 1),
+            (PlayerSide::Top, 5),
+            (PlayerSide::Bottom, 2),
+        ];
+
+        assert_eq!(rules.match_winner(score(&points)), None);
+    }
+
+    #[test]
+    fn top_and_bottom_sides_win_the_match() {
+        let rules = MatchRules::new(5, false);
+
+        assert_eq!(
+            rules.match_winner(score(&[
+                (PlayerSide::Main, 3),
+                (PlayerSide::Other, 2),
+                (PlayerSide::Top, 5),
+                (PlayerSide::Bottom, 4),
+            ])),
+            Some(PlayerSide::Top)
+        );
+        assert_eq!(
+            rules.match_winner(score(&[(PlayerSide::Top, 1), (PlayerSide::Bottom, 5)])),
+            Some(PlayerSide::Bottom)
+        );
+    }
+
+    #[test]
+    fn tie_between_any_two_sides_has_no_winner() {
+        let rules = MatchRules::new(3, false);
+
+        assert_eq!(
+            rules.match_winner(score(&[
+                (PlayerSide::Main, 1),
+                (PlayerSide::Top, 3),
+                (PlayerSide::Bottom, 3),
+            ])),
+            None
+        );
+    }
+
+    #[test]
+    fn points_to_win_cycle_through_the_options() {
+        assert_eq!(MatchRules::new(5, false).next_points_to_win(), 7);
+        assert_eq!(MatchRules::new(11, false).next_points_to_win(), 3);
+        assert_eq!(MatchRules::new(4, false).next_points_to_win(), 3);
+    }
+
+    fn gamepad(index: u32) -> PlayerDevice {
+        PlayerDevice::Gamepad(Entity::from_raw(index))
+    }
+
+    #[test]
+    fn claim_gives_the_side_to_the_device() {
+        let mut devices = PlayerDevices::default();
+
+        devices.claim(PlayerSide::Main, gamepad(1));
+
+        assert_eq!(devices.get(PlayerSide::Main), Some(gamepad(1)));
+        assert_eq!(devices.side_of(gamepad(1)), Some(PlayerSide::Main));
+        // the keyboard half left without a side.
+        assert_eq!(
+            devices.side_of(PlayerDevice::Keyboard(PlayerSide::Main)),
+            None
+        );
+        assert_eq!(
+            devices.get(PlayerSide::Other),
+            Some(PlayerDevice::Keyboard(PlayerSide::Other))
+        );
+    }
+
+    #[test]
+    fn claiming_the_same_side_twice_keeps_it() {
+        let mut devices = PlayerDevices::default();
+
+        devices.claim(PlayerSide::Other, gamepad(1));
+        let claimed = devices.clone();
+        devices.claim(PlayerSide::Other, gamepad(1));
+
+        assert_eq!(devices, claimed);
+    }
+
+    #[test]
+    fn claiming_the_other_side_swaps_the_devices() {
+        let mut devices = PlayerDevices::default();
+        devices.claim(PlayerSide::Main, gamepad(1));
+        devices.claim(PlayerSide::Other, gamepad(2));
+
+        devices.claim(PlayerSide::Other, gamepad(1));
+
+        assert_eq!(devices.get(PlayerSide::Main), Some(gamepad(2)));
+        assert_eq!(devices.get(PlayerSide::Other), Some(gamepad(1)));
+    }
+
+    #[test]
+    fn any_number_of_gamepads_can_claim() {
+        let mut devices = PlayerDevices::default();
+
+        for index in 1..=5 {
+            devices.claim(PlayerSide::Main, gamepad(index));
+        }
+        devices.claim(PlayerSide::Other, gamepad(3));
+
+        // the last gamepad claiming a side gets it, the previous ones are left without a side.
+        assert_eq!(devices.get(PlayerSide::Main), Some(gamepad(5)));
+        assert_eq!(devices.get(PlayerSide::Other), Some(gamepad(3)));
+        for index in [1, 2, 4] {
+            assert_eq!(devices.side_of(gamepad(index)), None);
+        }
+    }
+
+    #[test]
+    fn next_match_seed_is_derived_from_the_seed() {
+        let mut rng = GameRng::new(42);
+        rng.next_match();
+
+        let mut other = GameRng::new(42);
+        other.next_match();
+
+        assert_ne!(rng.seed(), 42);
+        assert_eq!(rng.seed(), other.seed());
+        assert_eq!(rng.gen::<u64>(), other.gen::<u64>());
     }
 }
@@ -1,14 +1,19 @@
 use bevy::prelude::*;
 
-use core::fmt;
+use core::{fmt, str::FromStr};
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, States)]
 pub enum GameState {
     #[default]
     MainMenu,
+    /// Human players claim their side with a keyboard half or gamepad before the match.
+    Lobby,
+    /// Entrants and bracket of a tournament, shown between its matches.
+    Tournament,
     GameActive {
         playing: bool,
     },
+    MatchOver,
 }
 
 impl GameState {
@@ -19,8 +24,11 @@ impl GameState {
     pub const fn as_str(&self) -> &'static str {
         match *self {
             Self::MainMenu => "main_menu",
+            Self::Lobby => "lobby",
+            Self::Tournament => "tournament",
             Self::GameActive { playing: false } => "game_active",
             Self::GameActive { playing: true } => "game_active.playing",
+            Self::MatchOver => "match_over",
         }
     }
 }
@@ -31,6 +39,22 @@ impl fmt::Display for GameState {
     }
 }
 
+impl FromStr for GameState {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "main_menu" => Ok(Self::MainMenu),
+            "lobby" => Ok(Self::Lobby),
+            "tournament" => Ok(Self::Tournament),
+            "game_active" => Ok(Self::GameActive { playing: false }),
+            "game_active.playing" => Ok(Self::playing()),
+            "match_over" => Ok(Self::MatchOver),
+            _ => Err(()),
+        }
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum GameActiveState {
     Playing = 1,
@@ -51,7 +75,10 @@ impl ComputedStates for GameActiveState {
 
     fn compute(sources: Self::SourceStates) -> Option<Self> {
         match sources {
-            GameState::MainMenu => None,
+            GameState::MainMenu
+            | GameState::Lobby
+            | GameState::Tournament
+            | GameState::MatchOver => None,
             GameState::GameActive { playing: true } => Some(Self::Playing),
             GameState::GameActive { playing: false } => Some(Self::Pause),
         }
@@ -72,7 +99,10 @@ impl ComputedStates for InGame {
 
     fn compute(sources: Self::SourceStates) -> Option<Self> {
         match sources {
-            GameState::MainMenu => None,
+            GameState::MainMenu
+            | GameState::Lobby
+            | GameState::Tournament
+            | GameState::MatchOver => None,
             GameState::GameActive { playing: true } => Some(Self),
             GameState::GameActive { playing: false } => Some(Self),
         }
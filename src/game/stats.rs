@@ -0,0 +1,161 @@
+use core::time::Duration;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::player::PlayerSide;
+
+/// Statistics of the current match, recorded while it is simulated.
+///
+/// A rally is made of the paddle hits between two points, so with several balls in play
+/// it also counts the hits on the other balls.
+#[derive(Clone, Default, PartialEq, Debug, Serialize, Deserialize, Resource)]
+pub struct MatchStats {
+    /// Paddle hits of each rally ended by a point, in order.
+    rallies: Vec<u32>,
+    /// Paddle hits of the rally in progress.
+    rally: u32,
+    /// Paddle hits of the main, other, top and bottom sides.
+    paddle_hits: [u32; 4],
+    /// Points won before any paddle hit, of the main, other, top and bottom sides.
+    serve_points: [u32; 4],
+    /// Fastest speed reached by a ball, in units per second.
+    fastest_ball: f32,
+    /// Time the match was played, without the pauses.
+    duration: Duration,
+}
+
+impl MatchStats {
+    const fn index(side: PlayerSide) -> usize {
+        match side {
+            PlayerSide::Main => 0,
+            PlayerSide::Other => 1,
+            PlayerSide::Top => 2,
+            PlayerSide::Bottom => 3,
+        }
+    }
+
+    /// Paddle hits of each rally ended by a point, in order.
+    pub fn rallies(&self) -> &[u32] {
+        &self.rallies
+    }
+
+    pub fn longest_rally(&self) -> u32 {
+        self.rallies.iter().copied().max().unwrap_or_default()
+    }
+
+    /// Mean paddle hits of the rallies ended by a point.
+    pub fn average_rally(&self) -> f32 {
+        if self.rallies.is_empty() {
+            return 0.0;
+        }
+        self.rallies.iter().sum::<u32>() as f32 / self.rallies.len() as f32
+    }
+
+    pub const fn paddle_hits(&self, side: PlayerSide) -> u32 {
+        self.paddle_hits[Self::index(side)]
+    }
+
+    /// Points the side won on the serve, before any paddle hit.
+    pub const fn serve_points(&self, side: PlayerSide) -> u32 {
+        self.serve_points[Self::index(side)]
+    }
+
+    pub const fn fastest_ball(&self) -> f32 {
+        self.fastest_ball
+    }
+
+    pub const fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Adds the time of a fixed update of the match.
+    pub fn advance(&mut self, delta: Duration) {
+        self.duration += delta;
+    }
+
+    pub const fn register_ball_speed(&mut self, speed: f32) {
+        self.fastest_ball = self.fastest_ball.max(speed);
+    }
+
+    pub const fn register_hit(&mut self, side: PlayerSide) {
+        self.rally += 1;
+        self.paddle_hits[Self::index(side)] += 1;
+    }
+
+    /// Ends the rally in progress with a point of the `winner`.
+    pub fn register_point(&mut self, winner: PlayerSide) {
+        if self.rally == 0 {
+            self.serve_points[Self::index(winner)] += 1;
+        }
+        self.rallies.push(self.rally);
+        self.rally = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rally_counts_the_hits_until_the_point() {
+        let mut stats = MatchStats::default();
+
+        stats.register_hit(PlayerSide::Main);
+        stats.register_hit(PlayerSide::Other);
+        stats.register_hit(PlayerSide::Main);
+        stats.register_point(PlayerSide::Main);
+        stats.register_hit(PlayerSide::Other);
+        stats.register_point(PlayerSide::Other);
+
+        assert_eq!(stats.rallies(), [3, 1]);
+        assert_eq!(stats.paddle_hits(PlayerSide::Main), 2);
+        assert_eq!(stats.paddle_hits(PlayerSide::Other), 2);
+        assert_eq!(stats.average_rally(), 2.0);
+    }
+
+    #[test]
+    fn longest_rally_of_the_match() {
+        let mut stats = MatchStats::default();
+        assert_eq!(stats.longest_rally(), 0);
+        assert_eq!(stats.average_rally(), 0.0);
+
+        for hits in [2, 7, 4] {
+            for _ in 0..hits {
+                stats.register_hit(PlayerSide::Top);
+            }
+            stats.register_point(PlayerSide::Bottom);
+        }
+
+        assert_eq!(stats.longest_rally(), 7);
+        assert_eq!(stats.paddle_hits(PlayerSide::Top), 13);
+    }
+
+    #[test]
+    fn serve_points_are_won_before_any_hit() {
+        let mut stats = MatchStats::default();
+
+        stats.register_point(PlayerSide::Other);
+        stats.register_hit(PlayerSide::Main);
+        stats.register_point(PlayerSide::Main);
+        stats.register_point(PlayerSide::Other);
+
+        assert_eq!(stats.serve_points(PlayerSide::Other), 2);
+        assert_eq!(stats.serve_points(PlayerSide::Main), 0);
+        assert_eq!(stats.rallies(), [0, 1, 0]);
+    }
+
+    #[test]
+    fn fastest_ball_and_duration() {
+        let mut stats = MatchStats::default();
+
+        stats.register_ball_speed(20.0);
+        stats.register_ball_speed(35.0);
+        stats.register_ball_speed(30.0);
+        stats.advance(Duration::from_millis(500));
+        stats.advance(Duration::from_millis(250));
+
+        assert_eq!(stats.fastest_ball(), 35.0);
+        assert_eq!(stats.duration(), Duration::from_millis(750));
+    }
+}
@@ -1,41 +1,71 @@
-use bevy::math::bounding::{Aabb2d, RayCast2d};
+use bevy::math::bounding::Aabb2d;
 use bevy::prelude::*;
 use rand::Rng;
 
 use super::{
-    arena::{Arena, ArenaDirection, Ball, Paddle, PaddleDirection, Wall},
-    event::{GameDataUpdated, PointMarked},
-    physics::{ball_collision, resolve_ball_collision, Collider, LinearVelocity},
-    player::{Player, PlayerAI, PlayerSide, PlayerType, SecondPlayerType},
-    resource::{CommonMesh, GameActiveData, SecondPlayer, StartMatchTimer, UserGamepad},
+    arena::{Arena, ArenaDirection, Ball, LastHit, Obstacle, Paddle, PaddleAxis, Wall},
+    event::{GameDataUpdated, MatchOver, PointMarked},
+    layout::ArenaLayout,
+    physics::{
+        ball_collision, resolve_ball_collision, sweep_ball, Collider, Intercept, LinearVelocity,
+    },
+    player::{
+        AIController, AIDifficulty, PaddleInput, Player, PlayerAI, PlayerRemote, PlayerSide,
+        PlayerType,
+    },
+    power_up::{ball_touches_power_up, BallCurve, PaddleEffect, PaddleEffects, PowerUp, Shield},
+    resource::{
+        ExtraBallProgress, FourPlayers, GameActiveData, GameRng, MainPlayer, MatchRules, MultiBall,
+        PaddleBounce, PlayerDevice, PlayerDevices, PowerUpTimer, PowerUps, SecondPlayer,
+        StartMatchTimer,
+    },
+    state::GameState,
+    stats::MatchStats,
 };
+use crate::settings::input::InputSettings;
 
-pub fn reset_game_data(mut game_data: ResMut<GameActiveData>) {
+pub fn reset_game_data(
+    mut game_data: ResMut<GameActiveData>,
+    mut stats: ResMut<MatchStats>,
+    mut power_up_timer: ResMut<PowerUpTimer>,
+    mut rng: ResMut<GameRng>,
+) {
     *game_data = GameActiveData::default();
+    *stats = MatchStats::default();
+    *power_up_timer = PowerUpTimer::default();
+    rng.reset();
+}
+
+/// Gives a new seed to the next match, so the matches of a session are not all the same.
+///
+/// Restarting the match in place (like seeking a replay) runs the `InGame` transitions
+/// without leaving the state, and keeps the seed.
+pub fn next_match_seed(mut rng: ResMut<GameRng>) {
+    rng.next_match();
 }
 
+/// Spawns the arena from its layout, with the layout walls only in a classic match.
+///
+/// The goal openings of the sides playing the match are closed by posts on both ends.
 pub fn spawn_arena(
+    layout: Res<ArenaLayout>,
+    four_players: Res<FourPlayers>,
     mut commands: Commands,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    app_meshs: Res<CommonMesh>,
 ) {
-    let material = materials.add(Arena::COLOR);
-
     commands.spawn(Arena).with_children(|children| {
-        children.spawn((
-            Wall::Top,
-            Mesh2d(app_meshs.quad()),
-            MeshMaterial2d(material.clone()),
-            Collider,
-            Wall::top_transform(),
-        ));
-        children.spawn((
-            Wall::Bottom,
-            Mesh2d(app_meshs.quad()),
-            MeshMaterial2d(material),
-            Collider,
-            Wall::bottom_transform(),
-        ));
+        if !four_players.enabled {
+            for wall in &layout.walls {
+                children.spawn((Wall, Collider, wall.transform()));
+            }
+        }
+        for side in four_players.sides() {
+            for post in layout.goal_posts(*side) {
+                children.spawn((Wall, Collider, post.transform()));
+            }
+        }
+        for obstacle in &layout.obstacles {
+            children.spawn((Obstacle, Collider, obstacle.transform()));
+        }
     });
 }
 
@@ -45,41 +75,34 @@ pub fn despawn_arena(arena: Single<Entity, With<Arena>>, mut commands: Commands)
 }
 
 pub fn spawn_players(
-    app_meshs: Res<CommonMesh>,
+    main_player: Res<MainPlayer>,
     second_player: Res<SecondPlayer>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
+    four_players: Res<FourPlayers>,
+    difficulty: Res<AIDifficulty>,
+    layout: Res<ArenaLayout>,
     mut commands: Commands,
 ) {
-    let material = materials.add(Paddle::COLOR);
+    for side in four_players.sides() {
+        let side = *side;
+        let player = side.to_player_type(main_player.kind, second_player.opponent, *four_players);
 
-    commands.spawn((
-        Player::new_main(),
-        Paddle,
-        Mesh2d(app_meshs.quad()),
-        MeshMaterial2d(material.clone()),
-        Collider,
-        Paddle::new_main_transform(),
-    ));
-
-    match second_player.opponent {
-        SecondPlayerType::Player => commands.spawn((
-            Player::new_second(),
+        let mut paddle = commands.spawn((
+            Player::new(side, player),
             Paddle,
-            Mesh2d(app_meshs.quad()),
-            MeshMaterial2d(material),
             Collider,
-            Paddle::new_second_transform(),
-        )),
-        SecondPlayerType::AI => commands.spawn((
-            Player::new_second(),
-            PlayerAI,
-            Paddle,
-            Mesh2d(app_meshs.quad()),
-            MeshMaterial2d(material),
-            Collider,
-            Paddle::new_second_transform(),
-        )),
-    };
+            Paddle::new_transform(side, &layout),
+        ));
+
+        match player {
+            PlayerType::AI => {
+                paddle.insert((PlayerAI, AIController::new(*difficulty)));
+            }
+            PlayerType::Remote => {
+                paddle.insert(PlayerRemote);
+            }
+            _ => {}
+        }
+    }
 }
 
 pub fn despawn_players(players: Query<Entity, With<Player>>, mut commands: Commands) {
@@ -88,221 +111,633 @@ pub fn despawn_players(players: Query<Entity, With<Player>>, mut commands: Comma
     }
 }
 
-pub fn spawn_ball(
-    mut commands: Commands,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    mut meshes: ResMut<Assets<Mesh>>,
-) {
-    let mesh = meshes.add(Ball::primitive());
-    let material = materials.add(Ball::COLOR);
-
-    commands.spawn((
-        Ball,
-        Mesh2d(mesh),
-        MeshMaterial2d(material),
-        LinearVelocity(Vec2::ZERO),
-        Ball::initial_transform(),
-    ));
+pub fn spawn_ball(mut commands: Commands) {
+    commands.spawn((Ball, LinearVelocity(Vec2::ZERO), Ball::initial_transform()));
 }
 
-pub fn despawn_ball(ball: Single<Entity, With<Ball>>, mut commands: Commands) {
-    let entity = ball.into_inner();
-    commands.entity(entity).despawn();
+pub fn despawn_balls(balls: Query<Entity, With<Ball>>, mut commands: Commands) {
+    for entity in &balls {
+        commands.entity(entity).despawn();
+    }
 }
 
-pub fn init_match(mut commands: Commands) {
+pub fn init_match(mut progress: ResMut<ExtraBallProgress>, mut commands: Commands) {
     commands.insert_resource(StartMatchTimer::default());
+    *progress = ExtraBallProgress::default();
+}
+
+/// Direction to a random side playing the match.
+fn random_ball_direction(four_players: &FourPlayers, rng: &mut GameRng) -> ArenaDirection {
+    if four_players.enabled {
+        let sides = four_players.sides();
+        sides[rng.gen_range(0..sides.len())].arena_direction()
+    } else {
+        rng.gen::<ArenaDirection>()
+    }
 }
 
-fn next_ball_direction(last_winner: Option<PlayerType>) -> ArenaDirection {
-    last_winner
-        .map(|player| player.arena_direction())
-        .unwrap_or_else(|| rand::thread_rng().gen::<ArenaDirection>())
+/// Serves the ball to the last winner, or to a random side playing the match.
+fn next_ball_direction(
+    last_winner: Option<PlayerSide>,
+    four_players: &FourPlayers,
+    rng: &mut GameRng,
+) -> ArenaDirection {
+    match last_winner {
+        Some(side) => side.arena_direction(),
+        None => random_ball_direction(four_players, rng),
+    }
 }
 
 pub fn start_match(
-    ball: Single<&mut LinearVelocity, With<Ball>>,
+    mut balls: Query<&mut LinearVelocity, With<Ball>>,
     game_data: Res<GameActiveData>,
+    four_players: Res<FourPlayers>,
     time: Res<Time>,
+    mut rng: ResMut<GameRng>,
     mut match_timer: ResMut<StartMatchTimer>,
     mut commands: Commands,
 ) {
     if match_timer.0.tick(time.delta()).just_finished() {
-        let mut velocity = ball.into_inner();
-
-        let direction = next_ball_direction(game_data.last_winner());
-        velocity.0 = Ball::random_linear_velocity(direction);
+        for mut velocity in &mut balls {
+            let direction = next_ball_direction(game_data.last_winner(), &four_players, &mut rng);
+            velocity.0 = Ball::random_linear_velocity(direction, rng.as_mut());
+        }
 
         commands.remove_resource::<StartMatchTimer>();
     }
 }
 
+/// Serves an extra ball from the center of the arena, when the [`MultiBall`] mode asks for it.
+pub fn spawn_extra_ball(
+    balls: Query<(), With<Ball>>,
+    multi_ball: Res<MultiBall>,
+    four_players: Res<FourPlayers>,
+    time: Res<Time>,
+    mut progress: ResMut<ExtraBallProgress>,
+    mut rng: ResMut<GameRng>,
+    mut commands: Commands,
+) {
+    let due = match *multi_ball {
+        MultiBall::Off => false,
+        MultiBall::Timer => progress.timer.tick(time.delta()).just_finished(),
+        MultiBall::Rally => progress.paddle_hits >= MultiBall::RALLY_HITS,
+    };
+    if !due {
+        return;
+    }
+
+    progress.paddle_hits = 0;
+    if balls.iter().count() >= MultiBall::MAX_BALLS {
+        return;
+    }
+
+    let direction = random_ball_direction(&four_players, &mut rng);
+    commands.spawn((
+        Ball,
+        LinearVelocity(Ball::random_linear_velocity(direction, rng.as_mut())),
+        Ball::initial_transform(),
+    ));
+}
+
 #[expect(clippy::type_complexity)]
-pub fn move_paddle_by_player(
-    mut paddles: Query<(&mut Transform, &Player), (With<Paddle>, Without<PlayerAI>)>,
+pub fn read_player_input(
+    mut paddles: Query<
+        (&mut PaddleInput, &Player),
+        (With<Paddle>, Without<PlayerAI>, Without<PlayerRemote>),
+    >,
     gamepads: Query<&Gamepad>,
-    user_gamepad: Res<UserGamepad>,
+    devices: Res<PlayerDevices>,
     keyboard: Res<ButtonInput<KeyCode>>,
-    time: Res<Time<Fixed>>,
+    settings: Res<InputSettings>,
 ) {
-    for (mut transform, player) in &mut paddles {
-        let gamepad = user_gamepad
-            .get_by_player(player)
-            .and_then(|entity| gamepads.get(entity).ok());
-
-        if keyboard.pressed(player.keyboard_input().paddle_up)
-            || gamepad.is_some_and(|gpad| gpad.pressed(player.gamepad_input().paddle_up))
-        {
-            Paddle::move_vertically(PaddleDirection::Up, &mut transform, time.delta_secs());
-        }
-        if keyboard.pressed(player.keyboard_input().paddle_down)
-            || gamepad.is_some_and(|gpad| gpad.pressed(player.gamepad_input().paddle_down))
-        {
-            Paddle::move_vertically(PaddleDirection::Down, &mut transform, time.delta_secs());
-        }
+    for (mut input, player) in &mut paddles {
+        *input = match devices.get_by_player(player) {
+            Some(PlayerDevice::Keyboard(half)) => {
+                PaddleInput::from_keyboard(&keyboard, settings.keyboard(half))
+            }
+            Some(PlayerDevice::Gamepad(entity)) => gamepads
+                .get(entity)
+                .map(|gamepad| PaddleInput::from_gamepad(gamepad, player.gamepad_input(&settings)))
+                .unwrap_or_default(),
+            None => PaddleInput::default(),
+        };
+    }
+}
 
-        Paddle::clamp_position(&mut transform);
+#[expect(clippy::type_complexity)]
+pub fn move_paddle_by_player(
+    mut paddles: Query<
+        (&mut Transform, &Player, &PaddleInput, &PaddleEffects),
+        (With<Paddle>, Without<PlayerAI>),
+    >,
+    layout: Res<ArenaLayout>,
+    time: Res<Time<Fixed>>,
+) {
+    for (mut transform, player, input, effects) in &mut paddles {
+        let axis = player.paddle_axis();
+        Paddle::move_by_input(
+            &mut transform,
+            axis,
+            input.0,
+            effects.speed_scale(),
+            time.delta_secs(),
+        );
+        Paddle::clamp_position(&mut transform, axis, layout.size);
     }
 }
 
-fn paddle_ai_movement(
-    paddle: &mut Transform,
+/// Predicts where the ball reaches the face of the AI paddle.
+///
+/// Returns `None` when the ball is not moving to the paddle.
+fn predict_ball_intercept(
+    paddle: &Transform,
+    player: &Player,
     ball: &Transform,
     ball_velocity: &LinearVelocity,
+    (arena, walls): (Vec2, bool),
+) -> Option<Intercept> {
+    let predictor = Ball::trajectory_predictor(ball, arena, walls);
+    let radius = Ball::bounding_circle(ball).radius();
+    let axis = player.paddle_axis();
+
+    // the paddle face looks to the center of the arena,
+    // and the ball center touches it one radius before.
+    let side = match player.get_side().arena_direction() {
+        ArenaDirection::Left | ArenaDirection::Down => -1.0,
+        ArenaDirection::Right | ArenaDirection::Up => 1.0,
+    };
+    let face = axis.across(paddle.translation.truncate())
+        - side * (axis.across(paddle.scale.truncate()) * 0.5 + radius);
+
+    let position = ball.translation.truncate();
+    match axis {
+        PaddleAxis::Vertical => predictor.intercept_x(position, ball_velocity.0, face),
+        PaddleAxis::Horizontal => predictor.intercept_y(position, ball_velocity.0, face),
+    }
+}
+
+fn paddle_ai_movement(
+    (paddle, effects): (&mut Transform, &PaddleEffects),
+    player: &Player,
+    controller: &mut AIController,
+    balls: &[(&Transform, &LinearVelocity)],
+    (arena, walls): (Vec2, bool),
+    rng: &mut GameRng,
     delta_time: f32,
 ) {
-    // when the ball is on the side or going to the main player:
-    if Ball::current_arena_direction(ball) == ArenaDirection::Left
-        || Ball::moving_to_arena_direction(ball_velocity) == ArenaDirection::Left
-    {
-        // stay where we are
-        return;
+    let axis = player.paddle_axis();
+
+    // look at the balls only after the reaction time of the AI difficulty,
+    // tracking the first one to reach the paddle.
+    if controller.react(delta_time) {
+        let intercept = balls
+            .iter()
+            .filter_map(|(ball, ball_velocity)| {
+                predict_ball_intercept(paddle, player, ball, ball_velocity, (arena, walls))
+            })
+            .min_by(|a, b| a.time.total_cmp(&b.time));
+        controller.update_target(intercept, axis, rng);
     }
 
-    // when the ball is on the side of the AI player:
-    // move paddle to the predicted position of the ball.
-
-    let arena_right_collider = Arena::right_collider();
-    let ball_direction = match Dir2::new(ball_velocity.0) {
-        Ok(dir) => dir,
-        _ => return,
+    let target = match controller.target() {
+        Some(target) => target,
+        None => return,
     };
-    let ray_cast = RayCast2d::new(ball.translation.truncate(), ball_direction, 100.0);
-    let x_distance = Arena::SIZE.x * 0.5 - ball.translation.x;
-
-    if let Some(move_distance) = ray_cast.aabb_intersection_at(&arena_right_collider) {
-        let delta_y = (move_distance * move_distance - x_distance * x_distance).sqrt();
-
-        let final_ball_position = if ball_direction.y > 0.0 {
-            ball.translation.y + delta_y
-        } else {
-            ball.translation.y - delta_y
-        };
-
-        if (final_ball_position - paddle.translation.y).abs() < Paddle::AI_DEADZONE {
-            // the paddle is already aligned with the final ball position.
-            return;
-        }
 
-        // move paddle to the ball position
-        if final_ball_position > paddle.translation.y {
-            Paddle::move_vertically(PaddleDirection::Up, paddle, delta_time);
-        } else if final_ball_position < paddle.translation.y {
-            Paddle::move_vertically(PaddleDirection::Down, paddle, delta_time);
-        }
+    if (target - axis.along(paddle.translation.truncate())).abs() < Paddle::AI_DEADZONE {
+        // the paddle is already aligned with the target position.
+        return;
     }
 
-    Paddle::clamp_position(paddle);
+    Paddle::move_towards(
+        paddle,
+        axis,
+        target,
+        controller.profile().max_speed * effects.speed_scale(),
+        delta_time,
+    );
+    Paddle::clamp_position(paddle, axis, arena);
 }
 
 #[expect(clippy::type_complexity)]
 pub fn move_paddle_by_ai(
-    ball: Single<(&Transform, &LinearVelocity), With<Ball>>,
-    paddles: Single<&mut Transform, (With<Paddle>, With<PlayerAI>, Without<Ball>)>,
+    balls: Query<(&Transform, &LinearVelocity), With<Ball>>,
+    mut paddles: Query<
+        (&mut Transform, &Player, &mut AIController, &PaddleEffects),
+        (With<Paddle>, With<PlayerAI>, Without<Ball>),
+    >,
+    four_players: Res<FourPlayers>,
+    layout: Res<ArenaLayout>,
+    mut rng: ResMut<GameRng>,
     time: Res<Time<Fixed>>,
 ) {
-    let (ball_transform, ball_velocity) = ball.into_inner();
-    let mut paddle_transform = paddles.into_inner();
-
-    paddle_ai_movement(
-        &mut paddle_transform,
-        ball_transform,
-        ball_velocity,
-        time.delta_secs(),
-    );
+    let balls: Vec<_> = balls.iter().collect();
+
+    for (mut paddle_transform, player, mut controller, effects) in &mut paddles {
+        paddle_ai_movement(
+            (&mut paddle_transform, effects),
+            player,
+            &mut controller,
+            &balls,
+            (layout.size, !four_players.enabled),
+            &mut rng,
+            time.delta_secs(),
+        );
+    }
 }
 
+/// Moves every ball on its own, the balls do not collide with each other.
 #[expect(clippy::type_complexity)]
 pub fn move_ball(
-    ball: Single<(&mut Transform, &mut LinearVelocity), With<Ball>>,
-    colliders: Query<(&Transform, Option<&Paddle>), (With<Collider>, Without<Ball>)>,
+    mut balls: Query<
+        (
+            &mut Transform,
+            &mut LinearVelocity,
+            &mut LastHit,
+            &mut BallCurve,
+        ),
+        With<Ball>,
+    >,
+    colliders: Query<
+        (&Transform, Option<(&Player, &PaddleEffects)>),
+        (With<Collider>, Without<Ball>),
+    >,
+    paddle_bounce: Res<PaddleBounce>,
+    mut progress: ResMut<ExtraBallProgress>,
+    mut stats: ResMut<MatchStats>,
+    mut rng: ResMut<GameRng>,
     time: Res<Time<Fixed>>,
 ) {
-    let (mut transform, mut velocity) = ball.into_inner();
+    let rng = rng.as_mut();
+
+    // the side of each paddle, `None` for the walls and shields.
+    let bounding_boxes: Vec<(Option<PlayerSide>, Aabb2d)> = colliders
+        .iter()
+        .map(|(collider, paddle)| {
+            (
+                paddle.map(|(player, _)| player.get_side()),
+                Collider::bounding_box(collider),
+            )
+        })
+        .collect();
+
+    // sides of the paddles curving the balls they hit.
+    let curving_sides: Vec<PlayerSide> = colliders
+        .iter()
+        .filter_map(|(_, paddle)| paddle)
+        .filter(|(_, effects)| effects.is_active(PaddleEffect::Curve))
+        .map(|(player, _)| player.get_side())
+        .collect();
+    let shot_curve = |side: PlayerSide, velocity: Vec2| {
+        if curving_sides.contains(&side) {
+            BallCurve::for_shot(velocity, PaddleAxis::of(side))
+        } else {
+            BallCurve::default()
+        }
+    };
 
-    transform.translation += velocity.0.extend(0.0) * time.delta_secs();
+    for (mut transform, mut velocity, mut last_hit, mut curve) in &mut balls {
+        curve.apply(&mut velocity.0, time.delta_secs());
+
+        let radius = Ball::bounding_circle(&transform).radius();
+
+        sweep_ball(
+            &mut transform,
+            &mut velocity,
+            radius,
+            time.delta_secs(),
+            &bounding_boxes,
+            |contact, velocity| {
+                let Some(side) = contact.collider else {
+                    return;
+                };
+                last_hit.0 = Some(side);
+                progress.paddle_hits += 1;
+                stats.register_hit(side);
+
+                // only the paddle face changes the ball direction, not its edges.
+                let axis = PaddleAxis::of(side);
+                if axis.across(contact.normal) != 0.0 {
+                    let offset =
+                        Paddle::contact_offset(&contact.bounding_box, axis, contact.position);
+                    paddle_bounce.apply(axis, offset, velocity, rng);
+                }
+                *curve = shot_curve(side, velocity.0);
+            },
+        );
 
-    velocity.0 *= Ball::ACCELERATION_PERCENT * time.delta_secs() + 1.0;
-    Ball::limit_velocity(&mut velocity);
+        velocity.0 *= Ball::ACCELERATION_PERCENT * time.delta_secs() + 1.0;
+        Ball::limit_velocity(&mut velocity);
 
-    let bounding_ball = Ball::bounding_circle(&transform);
+        // resolve the overlaps left by moving colliders, like a paddle moving into the ball.
+        let bounding_ball = Ball::bounding_circle(&transform);
 
-    for (collider, paddle) in &colliders {
-        let bounding_box = Aabb2d::new(
-            collider.translation.truncate(),
-            collider.scale.truncate() * 0.5,
-        );
+        for (side, bounding_box) in &bounding_boxes {
+            let offset = match ball_collision(&bounding_ball, bounding_box) {
+                None => continue,
+                Some(offset) => offset,
+            };
 
-        let offset = match ball_collision(&bounding_ball, &bounding_box) {
-            None => continue,
-            Some(offset) => offset,
-        };
+            resolve_ball_collision(offset, &mut transform, &mut velocity);
+
+            if let Some(side) = *side {
+                last_hit.0 = Some(side);
+                progress.paddle_hits += 1;
+                stats.register_hit(side);
+
+                let axis = PaddleAxis::of(side);
+                let offset =
+                    Paddle::contact_offset(bounding_box, axis, transform.translation.truncate());
+                paddle_bounce.apply(axis, offset, &mut velocity, rng);
+                *curve = shot_curve(side, velocity.0);
+            }
+        }
+
+        Ball::limit_velocity(&mut velocity);
+    }
+}
 
-        resolve_ball_collision(offset, &mut transform, &mut velocity);
+/// Ticks the paddle effects, resizing the paddles by them.
+pub fn update_paddle_effects(
+    mut paddles: Query<(&mut Transform, &Player, &mut PaddleEffects), With<Paddle>>,
+    layout: Res<ArenaLayout>,
+    time: Res<Time<Fixed>>,
+) {
+    for (mut transform, player, mut effects) in &mut paddles {
+        effects.tick(time.delta_secs());
 
-        if paddle.is_some() {
-            // apply a random Y direction when the ball bounces off the paddle.
-            let start = f32::min(-velocity.x, velocity.x);
-            let end = -start;
+        let axis = player.paddle_axis();
+        Paddle::set_length_scale(&mut transform, axis, effects.length_scale());
+        Paddle::clamp_position(&mut transform, axis, layout.size);
+    }
+}
 
-            velocity.y = rand::thread_rng().gen_range(start..end) * 0.8;
+/// Places a shield behind the goal of each paddle with an active [`PaddleEffect::Shield`].
+pub fn update_shields(
+    arena: Single<Entity, With<Arena>>,
+    paddles: Query<(&Player, &PaddleEffects), With<Paddle>>,
+    shields: Query<(Entity, &Shield)>,
+    layout: Res<ArenaLayout>,
+    mut commands: Commands,
+) {
+    for (player, effects) in &paddles {
+        let side = player.get_side();
+        let shield = shields.iter().find(|(_, shield)| shield.0 == side);
+
+        match (effects.is_active(PaddleEffect::Shield), shield) {
+            (true, None) => {
+                commands.entity(*arena).with_child((
+                    Shield(side),
+                    Collider,
+                    Shield::transform(side, &layout),
+                ));
+            }
+            (false, Some((entity, _))) => commands.entity(entity).despawn_recursive(),
+            _ => {}
         }
     }
+}
 
-    Ball::limit_velocity(&mut velocity);
+/// Spawns a random power-up in the middle of the arena every [`PowerUp::SPAWN_SECONDS`] of play.
+pub fn spawn_power_up(
+    arena: Single<Entity, With<Arena>>,
+    power_ups: Query<(), With<PowerUp>>,
+    layout: Res<ArenaLayout>,
+    time: Res<Time>,
+    mut timer: ResMut<PowerUpTimer>,
+    mut rng: ResMut<GameRng>,
+    mut commands: Commands,
+) {
+    if !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+    if power_ups.iter().count() >= PowerUp::MAX_POWER_UPS {
+        return;
+    }
+
+    let effect = rng.gen::<PaddleEffect>();
+    let transform = PowerUp::random_transform(rng.as_mut(), layout.size);
+    commands
+        .entity(*arena)
+        .with_child((PowerUp(effect), transform));
 }
 
-pub fn check_ball_leaved_arena(
-    ball: Single<(&mut Transform, &mut LinearVelocity), With<Ball>>,
-    mut point_event: EventWriter<PointMarked>,
+/// Gives the power-ups a ball passes through to the last player who hit it.
+///
+/// A ball not hit since its serve passes through them without collecting.
+pub fn collect_power_ups(
+    balls: Query<(&Transform, &LastHit), With<Ball>>,
+    power_ups: Query<(Entity, &Transform, &PowerUp)>,
+    mut paddles: Query<(&Player, &mut PaddleEffects), With<Paddle>>,
+    mut commands: Commands,
 ) {
-    let (mut transform, mut velocity) = ball.into_inner();
+    let mut collected = Vec::new();
+
+    for (ball, last_hit) in &balls {
+        let Some(collector) = last_hit.0 else {
+            continue;
+        };
+
+        for (entity, power_up_transform, power_up) in &power_ups {
+            if collected.contains(&entity) || !ball_touches_power_up(ball, power_up_transform) {
+                continue;
+            }
+            collected.push(entity);
+            commands.entity(entity).despawn_recursive();
+
+            let effect = power_up.0;
+            for (player, mut effects) in &mut paddles {
+                let side = player.get_side();
+                if (side == collector) != effect.targets_opponents() {
+                    effects.add(effect);
+                }
+            }
+        }
+    }
+}
 
-    if Arena::SIZE.x / 2.0 - Wall::THICKNESS <= transform.translation.x.abs() {
-        let winner_side = if transform.translation.x < 0.0 {
-            // Ball left, point goes to player other
+/// Side defending the edge the ball left the arena by, if it did.
+///
+/// The top and bottom edges are only open in the four players mode.
+fn conceding_side(position: Vec3, four_players: &FourPlayers, arena: Vec2) -> Option<PlayerSide> {
+    if arena.x / 2.0 - Wall::THICKNESS <= position.x.abs() {
+        Some(if position.x < 0.0 {
+            PlayerSide::Main
+        } else {
             PlayerSide::Other
+        })
+    } else if four_players.enabled && arena.y / 2.0 - Wall::THICKNESS <= position.y.abs() {
+        Some(if position.y < 0.0 {
+            PlayerSide::Bottom
         } else {
-            // Ball right, point goes to player main
-            PlayerSide::Main
+            PlayerSide::Top
+        })
+    } else {
+        None
+    }
+}
+
+/// Gives the point to the last side that hit the ball,
+/// or to the opposite side when the ball left by the edge of its last hitter or was never hit.
+///
+/// Each ball scores on its own: it is removed while other balls are in play,
+/// and the last one goes back to the center of the arena.
+pub fn check_ball_leaved_arena(
+    mut balls: Query<(Entity, &mut Transform, &mut LinearVelocity, &mut LastHit), With<Ball>>,
+    four_players: Res<FourPlayers>,
+    layout: Res<ArenaLayout>,
+    mut point_event: EventWriter<PointMarked>,
+    mut commands: Commands,
+) {
+    let mut in_play = balls.iter().count();
+
+    for (entity, mut transform, mut velocity, mut last_hit) in &mut balls {
+        let Some(conceding_side) =
+            conceding_side(transform.translation, &four_players, layout.size)
+        else {
+            continue;
+        };
+
+        let winner_side = match last_hit.0 {
+            Some(side) if side != conceding_side => side,
+            _ => conceding_side.opponent(),
         };
 
         point_event.send(PointMarked::new(winner_side));
 
-        Ball::reset_initial_stationary_position(&mut transform, &mut velocity);
+        if in_play > 1 {
+            commands.entity(entity).despawn();
+            in_play -= 1;
+        } else {
+            Ball::reset_initial_stationary_position(&mut transform, &mut velocity);
+            last_hit.0 = None;
+        }
     }
 }
 
 pub fn register_score_point(
-    second_player: Res<SecondPlayer>,
     mut game_data: ResMut<GameActiveData>,
     mut point_event: EventReader<PointMarked>,
     mut game_data_update: EventWriter<GameDataUpdated>,
 ) {
     for event in point_event.read() {
-        let player = event.winner.to_player_type(second_player.opponent);
-        game_data.register_point(player);
+        game_data.register_point(event.winner);
 
         game_data_update.send(GameDataUpdated);
     }
 }
+
+/// Adds the time of the fixed update to the match.
+pub fn advance_match_time(time: Res<Time<Fixed>>, mut stats: ResMut<MatchStats>) {
+    stats.advance(time.delta());
+}
+
+/// Keeps the fastest ball speed.
+pub fn update_match_stats(
+    balls: Query<&LinearVelocity, With<Ball>>,
+    mut stats: ResMut<MatchStats>,
+) {
+    for velocity in &balls {
+        stats.register_ball_speed(velocity.0.length());
+    }
+}
+
+pub fn register_stats_point(
+    mut point_event: EventReader<PointMarked>,
+    mut stats: ResMut<MatchStats>,
+) {
+    for event in point_event.read() {
+        stats.register_point(event.winner);
+    }
+}
+
+pub fn check_match_over(
+    rules: Res<MatchRules>,
+    mut game_data: ResMut<GameActiveData>,
+    mut match_over_event: EventWriter<MatchOver>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    if let Some(winner) = rules.match_winner(game_data.score()) {
+        game_data.finish_match(winner);
+
+        match_over_event.send(MatchOver::new(winner));
+        next_game_state.set(GameState::MatchOver);
+    }
+}
+
+pub fn match_in_progress(game_data: Res<GameActiveData>) -> bool {
+    !game_data.is_match_over()
+}
+
+pub fn power_ups_enabled(power_ups: Res<PowerUps>) -> bool {
+    power_ups.enabled
+}
+
+/// Whether every ball is stationary, waiting for the next serve.
+pub fn round_over(balls: Query<&LinearVelocity, With<Ball>>) -> bool {
+    balls.iter().all(|velocity| velocity.0 == Vec2::ZERO)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn four_players() -> FourPlayers {
+        FourPlayers {
+            enabled: true,
+            ..default()
+        }
+    }
+
+    fn conceding(position: Vec2, four_players: &FourPlayers) -> Option<PlayerSide> {
+        conceding_side(position.extend(0.0), four_players, Arena::SIZE)
+    }
+
+    #[test]
+    fn left_and_right_edges_concede_for_the_main_and_other_sides() {
+        let edge = Arena::SIZE.x / 2.0;
+
+        for four_players in [FourPlayers::default(), four_players()] {
+            assert_eq!(
+                conceding(Vec2::new(-edge, 0.0), &four_players),
+                Some(PlayerSide::Main)
+            );
+            assert_eq!(
+                conceding(Vec2::new(edge, 0.0), &four_players),
+                Some(PlayerSide::Other)
+            );
+        }
+    }
+
+    #[test]
+    fn top_and_bottom_edges_concede_in_the_four_players_mode() {
+        let edge = Arena::SIZE.y / 2.0;
+
+        assert_eq!(
+            conceding(Vec2::new(0.0, edge), &four_players()),
+            Some(PlayerSide::Top)
+        );
+        assert_eq!(
+            conceding(Vec2::new(0.0, -edge), &four_players()),
+            Some(PlayerSide::Bottom)
+        );
+        // the walls close them in a classic match.
+        assert_eq!(
+            conceding(Vec2::new(0.0, edge), &FourPlayers::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn ball_inside_the_arena_concedes_nothing() {
+        assert_eq!(conceding(Vec2::new(10.0, 5.0), &four_players()), None);
+        assert_eq!(conceding(Vec2::ZERO, &four_players()), None);
+    }
+
+    #[test]
+    fn ball_never_hit_scores_for_the_opposite_side() {
+        assert_eq!(PlayerSide::Top.opponent(), PlayerSide::Bottom);
+        assert_eq!(PlayerSide::Bottom.opponent(), PlayerSide::Top);
+        assert_eq!(PlayerSide::Main.opponent(), PlayerSide::Other);
+    }
+}
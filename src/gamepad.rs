@@ -1,16 +1,107 @@
 use bevy::{input::gamepad::GamepadConnectionEvent, prelude::*};
 
-use crate::game::resource::UserGamepad;
+use crate::game::{
+    player::{Player, PlayerAI},
+    resource::{PlayerDevice, PlayerDevices},
+    state::GameState,
+};
 
-pub fn setup_gamepad_connection(
+/// Pauses the match when the gamepad of a human player is disconnected.
+///
+/// The gamepad keeps its player, and controls it again once reconnected.
+pub fn pause_on_gamepad_disconnect(
     mut gamepad_event: EventReader<GamepadConnectionEvent>,
-    mut user_gamepad: ResMut<UserGamepad>,
+    players: Query<&Player, Without<PlayerAI>>,
+    devices: Res<PlayerDevices>,
+    game_state: Res<State<GameState>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
 ) {
-    for event in gamepad_event.read() {
-        if event.connected() {
-            user_gamepad.add_gamepad(event.gamepad);
-        } else {
-            user_gamepad.remove_gamepad(event.gamepad);
-        }
+    let disconnected = gamepad_event
+        .read()
+        .filter(|event| event.disconnected())
+        .filter_map(|event| devices.side_of(PlayerDevice::Gamepad(event.gamepad)))
+        .any(|side| players.iter().any(|player| player.get_input_side() == side));
+
+    if disconnected && *game_state.get() == GameState::playing() {
+        next_game_state.set(GameState::GameActive { playing: false });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{input::gamepad::GamepadConnection, state::app::StatesPlugin};
+
+    use super::*;
+    use crate::game::player::{PlayerSide, PlayerType};
+
+    fn app(gamepad: Entity) -> App {
+        let mut app = App::new();
+        app.add_plugins(StatesPlugin)
+            .insert_state(GameState::playing())
+            .add_event::<GamepadConnectionEvent>()
+            .add_systems(Update, pause_on_gamepad_disconnect);
+
+        let mut devices = PlayerDevices::default();
+        devices.claim(PlayerSide::Main, PlayerDevice::Gamepad(gamepad));
+        app.insert_resource(devices);
+        app.world_mut()
+            .spawn(Player::new(PlayerSide::Main, PlayerType::Main));
+
+        app
+    }
+
+    fn send_connection(app: &mut App, gamepad: Entity, connection: GamepadConnection) {
+        app.world_mut()
+            .send_event(GamepadConnectionEvent::new(gamepad, connection));
+        // the next state is applied on the following update.
+        app.update();
+        app.update();
+    }
+
+    #[test]
+    fn reconnecting_the_gamepad_restores_its_side() {
+        let gamepad = Entity::from_raw(1);
+        let mut app = app(gamepad);
+
+        send_connection(&mut app, gamepad, GamepadConnection::Disconnected);
+
+        assert_eq!(
+            *app.world().resource::<State<GameState>>().get(),
+            GameState::GameActive { playing: false }
+        );
+        let devices = app.world().resource::<PlayerDevices>();
+        assert_eq!(
+            devices.side_of(PlayerDevice::Gamepad(gamepad)),
+            Some(PlayerSide::Main)
+        );
+
+        send_connection(
+            &mut app,
+            gamepad,
+            GamepadConnection::Connected {
+                name: "Gamepad".to_string(),
+                vendor_id: None,
+                product_id: None,
+            },
+        );
+
+        let devices = app.world().resource::<PlayerDevices>();
+        assert_eq!(devices.get_gamepad(PlayerSide::Main), Some(gamepad));
+    }
+
+    #[test]
+    fn disconnecting_an_unclaimed_gamepad_keeps_playing() {
+        let mut app = app(Entity::from_raw(1));
+
+        send_connection(
+            &mut app,
+            Entity::from_raw(2),
+            GamepadConnection::Disconnected,
+        );
+
+        assert_eq!(
+            *app.world().resource::<State<GameState>>().get(),
+            GameState::playing()
+        );
     }
 }
@@ -0,0 +1,212 @@
+use core::time::Duration;
+
+use bevy::{
+    app::{PluginsState, ScheduleRunnerPlugin},
+    input::InputPlugin,
+    prelude::*,
+    state::app::StatesPlugin,
+    time::TimeUpdateStrategy,
+};
+
+use crate::{
+    game::{
+        self,
+        layout::ArenaLayout,
+        player::{AIDifficulty, MainPlayerType, PlayerSide, SecondPlayerType},
+        resource::{
+            FourPlayers, GameActiveData, GameRng, GameScore, MainPlayer, MatchRules, MultiBall,
+            PowerUps, SecondPlayer,
+        },
+        state::GameState,
+        stats::MatchStats,
+    },
+    settings::time::FIXED_UPDATE_HZ,
+};
+
+/// Options of a match simulated without a window or renderer.
+#[derive(Clone)]
+pub struct HeadlessOptions {
+    /// Seed of the gameplay randomness, random when not given.
+    pub seed: Option<u64>,
+    pub main: MainPlayerType,
+    pub opponent: SecondPlayerType,
+    pub difficulty: AIDifficulty,
+    pub rules: MatchRules,
+    pub four_players: FourPlayers,
+    pub multi_ball: MultiBall,
+    pub power_ups: PowerUps,
+    pub layout: ArenaLayout,
+    /// Maximum number of fixed updates before giving up the match.
+    pub max_ticks: u64,
+}
+
+impl HeadlessOptions {
+    /// Thirty minutes of game time.
+    pub const DEFAULT_MAX_TICKS: u64 = FIXED_UPDATE_HZ as u64 * 60 * 30;
+}
+
+impl Default for HeadlessOptions {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            main: MainPlayerType::AI,
+            opponent: SecondPlayerType::AI,
+            difficulty: AIDifficulty::default(),
+            rules: MatchRules::default(),
+            four_players: FourPlayers::default(),
+            multi_ball: MultiBall::default(),
+            power_ups: PowerUps::default(),
+            layout: ArenaLayout::default(),
+            max_ticks: Self::DEFAULT_MAX_TICKS,
+        }
+    }
+}
+
+/// Result of a match simulated without a window or renderer.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MatchSummary {
+    pub seed: u64,
+    pub winner: PlayerSide,
+    pub score: GameScore,
+    /// Number of fixed updates simulated.
+    pub ticks: u64,
+    pub stats: MatchStats,
+}
+
+/// Number of fixed updates run by the headless app.
+#[derive(Clone, Copy, Default, Resource)]
+pub struct HeadlessTicks(pub u64);
+
+/// Creates an app with the game logic, without a window or renderer.
+///
+/// Every update of the app advances exactly one fixed timestep, running the game as fast as possible.
+pub fn app(options: &HeadlessOptions) -> App {
+    let mut app = App::new();
+
+    let fixed_time = Time::<Fixed>::from_hz(FIXED_UPDATE_HZ);
+
+    app.add_plugins((
+        MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::ZERO)),
+        StatesPlugin,
+        InputPlugin,
+    ));
+
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(fixed_time.timestep()));
+    app.insert_resource(fixed_time);
+
+    app.insert_resource(match options.seed {
+        Some(seed) => GameRng::new(seed),
+        None => GameRng::from_entropy(),
+    });
+    app.insert_resource(MainPlayer { kind: options.main });
+    app.insert_resource(SecondPlayer {
+        opponent: options.opponent,
+    });
+    app.insert_resource(options.difficulty);
+    app.insert_resource(options.rules);
+    app.insert_resource(options.four_players);
+    app.insert_resource(options.multi_ball);
+    app.insert_resource(options.power_ups);
+    app.insert_resource(options.layout.clone());
+    app.init_resource::<HeadlessTicks>();
+
+    app.add_plugins(game::plugin);
+
+    app.add_systems(Startup, start_game);
+    app.add_systems(FixedFirst, count_ticks);
+
+    app
+}
+
+pub fn start_game(mut next_game_state: ResMut<NextState<GameState>>) {
+    next_game_state.set(GameState::playing());
+}
+
+pub fn count_ticks(mut ticks: ResMut<HeadlessTicks>) {
+    ticks.0 += 1;
+}
+
+/// Updates the app until the match is over.
+///
+/// Returns `None` if the match is not over after `max_ticks` fixed updates.
+/// It can be called again for the next match, once the game state is back to playing.
+pub fn run_match(app: &mut App, max_ticks: u64) -> Option<MatchSummary> {
+    if app.plugins_state() != PluginsState::Cleaned {
+        while app.plugins_state() == PluginsState::Adding {
+            bevy::tasks::tick_global_task_pools_on_main_thread();
+        }
+        app.finish();
+        app.cleanup();
+    }
+
+    loop {
+        app.update();
+
+        let world = app.world();
+        let ticks = world.resource::<HeadlessTicks>().0;
+        let game_data = world.resource::<GameActiveData>();
+
+        if let Some(winner) = game_data.match_winner() {
+            return Some(MatchSummary {
+                seed: world.resource::<GameRng>().seed(),
+                winner,
+                score: game_data.score(),
+                ticks,
+                stats: world.resource::<MatchStats>().clone(),
+            });
+        }
+
+        if ticks >= max_ticks {
+            return None;
+        }
+    }
+}
+
+/// Simulates a full match without a window or renderer.
+pub fn simulate_match(options: &HeadlessOptions) -> Option<MatchSummary> {
+    let mut app = app(options);
+    run_match(&mut app, options.max_ticks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_simulates_the_same_match() {
+        let options = HeadlessOptions {
+            seed: Some(42),
+            difficulty: AIDifficulty::Easy,
+            rules: MatchRules::new(3, false),
+            ..default()
+        };
+
+        let summary = simulate_match(&options);
+
+        assert!(summary.is_some());
+        assert_eq!(simulate_match(&options), summary);
+    }
+
+    #[test]
+    fn consecutive_matches_have_different_seeds() {
+        let options = HeadlessOptions {
+            seed: Some(42),
+            difficulty: AIDifficulty::Easy,
+            rules: MatchRules::new(3, false),
+            ..default()
+        };
+        let mut app = app(&options);
+
+        let first = run_match(&mut app, options.max_ticks).expect("first match");
+        // leaves the match over, and starts the rematch.
+        app.update();
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::playing());
+        app.update();
+        let second = run_match(&mut app, options.max_ticks).expect("second match");
+
+        assert_eq!(first.seed, 42);
+        assert_ne!(second.seed, first.seed);
+    }
+}
@@ -0,0 +1,541 @@
+use core::{fmt, time::Duration};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::{
+        player::{MainPlayerType, PlayerSide, PlayerType, SecondPlayerType},
+        resource::{FourPlayers, GameActiveData, GameScore, MainPlayer, SecondPlayer},
+        state::InGame,
+        stats::MatchStats,
+    },
+    network::{client_in_match, NetworkClient},
+    replay::ReplayPlayback,
+    settings::storage,
+    spectator::spectating,
+};
+
+/// Kind of match, by the players taking part in it.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum MatchMode {
+    /// Two human players in the same instance.
+    TwoPlayers,
+    /// A human player against the AI, on either side.
+    AI,
+    AIVsAI,
+    FourPlayers,
+    /// A human player against the player of another instance.
+    Online,
+}
+
+impl MatchMode {
+    pub const ALL: [Self; 5] = [
+        Self::TwoPlayers,
+        Self::AI,
+        Self::AIVsAI,
+        Self::FourPlayers,
+        Self::Online,
+    ];
+
+    pub const fn new(
+        main: MainPlayerType,
+        opponent: SecondPlayerType,
+        four_players: &FourPlayers,
+    ) -> Self {
+        if four_players.enabled {
+            return Self::FourPlayers;
+        }
+
+        match (main, opponent) {
+            (_, SecondPlayerType::Remote) => Self::Online,
+            (MainPlayerType::Player, SecondPlayerType::Player) => Self::TwoPlayers,
+            (MainPlayerType::AI, SecondPlayerType::AI) => Self::AIVsAI,
+            _ => Self::AI,
+        }
+    }
+
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::TwoPlayers => "2 Players",
+            Self::AI => "AI",
+            Self::AIVsAI => "AI vs AI",
+            Self::FourPlayers => "4 Players",
+            Self::Online => "Online",
+        }
+    }
+}
+
+impl fmt::Display for MatchMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Player of a side in a recorded match.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct MatchPlayer {
+    pub side: PlayerSide,
+    pub player: PlayerType,
+}
+
+/// Result of a finished or abandoned match.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct MatchRecord {
+    /// End of the match, in seconds since the Unix epoch.
+    pub date: u64,
+    pub mode: MatchMode,
+    /// Player of each side, as seen from this instance.
+    pub players: Vec<MatchPlayer>,
+    pub score: GameScore,
+    /// Time the match was played, without the pauses.
+    pub duration: Duration,
+    /// Side of the winner, `None` when the match was abandoned.
+    pub winner: Option<PlayerSide>,
+}
+
+impl MatchRecord {
+    pub fn sides(&self) -> Vec<PlayerSide> {
+        self.players.iter().map(|player| player.side).collect()
+    }
+
+    /// Name of the player of a side, telling the side of the AI only when several AI players played.
+    pub fn player_name(&self, side: PlayerSide) -> &'static str {
+        let player = self
+            .players
+            .iter()
+            .find(|player| player.side == side)
+            .map_or(PlayerType::AI, |player| player.player);
+        let several_ai = self
+            .players
+            .iter()
+            .filter(|player| player.player == PlayerType::AI)
+            .count()
+            > 1;
+
+        match (player, side) {
+            (PlayerType::Main, _) => "Player 1",
+            (PlayerType::Second, _) => "Player 2",
+            (PlayerType::Third, _) => "Player 3",
+            (PlayerType::Fourth, _) => "Player 4",
+            (PlayerType::Remote, _) => "Remote player",
+            (PlayerType::AI, PlayerSide::Main) if several_ai => "Left AI",
+            (PlayerType::AI, PlayerSide::Other) if several_ai => "Right AI",
+            (PlayerType::AI, PlayerSide::Top) if several_ai => "Top AI",
+            (PlayerType::AI, PlayerSide::Bottom) if several_ai => "Bottom AI",
+            (PlayerType::AI, _) => "AI",
+        }
+    }
+
+    pub fn winner_name(&self) -> Option<&'static str> {
+        self.winner.map(|side| self.player_name(side))
+    }
+
+    /// Date of the end of the match, in UTC, as `YYYY-MM-DD HH:MM`.
+    pub fn date_text(&self) -> String {
+        let days = (self.date / 86_400) as i64;
+        let minutes = self.date % 86_400 / 60;
+        let (year, month, day) = civil_from_days(days);
+
+        format!(
+            "{year:04}-{month:02}-{day:02} {:02}:{:02}",
+            minutes / 60,
+            minutes % 60
+        )
+    }
+}
+
+/// Year, month and day of a number of days since the Unix epoch, in the proleptic Gregorian calendar.
+const fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+/// Wins of a player in the finished matches they played.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct WinRate {
+    pub name: &'static str,
+    pub wins: u32,
+    pub matches: u32,
+}
+
+impl WinRate {
+    /// Fraction of the matches won, from 0 to 1.
+    pub fn rate(&self) -> f32 {
+        if self.matches == 0 {
+            return 0.0;
+        }
+        self.wins as f32 / self.matches as f32
+    }
+}
+
+/// Version of the history file, read before the rest of the file.
+#[derive(Deserialize)]
+#[serde(rename = "MatchHistory")]
+struct HistoryHeader {
+    version: u32,
+}
+
+/// Results of the matches played in this game, oldest first.
+///
+/// The file is versioned: the files written by an older version of the game are converted when
+/// loaded, so a change of [`MatchHistory::VERSION`] must add the conversion of the previous one.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct MatchHistory {
+    pub version: u32,
+    pub matches: Vec<MatchRecord>,
+}
+
+impl Default for MatchHistory {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            matches: Vec::new(),
+        }
+    }
+}
+
+impl MatchHistory {
+    pub const VERSION: u32 = 1;
+    pub const FILE_NAME: &str = "history.ron";
+
+    /// Path of the history file, in the user data directory.
+    pub fn path() -> Option<PathBuf> {
+        storage::data_dir().map(|dir| dir.join(Self::FILE_NAME))
+    }
+
+    pub fn parse(content: &str) -> Result<Self, HistoryError> {
+        let header: HistoryHeader = ron::from_str(content)?;
+
+        match header.version {
+            Self::VERSION => Ok(ron::from_str(content)?),
+            version => Err(HistoryError::Version(version)),
+        }
+    }
+
+    /// Reads the history file, an empty history when it does not exist.
+    pub fn load(path: &Path) -> Result<Self, HistoryError> {
+        match fs::read_to_string(path) {
+            Ok(content) => Self::parse(&content),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), HistoryError> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let content = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        fs::write(path, content)?;
+
+        Ok(())
+    }
+
+    /// Adds a match to the history file.
+    ///
+    /// A file that can not be read, such as one written by a newer version, is left untouched.
+    pub fn append(path: &Path, record: MatchRecord) -> Result<(), HistoryError> {
+        let mut history = Self::load(path)?;
+        history.matches.push(record);
+        history.save(path)
+    }
+
+    /// Matches of the `mode`, or of every mode when not given.
+    pub fn matches(
+        &self,
+        mode: Option<MatchMode>,
+    ) -> impl DoubleEndedIterator<Item = &MatchRecord> {
+        self.matches
+            .iter()
+            .filter(move |record| mode.is_none_or(|mode| record.mode == mode))
+    }
+
+    /// Win rate of each player in the finished matches of the `mode`, from the best one.
+    ///
+    /// The abandoned matches are not counted.
+    pub fn win_rates(&self, mode: Option<MatchMode>) -> Vec<WinRate> {
+        let mut rates: Vec<WinRate> = Vec::new();
+
+        for record in self.matches(mode).filter(|record| record.winner.is_some()) {
+            for player in &record.players {
+                let name = record.player_name(player.side);
+                let won = record.winner == Some(player.side);
+
+                match rates.iter_mut().find(|rate| rate.name == name) {
+                    Some(rate) => {
+                        rate.matches += 1;
+                        rate.wins += u32::from(won);
+                    }
+                    None => rates.push(WinRate {
+                        name,
+                        wins: u32::from(won),
+                        matches: 1,
+                    }),
+                }
+            }
+        }
+
+        rates.sort_by(|a, b| {
+            b.rate()
+                .total_cmp(&a.rate())
+                .then(b.matches.cmp(&a.matches))
+        });
+        rates
+    }
+}
+
+#[derive(Debug)]
+pub enum HistoryError {
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+    /// The history was written by a newer version of the game.
+    Version(u32),
+}
+
+impl fmt::Display for HistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Parse(err) => write!(f, "invalid history file: {err}"),
+            Self::Serialize(err) => write!(f, "could not write history: {err}"),
+            Self::Version(version) => write!(
+                f,
+                "unsupported history version {version}, expected {} or older",
+                MatchHistory::VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for HistoryError {}
+
+impl From<io::Error> for HistoryError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<ron::error::SpannedError> for HistoryError {
+    fn from(err: ron::error::SpannedError) -> Self {
+        Self::Parse(err)
+    }
+}
+
+impl From<ron::Error> for HistoryError {
+    fn from(err: ron::Error) -> Self {
+        Self::Serialize(err)
+    }
+}
+
+/// Adds the match that just ended, or was abandoned, to the history file.
+pub fn record_match(
+    game_data: Res<GameActiveData>,
+    stats: Res<MatchStats>,
+    main_player: Res<MainPlayer>,
+    second_player: Res<SecondPlayer>,
+    four_players: Res<FourPlayers>,
+    client: Option<Res<NetworkClient>>,
+) {
+    // the client plays the right side of the host match, where the host sees the remote player.
+    let is_client = client_in_match(client);
+    let players = four_players
+        .sides()
+        .iter()
+        .map(|side| {
+            let player = match (is_client, side) {
+                (true, PlayerSide::Main) => PlayerType::Remote,
+                (true, PlayerSide::Other) => PlayerType::Main,
+                _ => side.to_player_type(main_player.kind, second_player.opponent, *four_players),
+            };
+            MatchPlayer {
+                side: *side,
+                player,
+            }
+        })
+        .collect();
+
+    let record = MatchRecord {
+        date: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_secs())
+            .unwrap_or_default(),
+        mode: MatchMode::new(main_player.kind, second_player.opponent, &four_players),
+        players,
+        score: game_data.score(),
+        duration: stats.duration(),
+        winner: game_data.match_winner(),
+    };
+
+    let Some(path) = MatchHistory::path() else {
+        warn!("no data directory to save the match history");
+        return;
+    };
+
+    if let Err(err) = MatchHistory::append(&path, record) {
+        warn!("could not add the match to {}: {err}", path.display());
+    }
+}
+
+/// Keeps the history of the matches played, not the replays watched or the streams spectated.
+pub fn plugin(app: &mut App) {
+    app.add_systems(
+        OnExit(InGame),
+        record_match.run_if(not(resource_exists::<ReplayPlayback>).and(not(spectating))),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(
+        mode: MatchMode,
+        players: &[(PlayerSide, PlayerType)],
+        winner: Option<PlayerSide>,
+    ) -> MatchRecord {
+        MatchRecord {
+            date: 0,
+            mode,
+            players: players
+                .iter()
+                .map(|(side, player)| MatchPlayer {
+                    side: *side,
+                    player: *player,
+                })
+                .collect(),
+            score: GameScore::default(),
+            duration: Duration::from_secs(90),
+            winner,
+        }
+    }
+
+    fn two_players(winner: Option<PlayerSide>) -> MatchRecord {
+        record(
+            MatchMode::TwoPlayers,
+            &[
+                (PlayerSide::Main, PlayerType::Main),
+                (PlayerSide::Other, PlayerType::Second),
+            ],
+            winner,
+        )
+    }
+
+    #[test]
+    fn parses_the_saved_history() {
+        let history = MatchHistory {
+            version: MatchHistory::VERSION,
+            matches: vec![two_players(Some(PlayerSide::Main)), two_players(None)],
+        };
+        let content =
+            ron::ser::to_string_pretty(&history, ron::ser::PrettyConfig::default()).unwrap();
+
+        assert_eq!(MatchHistory::parse(&content).unwrap(), history);
+    }
+
+    #[test]
+    fn rejects_a_newer_version() {
+        let content = format!(
+            "(version: {}, matches: [], added: true)",
+            MatchHistory::VERSION + 1
+        );
+
+        assert!(matches!(
+            MatchHistory::parse(&content),
+            Err(HistoryError::Version(version)) if version == MatchHistory::VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn rejects_an_invalid_file() {
+        assert!(matches!(
+            MatchHistory::parse("(matches: [])"),
+            Err(HistoryError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn win_rates_leave_out_the_abandoned_matches() {
+        let history = MatchHistory {
+            version: MatchHistory::VERSION,
+            matches: vec![
+                two_players(Some(PlayerSide::Main)),
+                two_players(Some(PlayerSide::Main)),
+                two_players(Some(PlayerSide::Other)),
+                two_players(None),
+                record(
+                    MatchMode::AI,
+                    &[
+                        (PlayerSide::Main, PlayerType::Main),
+                        (PlayerSide::Other, PlayerType::AI),
+                    ],
+                    Some(PlayerSide::Other),
+                ),
+            ],
+        };
+
+        assert_eq!(
+            history.win_rates(Some(MatchMode::TwoPlayers)),
+            [
+                WinRate {
+                    name: "Player 1",
+                    wins: 2,
+                    matches: 3
+                },
+                WinRate {
+                    name: "Player 2",
+                    wins: 1,
+                    matches: 3
+                },
+            ]
+        );
+        assert_eq!(history.win_rates(None)[0].name, "AI");
+        assert_eq!(history.matches(Some(MatchMode::AI)).count(), 1);
+    }
+
+    #[test]
+    fn names_the_ai_by_side_when_several_played() {
+        let record = record(
+            MatchMode::AIVsAI,
+            &[
+                (PlayerSide::Main, PlayerType::AI),
+                (PlayerSide::Other, PlayerType::AI),
+            ],
+            Some(PlayerSide::Other),
+        );
+
+        assert_eq!(record.player_name(PlayerSide::Main), "Left AI");
+        assert_eq!(record.winner_name(), Some("Right AI"));
+    }
+
+    #[test]
+    fn formats_the_date_in_utc() {
+        let mut record = two_players(None);
+
+        assert_eq!(record.date_text(), "1970-01-01 00:00");
+
+        record.date = 951_827_400;
+        assert_eq!(record.date_text(), "2000-02-29 12:30");
+    }
+}
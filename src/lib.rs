@@ -0,0 +1,12 @@
+pub mod camera;
+pub mod cli;
+pub mod game;
+pub mod gamepad;
+pub mod headless;
+pub mod history;
+pub mod network;
+pub mod replay;
+pub mod settings;
+pub mod spectator;
+pub mod tournament;
+pub mod ui;
@@ -1,26 +1,189 @@
+use core::time::Duration;
+
 use bevy::{input::gamepad::GamepadConnectionEvent, prelude::*};
 
-use game::resource::UserGamepad;
-use gamepad::setup_gamepad_connection;
-use settings::camera::orthographic_projection;
+use bevy_pong::{
+    camera,
+    cli::LaunchOptions,
+    game::{
+        self,
+        layout::{self, ArenaLayout},
+        resource::{FourPlayers, GameRng, PowerUps},
+    },
+    gamepad::pause_on_gamepad_disconnect,
+    headless::{self, HeadlessOptions},
+    history,
+    network::{self, LinkConditioner, NetworkClient, NetworkSettings},
+    replay::{self, WatchReplay},
+    settings::{self, time::FIXED_UPDATE_HZ},
+    spectator::{self, SpectatorClient, SpectatorStream},
+    tournament, ui,
+};
+
+fn main() -> AppExit {
+    let options = LaunchOptions::from_args();
 
-mod game;
-mod gamepad;
-mod settings;
-mod ui;
+    if options.headless {
+        return run_headless(&options);
+    }
 
-fn main() {
     let mut app = App::new();
 
+    if let Some(seed) = options.seed {
+        app.insert_resource(GameRng::new(seed));
+    }
+    if options.four_players {
+        app.insert_resource(FourPlayers {
+            enabled: true,
+            ..Default::default()
+        });
+    }
+    if let Some(multi_ball) = options.multi_ball {
+        app.insert_resource(multi_ball);
+    }
+    if options.power_ups {
+        app.insert_resource(PowerUps { enabled: true });
+    }
+    if let Some(layout) = launch_layout(&options) {
+        app.insert_resource(layout);
+    }
+    let network_settings = launch_network_settings(&options);
+    app.insert_resource(network_settings);
+    if let Some(address) = &options.join {
+        match NetworkClient::connect(address, network_settings.conditioner) {
+            Ok(client) => {
+                info!("joining the host {}", client.host());
+                app.insert_resource(client);
+            }
+            Err(err) => eprintln!("could not join {address}: {err}"),
+        }
+    }
+    if let Some(address) = &options.stream {
+        match SpectatorStream::bind(address) {
+            Ok(stream) => {
+                info!("streaming the game on {address}");
+                app.insert_resource(stream);
+            }
+            Err(err) => eprintln!("could not stream on {address}: {err}"),
+        }
+    }
+    if let Some(address) = &options.spectate {
+        match SpectatorClient::connect(address) {
+            Ok(client) => {
+                info!("spectating the stream of {}", client.address());
+                app.insert_resource(client);
+            }
+            Err(err) => eprintln!("could not spectate {address}: {err}"),
+        }
+    }
+
     app.add_plugins((
         app_plugin,
-        ui::main_menu::plugin,
+        settings::user::plugin,
+        camera::plugin,
         game::plugin,
-        ui::in_game::plugin,
-        ui::pause_menu::plugin,
+        game::render::plugin,
+        layout::plugin,
+        replay::plugin,
+        network::plugin,
+        spectator::plugin,
+        history::plugin,
+        tournament::plugin,
+        (
+            ui::component::focus::plugin,
+            ui::main_menu::plugin,
+            ui::lobby::plugin,
+            ui::controls::plugin,
+            ui::history::plugin,
+            ui::in_game::plugin,
+            ui::pause_menu::plugin,
+            ui::match_over::plugin,
+            ui::replay::plugin,
+            ui::tournament::plugin,
+        ),
     ));
 
-    app.run();
+    if let Some(path) = options.replay {
+        app.world_mut().send_event(WatchReplay { path: Some(path) });
+    }
+
+    app.run()
+}
+
+/// Reads the layout file given in the launch options, if any.
+fn launch_layout(options: &LaunchOptions) -> Option<ArenaLayout> {
+    let path = options.layout.as_ref()?;
+
+    match ArenaLayout::load(path) {
+        Ok(layout) => Some(layout),
+        Err(err) => {
+            eprintln!("could not load layout {}: {err}", path.display());
+            None
+        }
+    }
+}
+
+/// Network settings given in the launch options, the default ones otherwise.
+fn launch_network_settings(options: &LaunchOptions) -> NetworkSettings {
+    let defaults = NetworkSettings::default();
+
+    NetworkSettings {
+        port: options.port.unwrap_or(defaults.port),
+        netcode: options.netcode.unwrap_or(defaults.netcode),
+        input_delay: options.input_delay.unwrap_or(defaults.input_delay),
+        conditioner: LinkConditioner {
+            latency: Duration::from_millis(options.simulate_latency.unwrap_or(0)),
+            loss: f32::from(options.simulate_loss.unwrap_or(0).min(100)) / 100.0,
+        },
+    }
+}
+
+fn run_headless(options: &LaunchOptions) -> AppExit {
+    let options = HeadlessOptions {
+        seed: options.seed,
+        four_players: FourPlayers {
+            enabled: options.four_players,
+            ..Default::default()
+        },
+        multi_ball: options.multi_ball.unwrap_or_default(),
+        power_ups: PowerUps {
+            enabled: options.power_ups,
+        },
+        layout: launch_layout(options).unwrap_or_default(),
+        ..Default::default()
+    };
+
+    match headless::simulate_match(&options) {
+        Some(summary) => {
+            println!(
+                "seed {}: {} side won {} after {} ticks",
+                summary.seed,
+                summary.winner,
+                summary.score.format(options.four_players.sides()),
+                summary.ticks,
+            );
+            let stats = &summary.stats;
+            println!(
+                "  {} rallies, longest {} hits, average {:.1} hits, fastest ball {:.1}, points on serve {}",
+                stats.rallies().len(),
+                stats.longest_rally(),
+                stats.average_rally(),
+                stats.fastest_ball(),
+                options
+                    .four_players
+                    .sides()
+                    .iter()
+                    .map(|side| stats.serve_points(*side).to_string())
+                    .collect::<Vec<_>>()
+                    .join(" - "),
+            );
+            AppExit::Success
+        }
+        None => {
+            eprintln!("match not finished after {} ticks", options.max_ticks);
+            AppExit::error()
+        }
+    }
 }
 
 fn app_plugin(app: &mut App) {
@@ -32,15 +195,12 @@ fn app_plugin(app: &mut App) {
     #[cfg(feature = "devtools")]
     app.add_plugins(devtools_plugin);
 
-    app.insert_resource(Time::<Fixed>::from_hz(64.0));
+    app.insert_resource(Time::<Fixed>::from_hz(FIXED_UPDATE_HZ));
     app.insert_resource(ClearColor(Color::BLACK));
-    app.init_resource::<UserGamepad>();
-
-    app.add_systems(Startup, spawn_camera);
 
     app.add_systems(
         Update,
-        setup_gamepad_connection.run_if(on_event::<GamepadConnectionEvent>),
+        pause_on_gamepad_disconnect.run_if(on_event::<GamepadConnectionEvent>),
     );
 }
 
@@ -50,7 +210,3 @@ pub fn devtools_plugin(app: &mut App) {
 
     app.add_plugins(FrameTimeDiagnosticsPlugin);
 }
-
-pub fn spawn_camera(mut commands: Commands) {
-    commands.spawn((Camera2d, orthographic_projection()));
-}
@@ -0,0 +1,1303 @@
+use core::{str::FromStr, time::Duration};
+use std::{
+    collections::VecDeque,
+    io,
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+    sync::Mutex,
+    time::Instant,
+};
+
+use bevy::prelude::*;
+use rollback::{PeerInputs, RollbackSession};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    game::{
+        arena::{Arena, Ball, Paddle},
+        event::{GameDataUpdated, MatchOver},
+        physics::LinearVelocity,
+        player::{PaddleInput, Player, PlayerRemote, PlayerSide},
+        power_up::{PaddleEffect, PaddleEffects, PowerUp},
+        resource::{
+            GameActiveData, GameRng, PlayerDevice, PlayerDevices, SecondPlayer, StartMatchTimer,
+        },
+        state::{GameActiveState, GameState, InGame},
+        stats::MatchStats,
+        system::{read_player_input, update_shields},
+        GameSystems,
+    },
+    replay::{record_player_input, start_recording, MatchSettings, ReplayRecorder, ReplaySettings},
+    settings::input::InputSettings,
+    ui::main_menu::spawn_main_menu,
+};
+
+pub mod rollback;
+
+/// Version of the messages, the host only accepts clients of the same version.
+pub const PROTOCOL_VERSION: u32 = 3;
+pub const DEFAULT_PORT: u16 = 7878;
+/// Ticks between the input of a player and its paddle moving, in the [`Netcode::Rollback`] matches.
+pub const DEFAULT_INPUT_DELAY: u32 = 2;
+/// Time without messages after which the other instance is disconnected.
+pub const TIMEOUT: Duration = Duration::from_secs(5);
+/// Time between the join requests of a client waiting for the host.
+pub const JOIN_INTERVAL: Duration = Duration::from_secs(1);
+/// Snapshots between each resend of the match start, until the client plays the match.
+pub const START_INTERVAL: u64 = 16;
+/// Snapshots the client shows behind the latest one, to always have two snapshots to interpolate.
+pub const INTERPOLATION_DELAY: f64 = 4.0;
+/// Most snapshots kept by the client.
+const MAX_SNAPSHOTS: usize = 64;
+const MAX_MESSAGE_SIZE: usize = 65_536;
+
+/// How the online matches are simulated, chosen by the host.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Netcode {
+    /// The host simulates the match and sends its state to the client.
+    #[default]
+    Server,
+    /// Both instances simulate the match from the inputs of every player, see [`rollback`].
+    Rollback,
+}
+
+impl FromStr for Netcode {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "server" => Ok(Self::Server),
+            "rollback" => Ok(Self::Rollback),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Simulated latency and loss of the sent messages, to try the online play on loopback.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct LinkConditioner {
+    /// Delay before each message is sent.
+    pub latency: Duration,
+    /// Part of the messages dropped, from 0 to 1.
+    pub loss: f32,
+}
+
+/// Network options of the host and the client.
+#[derive(Clone, Copy, PartialEq, Debug, Resource)]
+pub struct NetworkSettings {
+    /// UDP port the host listens on.
+    pub port: u16,
+    pub netcode: Netcode,
+    /// Ticks the local inputs are delayed by, in the [`Netcode::Rollback`] matches.
+    pub input_delay: u32,
+    pub conditioner: LinkConditioner,
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        Self {
+            port: DEFAULT_PORT,
+            netcode: Netcode::default(),
+            input_delay: DEFAULT_INPUT_DELAY,
+            conditioner: LinkConditioner::default(),
+        }
+    }
+}
+
+/// Message sent by the client to the host.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum ClientMessage {
+    /// Asks to play as the remote player of the host.
+    Join {
+        version: u32,
+    },
+    /// Paddle input of the remote player, sent every frame.
+    Input {
+        /// Match played by the client, acknowledging its [`MatchStart`].
+        match_id: Option<u32>,
+        input: f32,
+    },
+    /// Paddle inputs of the client, in the [`Netcode::Rollback`] matches.
+    Inputs(PeerInputs),
+    Leave,
+}
+
+/// Message sent by the host to the client.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum HostMessage {
+    Accepted,
+    Rejected(String),
+    Start(MatchStart),
+    State(Snapshot),
+    /// Paddle inputs of the host, in the [`Netcode::Rollback`] matches.
+    Inputs(PeerInputs),
+    /// Keeps the connection alive when the host does not send its state.
+    KeepAlive,
+    Leave,
+}
+
+/// Settings of a match started by the host, sent until the client plays it.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct MatchStart {
+    pub match_id: u32,
+    pub seed: u64,
+    /// Duration of each fixed update of the host, between two snapshots.
+    pub timestep: Duration,
+    pub settings: ReplaySettings,
+    pub netcode: Netcode,
+    /// Ticks the local inputs are delayed by, in the [`Netcode::Rollback`] matches.
+    pub input_delay: u32,
+}
+
+/// State of the host game, told to the client.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum RemotePhase {
+    /// The host is not playing a match.
+    Lobby,
+    Playing,
+    Paused,
+    MatchOver,
+}
+
+impl RemotePhase {
+    pub const fn of(state: &GameState) -> Self {
+        match *state {
+            GameState::MainMenu | GameState::Lobby | GameState::Tournament => Self::Lobby,
+            GameState::GameActive { playing: true } => Self::Playing,
+            GameState::GameActive { playing: false } => Self::Paused,
+            GameState::MatchOver => Self::MatchOver,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct PaddleState {
+    pub side: PlayerSide,
+    pub position: Vec2,
+    pub scale: Vec2,
+    pub effects: PaddleEffects,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct BallState {
+    /// Identifier of the ball on the host, kept while the ball is in play.
+    pub id: u32,
+    pub position: Vec2,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct PowerUpState {
+    pub effect: PaddleEffect,
+    pub position: Vec2,
+}
+
+/// State of the match on the host, sent every fixed update.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub match_id: u32,
+    /// Number of the snapshot in the match, increasing by one every fixed update.
+    pub sequence: u64,
+    pub phase: RemotePhase,
+    pub game_data: GameActiveData,
+    /// Elapsed seconds of the serve countdown, while it runs.
+    pub countdown: Option<f32>,
+    pub paddles: Vec<PaddleState>,
+    pub balls: Vec<BallState>,
+    pub power_ups: Vec<PowerUpState>,
+    /// Statistics of the match, as the client does not simulate it.
+    pub stats: MatchStats,
+}
+
+impl Snapshot {
+    pub fn paddle(&self, side: PlayerSide) -> Option<&PaddleState> {
+        self.paddles.iter().find(|paddle| paddle.side == side)
+    }
+
+    pub fn ball(&self, id: u32) -> Option<&BallState> {
+        self.balls.iter().find(|ball| ball.id == id)
+    }
+}
+
+/// Message held by the [`LinkConditioner`] until its send time.
+struct DelayedMessage {
+    send_at: Instant,
+    content: String,
+    address: SocketAddr,
+}
+
+/// Non-blocking UDP socket exchanging messages serialized as RON.
+pub struct NetworkSocket {
+    socket: UdpSocket,
+    conditioner: LinkConditioner,
+    delayed: Mutex<VecDeque<DelayedMessage>>,
+    /// Datagram being received, kept between the calls.
+    buffer: Vec<u8>,
+}
+
+impl NetworkSocket {
+    pub fn bind(address: impl ToSocketAddrs, conditioner: LinkConditioner) -> io::Result<Self> {
+        let socket = UdpSocket::bind(address)?;
+        socket.set_nonblocking(true)?;
+
+        Ok(Self {
+            socket,
+            conditioner,
+            delayed: Mutex::new(VecDeque::new()),
+            buffer: vec![0; MAX_MESSAGE_SIZE],
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Sends the message, only logging the errors as the messages can be lost anyway.
+    pub fn send<T: Serialize>(&self, message: &T, address: SocketAddr) {
+        let content = match ron::to_string(message) {
+            Ok(content) => content,
+            Err(err) => {
+                warn!("could not serialize message: {err}");
+                return;
+            }
+        };
+
+        if self.conditioner.loss > 0.0 && rand::random::<f32>() < self.conditioner.loss {
+            return;
+        }
+        if self.conditioner.latency.is_zero() {
+            self.send_content(&content, address);
+            return;
+        }
+
+        if let Ok(mut delayed) = self.delayed.lock() {
+            delayed.push_back(DelayedMessage {
+                send_at: Instant::now() + self.conditioner.latency,
+                content,
+                address,
+            });
+        }
+        self.send_delayed();
+    }
+
+    fn send_content(&self, content: &str, address: SocketAddr) {
+        if let Err(err) = self.socket.send_to(content.as_bytes(), address) {
+            warn!("could not send message to {address}: {err}");
+        }
+    }
+
+    /// Sends the messages held by the [`LinkConditioner`] whose send time has come.
+    fn send_delayed(&self) {
+        let Ok(mut delayed) = self.delayed.lock() else {
+            return;
+        };
+        let now = Instant::now();
+
+        while delayed
+            .front()
+            .is_some_and(|message| message.send_at <= now)
+        {
+            if let Some(message) = delayed.pop_front() {
+                self.send_content(&message.content, message.address);
+            }
+        }
+    }
+
+    /// Messages received since the last call, with their sender.
+    ///
+    /// Invalid messages are skipped.
+    pub fn receive<T: DeserializeOwned>(&mut self) -> Vec<(T, SocketAddr)> {
+        let mut messages = Vec::new();
+
+        self.send_delayed();
+
+        loop {
+            match self.socket.recv_from(&mut self.buffer) {
+                Ok((len, address)) => match ron::de::from_bytes(&self.buffer[..len]) {
+                    Ok(message) => messages.push((message, address)),
+                    Err(err) => warn!("invalid message from {address}: {err}"),
+                },
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                // a message sent before did not arrive, such as to a closed port.
+                Err(err) if err.kind() == io::ErrorKind::ConnectionReset => continue,
+                Err(err) => {
+                    warn!("could not receive messages: {err}");
+                    break;
+                }
+            }
+        }
+
+        messages
+    }
+}
+
+/// Client connected to the host, playing its remote player.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RemotePlayer {
+    pub address: SocketAddr,
+    /// Last paddle input received.
+    pub input: f32,
+    /// Match played by the client, once it received its start.
+    match_id: Option<u32>,
+    /// Real time of the last message received.
+    last_received: Duration,
+}
+
+/// Instance starting the matches with a remote player, and running their authoritative simulation
+/// with the [`Netcode::Server`].
+#[derive(Resource)]
+pub struct NetworkHost {
+    socket: NetworkSocket,
+    remote: Option<RemotePlayer>,
+    netcode: Netcode,
+    input_delay: u32,
+    /// Identifier of the current match, changed on every match start.
+    match_id: u32,
+    /// Snapshots sent in the current match.
+    sequence: u64,
+}
+
+impl NetworkHost {
+    /// Listens on the port of the `settings`, on every network interface.
+    pub fn bind(settings: &NetworkSettings) -> io::Result<Self> {
+        Ok(Self {
+            socket: NetworkSocket::bind(("0.0.0.0", settings.port), settings.conditioner)?,
+            remote: None,
+            netcode: settings.netcode,
+            input_delay: settings.input_delay,
+            match_id: 0,
+            sequence: 0,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    pub const fn remote(&self) -> Option<&RemotePlayer> {
+        self.remote.as_ref()
+    }
+
+    pub const fn netcode(&self) -> Netcode {
+        self.netcode
+    }
+
+    fn reject(&self, reason: &str, address: SocketAddr) {
+        info!("rejected remote player {address}: {reason}");
+        self.socket
+            .send(&HostMessage::Rejected(reason.to_string()), address);
+    }
+}
+
+/// Match of the host played by the client.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct RemoteMatch {
+    id: u32,
+    timestep: Duration,
+    netcode: Netcode,
+    input_delay: u32,
+}
+
+/// Instance playing the remote player of a host,
+/// showing the state received from it or simulating the match with it.
+#[derive(Resource)]
+pub struct NetworkClient {
+    socket: NetworkSocket,
+    host: SocketAddr,
+    accepted: bool,
+    /// Real time of the last message received, or of the last join request.
+    last_received: Duration,
+    last_join: Option<Duration>,
+    remote_match: Option<RemoteMatch>,
+    /// Phase of the host in the latest snapshot.
+    host_phase: Option<RemotePhase>,
+    snapshots: VecDeque<Snapshot>,
+    /// Sequence shown between the received snapshots, advancing with the time.
+    render_sequence: f64,
+    /// Settings, seed and timestep restored when the client leaves the host.
+    previous: Option<(ReplaySettings, u64, Duration)>,
+}
+
+impl NetworkClient {
+    /// Prepares to join the host at the `address`, such as `127.0.0.1:7878`.
+    pub fn connect(address: &str, conditioner: LinkConditioner) -> io::Result<Self> {
+        let host = address.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "the address has no host")
+        })?;
+        let local = if host.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+
+        Ok(Self {
+            socket: NetworkSocket::bind(local, conditioner)?,
+            host,
+            accepted: false,
+            last_received: Duration::ZERO,
+            last_join: None,
+            remote_match: None,
+            host_phase: None,
+            snapshots: VecDeque::new(),
+            render_sequence: 0.0,
+            previous: None,
+        })
+    }
+
+    pub const fn host(&self) -> SocketAddr {
+        self.host
+    }
+
+    pub const fn is_accepted(&self) -> bool {
+        self.accepted
+    }
+
+    pub fn match_id(&self) -> Option<u32> {
+        self.remote_match.map(|remote_match| remote_match.id)
+    }
+
+    fn send(&self, message: &ClientMessage) {
+        self.socket.send(message, self.host);
+    }
+
+    fn start_match(&mut self, start: &MatchStart) {
+        self.remote_match = Some(RemoteMatch {
+            id: start.match_id,
+            timestep: start.timestep,
+            netcode: start.netcode,
+            input_delay: start.input_delay,
+        });
+        self.host_phase = None;
+        self.snapshots.clear();
+        self.render_sequence = 0.0;
+    }
+
+    /// Keeps the snapshots of the current match, in order.
+    fn push(&mut self, snapshot: Snapshot) {
+        if self.match_id() != Some(snapshot.match_id) {
+            return;
+        }
+        if self
+            .snapshots
+            .back()
+            .is_some_and(|latest| latest.sequence >= snapshot.sequence)
+        {
+            return;
+        }
+
+        self.snapshots.push_back(snapshot);
+        if self.snapshots.len() > MAX_SNAPSHOTS {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// Advances the shown sequence by `delta`, returning the snapshots around it
+    /// with the interpolation factor between them.
+    fn advance(&mut self, delta: Duration) -> Option<(&Snapshot, &Snapshot, f32)> {
+        let timestep = self.remote_match?.timestep.as_secs_f64();
+        let latest = self.snapshots.back()?.sequence as f64;
+
+        self.render_sequence += delta.as_secs_f64() / timestep;
+        // catch up when too far behind, such as after lost snapshots,
+        // and never go past the latest snapshot.
+        let target = latest - INTERPOLATION_DELAY;
+        if self.render_sequence < target - INTERPOLATION_DELAY {
+            self.render_sequence = target;
+        }
+        self.render_sequence = self.render_sequence.min(latest);
+
+        while self.snapshots.len() > 1 && self.snapshots[1].sequence as f64 <= self.render_sequence
+        {
+            self.snapshots.pop_front();
+        }
+
+        let from = self.snapshots.front()?;
+        let to = self.snapshots.get(1).unwrap_or(from);
+        let factor = if to.sequence > from.sequence {
+            let progress = (self.render_sequence - from.sequence as f64)
+                / (to.sequence - from.sequence) as f64;
+            progress.clamp(0.0, 1.0) as f32
+        } else {
+            1.0
+        };
+
+        Some((from, to, factor))
+    }
+}
+
+/// Ball shown by the client, with the identifier of the ball on the host.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Component)]
+pub struct RemoteBall(pub u32);
+
+pub fn remote_opponent(second_player: Res<SecondPlayer>) -> bool {
+    second_player.opponent.is_remote()
+}
+
+/// Whether the client plays a match of the host, instead of its own.
+pub fn client_in_match(client: Option<Res<NetworkClient>>) -> bool {
+    client.is_some_and(|client| client.remote_match.is_some())
+}
+
+/// Whether the client shows a match simulated by the host, with the [`Netcode::Server`].
+pub fn client_mirrors_host(client: Option<Res<NetworkClient>>) -> bool {
+    client.is_some_and(|client| {
+        client
+            .remote_match
+            .is_some_and(|remote_match| remote_match.netcode == Netcode::Server)
+    })
+}
+
+/// Whether a match with the `second_player` can start, which needs a connected remote player.
+pub fn remote_player_ready(second_player: &SecondPlayer, host: Option<&NetworkHost>) -> bool {
+    !second_player.opponent.is_remote() || host.is_some_and(|host| host.remote.is_some())
+}
+
+pub fn start_hosting(
+    settings: Res<NetworkSettings>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut commands: Commands,
+) {
+    match NetworkHost::bind(&settings) {
+        Ok(host) => {
+            info!("hosting on port {}", settings.port);
+            commands.insert_resource(host);
+        }
+        Err(err) => {
+            warn!("could not host on port {}: {err}", settings.port);
+            next_game_state.set(GameState::MainMenu);
+        }
+    }
+}
+
+pub fn stop_hosting(host: Res<NetworkHost>, mut commands: Commands) {
+    if let Some(remote) = host.remote {
+        host.socket.send(&HostMessage::Leave, remote.address);
+    }
+
+    commands.remove_resource::<NetworkHost>();
+}
+
+/// Accepts a single remote player, and drops it after [`TIMEOUT`] without messages.
+pub fn receive_client_messages(
+    mut host: ResMut<NetworkHost>,
+    mut session: Option<ResMut<RollbackSession>>,
+    time: Res<Time<Real>>,
+) {
+    let now = time.elapsed();
+
+    for (message, address) in host.socket.receive::<ClientMessage>() {
+        let from_remote = host.remote.is_some_and(|remote| remote.address == address);
+
+        match message {
+            ClientMessage::Join { version } if version != PROTOCOL_VERSION => {
+                let reason =
+                    format!("the host uses the protocol version {PROTOCOL_VERSION}, not {version}");
+                host.reject(&reason, address);
+            }
+            ClientMessage::Join { .. } if host.remote.is_some() && !from_remote => {
+                host.reject("the host already has a remote player", address);
+            }
+            ClientMessage::Join { .. } => {
+                if !from_remote {
+                    info!("remote player joined from {address}");
+                    host.remote = Some(RemotePlayer {
+                        address,
+                        input: 0.0,
+                        match_id: None,
+                        last_received: now,
+                    });
+                }
+                host.socket.send(&HostMessage::Accepted, address);
+            }
+            ClientMessage::Input { match_id, input } if from_remote => {
+                if let Some(remote) = &mut host.remote {
+                    remote.input = if input.is_finite() {
+                        input.clamp(-1.0, 1.0)
+                    } else {
+                        0.0
+                    };
+                    remote.match_id = match_id;
+                    remote.last_received = now;
+                }
+            }
+            ClientMessage::Inputs(inputs) if from_remote => {
+                if let Some(remote) = &mut host.remote {
+                    remote.match_id = Some(inputs.match_id);
+                    remote.last_received = now;
+                }
+                if let Some(session) = &mut session {
+                    session.receive(&inputs);
+                }
+            }
+            ClientMessage::Leave if from_remote => {
+                info!("remote player {address} left");
+                host.remote = None;
+            }
+            ClientMessage::Input { .. } | ClientMessage::Inputs(_) | ClientMessage::Leave => {}
+        }
+    }
+
+    if let Some(remote) = host.remote {
+        if now.saturating_sub(remote.last_received) > TIMEOUT {
+            warn!("remote player {} timed out", remote.address);
+            host.remote = None;
+        }
+    }
+}
+
+/// Pauses the match while the remote player is not connected.
+pub fn pause_without_remote_player(
+    host: Res<NetworkHost>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    if host.remote.is_none() {
+        next_game_state.set(GameState::GameActive { playing: false });
+    }
+}
+
+pub fn next_remote_match(mut host: ResMut<NetworkHost>) {
+    host.match_id = host.match_id.wrapping_add(1);
+    host.sequence = 0;
+}
+
+/// Replaces the input of the remote player paddle with the last one received.
+pub fn apply_remote_input(
+    mut paddles: Query<&mut PaddleInput, With<PlayerRemote>>,
+    host: Res<NetworkHost>,
+) {
+    let input = host.remote.map_or(0.0, |remote| remote.input);
+
+    for mut paddle_input in &mut paddles {
+        paddle_input.0 = input;
+    }
+}
+
+/// Sends the settings of the current match until the remote player plays it.
+pub fn send_match_start(
+    host: Res<NetworkHost>,
+    game_state: Res<State<GameState>>,
+    settings: MatchSettings,
+    rng: Res<GameRng>,
+    time: Res<Time<Fixed>>,
+) {
+    let Some(remote) = host.remote else {
+        return;
+    };
+    let in_game = matches!(game_state.get(), GameState::GameActive { .. });
+    if !in_game
+        || remote.match_id == Some(host.match_id)
+        || !host.sequence.is_multiple_of(START_INTERVAL)
+    {
+        return;
+    }
+
+    let start = MatchStart {
+        match_id: host.match_id,
+        seed: rng.seed(),
+        timestep: time.timestep(),
+        settings: settings.get(),
+        netcode: host.netcode,
+        input_delay: host.input_delay,
+    };
+    host.socket.send(&HostMessage::Start(start), remote.address);
+}
+
+/// Sends the paddles, balls, power-ups and score to the remote player.
+///
+/// Also sent outside of the matches, to keep the connection alive.
+/// With the [`Netcode::Rollback`], only keeps the connection alive as the client simulates the match.
+pub fn send_host_state(
+    paddles: Query<(&Player, &Transform, &PaddleEffects), With<Paddle>>,
+    balls: Query<(Entity, &Transform), With<Ball>>,
+    power_ups: Query<(&Transform, &PowerUp)>,
+    (game_data, stats): (Res<GameActiveData>, Res<MatchStats>),
+    match_timer: Option<Res<StartMatchTimer>>,
+    game_state: Res<State<GameState>>,
+    mut host: ResMut<NetworkHost>,
+) {
+    let Some(remote) = host.remote else {
+        return;
+    };
+    if host.netcode == Netcode::Rollback {
+        host.socket.send(&HostMessage::KeepAlive, remote.address);
+        host.sequence += 1;
+        return;
+    }
+
+    let snapshot = Snapshot {
+        match_id: host.match_id,
+        sequence: host.sequence,
+        phase: RemotePhase::of(game_state.get()),
+        game_data: game_data.clone(),
+        countdown: match_timer.map(|timer| timer.0.elapsed_secs()),
+        paddles: paddles
+            .iter()
+            .map(|(player, transform, effects)| PaddleState {
+                side: player.get_side(),
+                position: transform.translation.truncate(),
+                scale: transform.scale.truncate(),
+                effects: *effects,
+            })
+            .collect(),
+        balls: balls
+            .iter()
+            .map(|(entity, transform)| BallState {
+                id: entity.index(),
+                position: transform.translation.truncate(),
+            })
+            .collect(),
+        power_ups: power_ups
+            .iter()
+            .map(|(transform, power_up)| PowerUpState {
+                effect: power_up.0,
+                position: transform.translation.truncate(),
+            })
+            .collect(),
+        stats: stats.clone(),
+    };
+
+    host.socket
+        .send(&HostMessage::State(snapshot), remote.address);
+    host.sequence += 1;
+}
+
+/// Restores the settings changed by the host and disconnects.
+fn leave_host(
+    client: &NetworkClient,
+    settings: &mut MatchSettings,
+    fixed_time: &mut Time<Fixed>,
+    commands: &mut Commands,
+) {
+    if let Some((previous, seed, timestep)) = client.previous.clone() {
+        settings.set(previous);
+        fixed_time.set_timestep(timestep);
+        commands.insert_resource(GameRng::new(seed));
+    }
+
+    commands.remove_resource::<NetworkClient>();
+}
+
+/// Starts the matches of the host, and leaves it when it leaves or after [`TIMEOUT`] without messages.
+///
+/// The client runs its fixed updates at the timestep of the host, while in its matches.
+#[expect(clippy::too_many_arguments)]
+pub fn receive_host_messages(
+    mut client: ResMut<NetworkClient>,
+    mut session: Option<ResMut<RollbackSession>>,
+    (mut settings, mut fixed_time): (MatchSettings, ResMut<Time<Fixed>>),
+    rng: Res<GameRng>,
+    game_state: Res<State<GameState>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    time: Res<Time<Real>>,
+    mut commands: Commands,
+) {
+    let now = time.elapsed();
+    let mut disconnected = false;
+
+    for (message, address) in client.socket.receive::<HostMessage>() {
+        if address != client.host {
+            continue;
+        }
+        client.last_received = now;
+
+        match message {
+            HostMessage::Accepted if !client.accepted => {
+                info!("joined the host {address}");
+                client.accepted = true;
+            }
+            HostMessage::Accepted => {}
+            HostMessage::Rejected(reason) => {
+                warn!("the host {address} rejected the connection: {reason}");
+                disconnected = true;
+            }
+            HostMessage::Start(start) if client.match_id() != Some(start.match_id) => {
+                if client.previous.is_none() {
+                    client.previous = Some((settings.get(), rng.seed(), fixed_time.timestep()));
+                }
+                client.start_match(&start);
+
+                settings.set(start.settings);
+                fixed_time.set_timestep(start.timestep);
+                commands.insert_resource(GameRng::new(start.seed));
+
+                if !matches!(game_state.get(), GameState::GameActive { .. }) {
+                    next_game_state.set(GameState::playing());
+                }
+            }
+            HostMessage::Start(_) => {}
+            HostMessage::State(snapshot) => client.push(snapshot),
+            HostMessage::Inputs(inputs) => {
+                if let Some(session) = &mut session {
+                    session.receive(&inputs);
+                }
+            }
+            HostMessage::KeepAlive => {}
+            HostMessage::Leave => {
+                info!("the host {address} left");
+                disconnected = true;
+            }
+        }
+    }
+
+    if client.accepted && now.saturating_sub(client.last_received) > TIMEOUT {
+        warn!("the host {} timed out", client.host);
+        disconnected = true;
+    }
+
+    if disconnected {
+        leave_host(&client, &mut settings, &mut fixed_time, &mut commands);
+        if *game_state.get() != GameState::MainMenu {
+            next_game_state.set(GameState::MainMenu);
+        }
+    }
+}
+
+/// The client does not record the matches it does not simulate.
+pub fn discard_recording(mut commands: Commands) {
+    commands.remove_resource::<ReplayRecorder>();
+}
+
+/// Leaves the host when the client goes back to the main menu.
+pub fn leave_remote_match(
+    client: Res<NetworkClient>,
+    mut settings: MatchSettings,
+    mut fixed_time: ResMut<Time<Fixed>>,
+    mut commands: Commands,
+) {
+    client.send(&ClientMessage::Leave);
+    leave_host(&client, &mut settings, &mut fixed_time, &mut commands);
+}
+
+/// Input of the device claimed by the first player of the client.
+fn local_input(
+    devices: &PlayerDevices,
+    keyboard: &ButtonInput<KeyCode>,
+    gamepads: &Query<&Gamepad>,
+    settings: &InputSettings,
+) -> PaddleInput {
+    match devices.get(PlayerSide::Main) {
+        Some(PlayerDevice::Keyboard(half)) => {
+            PaddleInput::from_keyboard(keyboard, settings.keyboard(half))
+        }
+        Some(PlayerDevice::Gamepad(entity)) => gamepads
+            .get(entity)
+            .map(|gamepad| PaddleInput::from_gamepad(gamepad, settings.gamepad(PlayerSide::Main)))
+            .unwrap_or_default(),
+        None => PaddleInput::default(),
+    }
+}
+
+/// Asks to join the host until accepted, then sends the paddle input every frame.
+pub fn send_client_input(
+    mut client: ResMut<NetworkClient>,
+    gamepads: Query<&Gamepad>,
+    devices: Res<PlayerDevices>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    settings: Res<InputSettings>,
+    active_state: Option<Res<State<GameActiveState>>>,
+    time: Res<Time<Real>>,
+) {
+    let now = time.elapsed();
+
+    if !client.accepted {
+        let due = client
+            .last_join
+            .is_none_or(|last_join| now.saturating_sub(last_join) >= JOIN_INTERVAL);
+        if due {
+            client.send(&ClientMessage::Join {
+                version: PROTOCOL_VERSION,
+            });
+            client.last_join = Some(now);
+        }
+        return;
+    }
+
+    // the paddle stops while the client is paused, or between the matches.
+    let playing = active_state.is_some_and(|state| *state.get() == GameActiveState::Playing);
+    let server_match = client
+        .remote_match
+        .is_some_and(|remote_match| remote_match.netcode == Netcode::Server);
+    let input = if playing && server_match {
+        local_input(&devices, &keyboard, &gamepads, &settings)
+    } else {
+        PaddleInput::default()
+    };
+
+    client.send(&ClientMessage::Input {
+        match_id: client.match_id(),
+        input: input.0,
+    });
+}
+
+/// Follows the score, statistics, pauses and match end of the host.
+///
+/// A pause of the client only pauses its own screen, until the host pauses or resumes.
+pub fn sync_host_phase(
+    mut client: ResMut<NetworkClient>,
+    (mut game_data, mut stats): (ResMut<GameActiveData>, ResMut<MatchStats>),
+    (mut data_event, mut match_over_event): (EventWriter<GameDataUpdated>, EventWriter<MatchOver>),
+    game_state: Res<State<GameState>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    let Some((phase, data)) = client
+        .snapshots
+        .back()
+        .map(|latest| (latest.phase, latest.game_data.clone()))
+    else {
+        return;
+    };
+
+    if *game_data != data {
+        *game_data = data;
+        data_event.send(GameDataUpdated);
+    }
+    if let Some(latest) = client.snapshots.back() {
+        if *stats != latest.stats {
+            *stats = latest.stats.clone();
+        }
+    }
+
+    let changed = client.host_phase.replace(phase) != Some(phase);
+    match (phase, *game_state.get()) {
+        (RemotePhase::MatchOver, GameState::GameActive { .. }) => {
+            if let Some(winner) = game_data.match_winner() {
+                match_over_event.send(MatchOver::new(winner));
+            }
+            next_game_state.set(GameState::MatchOver);
+        }
+        (RemotePhase::Playing, GameState::GameActive { playing: false }) if changed => {
+            next_game_state.set(GameState::playing());
+        }
+        (RemotePhase::Paused, GameState::GameActive { playing: true }) if changed => {
+            next_game_state.set(GameState::GameActive { playing: false });
+        }
+        _ => {}
+    }
+}
+
+/// Shows the state of the host, interpolated between the two snapshots around the shown sequence.
+#[expect(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn apply_host_state(
+    mut paddles: Query<(&Player, &mut Transform, &mut PaddleEffects), With<Paddle>>,
+    mut balls: Query<(Entity, Option<&RemoteBall>, &mut Transform), (With<Ball>, Without<Paddle>)>,
+    power_ups: Query<(Entity, &Transform, &PowerUp), (Without<Ball>, Without<Paddle>)>,
+    arena: Single<Entity, With<Arena>>,
+    match_timer: Option<ResMut<StartMatchTimer>>,
+    mut client: ResMut<NetworkClient>,
+    time: Res<Time<Real>>,
+    mut commands: Commands,
+) {
+    let Some((from, to, factor)) = client.advance(time.delta()) else {
+        return;
+    };
+
+    for (player, mut transform, mut effects) in &mut paddles {
+        let side = player.get_side();
+        let Some(end) = to.paddle(side) else {
+            continue;
+        };
+        let start = from.paddle(side).unwrap_or(end);
+
+        transform.translation = start
+            .position
+            .lerp(end.position, factor)
+            .extend(transform.translation.z);
+        transform.scale = start
+            .scale
+            .lerp(end.scale, factor)
+            .extend(transform.scale.z);
+        if *effects != end.effects {
+            *effects = end.effects;
+        }
+    }
+
+    // the balls are matched by their identifier on the host,
+    // removing the ones not in play anymore.
+    for (entity, remote, mut transform) in &mut balls {
+        let Some(end) = remote.and_then(|remote| to.ball(remote.0)) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+        let start = from.ball(end.id).unwrap_or(end);
+
+        transform.translation = start
+            .position
+            .lerp(end.position, factor)
+            .extend(transform.translation.z);
+    }
+    for ball in &to.balls {
+        let shown = balls
+            .iter()
+            .any(|(_, remote, _)| remote.is_some_and(|remote| remote.0 == ball.id));
+        if !shown {
+            let mut transform = Ball::initial_transform();
+            transform.translation = ball.position.extend(transform.translation.z);
+            commands.spawn((
+                Ball,
+                RemoteBall(ball.id),
+                LinearVelocity(Vec2::ZERO),
+                transform,
+            ));
+        }
+    }
+
+    let same_power_up = |transform: &Transform, power_up: &PowerUp, state: &PowerUpState| {
+        power_up.0 == state.effect && transform.translation.truncate() == state.position
+    };
+    for (entity, transform, power_up) in &power_ups {
+        if !to
+            .power_ups
+            .iter()
+            .any(|state| same_power_up(transform, power_up, state))
+        {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+    for state in &to.power_ups {
+        if !power_ups
+            .iter()
+            .any(|(_, transform, power_up)| same_power_up(transform, power_up, state))
+        {
+            commands
+                .entity(*arena)
+                .with_child((PowerUp(state.effect), PowerUp::transform(state.position)));
+        }
+    }
+
+    match (to.countdown, match_timer) {
+        (Some(elapsed), Some(mut timer)) => {
+            timer.0.set_elapsed(Duration::from_secs_f32(elapsed));
+        }
+        (Some(elapsed), None) => {
+            let mut timer = StartMatchTimer::default();
+            timer.0.set_elapsed(Duration::from_secs_f32(elapsed));
+            commands.insert_resource(timer);
+        }
+        (None, Some(_)) => commands.remove_resource::<StartMatchTimer>(),
+        (None, None) => {}
+    }
+}
+
+/// Host and client of a match over UDP.
+///
+/// With the [`Netcode::Server`], the host runs the simulation and sends its state every fixed update,
+/// while the client only sends the input of its paddle and shows the received state.
+/// The [`Netcode::Rollback`] is added by the [`rollback::plugin`].
+pub fn plugin(app: &mut App) {
+    app.init_resource::<NetworkSettings>();
+
+    app.add_plugins(rollback::plugin);
+
+    app.configure_sets(
+        FixedUpdate,
+        GameSystems::Simulation.run_if(not(client_mirrors_host)),
+    );
+    app.configure_sets(
+        FixedUpdate,
+        GameSystems::Scoring.run_if(not(client_mirrors_host)),
+    );
+
+    app.add_systems(
+        OnEnter(GameState::Lobby),
+        start_hosting.run_if(remote_opponent.and(not(resource_exists::<NetworkHost>))),
+    );
+    app.add_systems(
+        OnEnter(GameState::MainMenu),
+        (
+            stop_hosting.run_if(resource_exists::<NetworkHost>),
+            leave_remote_match.run_if(client_in_match),
+        )
+            .before(spawn_main_menu),
+    );
+    app.add_systems(
+        OnEnter(InGame),
+        (
+            next_remote_match.run_if(resource_exists::<NetworkHost>),
+            discard_recording
+                .after(start_recording)
+                .run_if(client_mirrors_host),
+        ),
+    );
+
+    app.add_systems(
+        FixedUpdate,
+        apply_remote_input
+            .run_if(resource_exists::<NetworkHost>.and(not(resource_exists::<RollbackSession>)))
+            .after(read_player_input)
+            .before(record_player_input)
+            .in_set(GameSystems::Input),
+    );
+    app.add_systems(
+        FixedUpdate,
+        (send_match_start, send_host_state)
+            .chain()
+            .distributive_run_if(resource_exists::<NetworkHost>)
+            .after(GameSystems::Scoring),
+    );
+
+    app.add_systems(
+        Update,
+        (
+            receive_client_messages,
+            pause_without_remote_player.run_if(in_state(GameActiveState::Playing)),
+        )
+            .chain()
+            .distributive_run_if(resource_exists::<NetworkHost>),
+    );
+    app.add_systems(
+        Update,
+        (
+            receive_host_messages,
+            send_client_input,
+            sync_host_phase.run_if(client_mirrors_host),
+            (apply_host_state, update_shields)
+                .chain()
+                .distributive_run_if(client_mirrors_host.and(in_state(InGame))),
+        )
+            .chain()
+            .distributive_run_if(resource_exists::<NetworkClient>),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{
+        layout::ArenaLayout,
+        player::{AIDifficulty, MainPlayerType, SecondPlayerType},
+        resource::{FourPlayers, MatchRules, MultiBall, PaddleBounce, PowerUps},
+    };
+
+    fn snapshot(match_id: u32, sequence: u64) -> Snapshot {
+        let mut game_data = GameActiveData::default();
+        game_data.register_point(PlayerSide::Other);
+
+        Snapshot {
+            match_id,
+            sequence,
+            phase: RemotePhase::Playing,
+            game_data,
+            countdown: Some(0.5),
+            paddles: vec![PaddleState {
+                side: PlayerSide::Main,
+                position: Vec2::new(-45.0, sequence as f32),
+                scale: Vec2::new(1.2, 10.0),
+                effects: PaddleEffects::default(),
+            }],
+            balls: vec![BallState {
+                id: 1,
+                position: Vec2::new(sequence as f32, 0.0),
+            }],
+            power_ups: vec![PowerUpState {
+                effect: PaddleEffect::Big,
+                position: Vec2::ONE,
+            }],
+            stats: MatchStats::default(),
+        }
+    }
+
+    fn match_start(match_id: u32) -> MatchStart {
+        MatchStart {
+            match_id,
+            seed: 42,
+            timestep: Duration::from_millis(10),
+            settings: ReplaySettings {
+                main: MainPlayerType::default(),
+                opponent: SecondPlayerType::default(),
+                difficulty: AIDifficulty::default(),
+                rules: MatchRules::default(),
+                bounce: PaddleBounce::default(),
+                four_players: FourPlayers::default(),
+                multi_ball: MultiBall::default(),
+                power_ups: PowerUps::default(),
+                layout: ArenaLayout::default(),
+            },
+            netcode: Netcode::Server,
+            input_delay: DEFAULT_INPUT_DELAY,
+        }
+    }
+
+    /// Writes and reads the message as the [`NetworkSocket`] does.
+    fn round_trip<T: Serialize + DeserializeOwned>(message: &T) -> T {
+        let content = ron::to_string(message).unwrap();
+        ron::de::from_bytes(content.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn client_messages_round_trip() {
+        for message in [
+            ClientMessage::Join {
+                version: PROTOCOL_VERSION,
+            },
+            ClientMessage::Input {
+                match_id: Some(3),
+                input: -0.5,
+            },
+            ClientMessage::Input {
+                match_id: None,
+                input: 1.0,
+            },
+            ClientMessage::Leave,
+        ] {
+            assert_eq!(round_trip(&message), message);
+        }
+    }
+
+    #[test]
+    fn host_messages_round_trip() {
+        for message in [
+            HostMessage::Accepted,
+            HostMessage::Rejected("the host is full".to_string()),
+            HostMessage::Start(match_start(2)),
+            HostMessage::State(snapshot(2, 10)),
+            HostMessage::Leave,
+        ] {
+            assert_eq!(round_trip(&message), message);
+        }
+    }
+
+    fn client() -> NetworkClient {
+        let mut client =
+            NetworkClient::connect("127.0.0.1:7878", LinkConditioner::default()).unwrap();
+        client.start_match(&match_start(1));
+        client
+    }
+
+    #[test]
+    fn client_keeps_the_snapshots_of_its_match_in_order() {
+        let mut client = client();
+
+        client.push(snapshot(1, 2));
+        client.push(snapshot(1, 1));
+        client.push(snapshot(0, 3));
+        client.push(snapshot(1, 4));
+
+        let sequences: Vec<u64> = client
+            .snapshots
+            .iter()
+            .map(|snapshot| snapshot.sequence)
+            .collect();
+        assert_eq!(sequences, [2, 4]);
+    }
+
+    #[test]
+    fn client_interpolates_between_the_snapshots() {
+        let mut client = client();
+        for sequence in 0..=20 {
+            client.push(snapshot(1, sequence));
+        }
+
+        // catches up to the interpolation delay behind the latest snapshot.
+        let (from, to, factor) = client.advance(Duration::ZERO).unwrap();
+        assert_eq!((from.sequence, to.sequence), (16, 17));
+        assert_eq!(factor, 0.0);
+
+        // two and a half timesteps later.
+        let (from, to, factor) = client.advance(Duration::from_millis(25)).unwrap();
+        assert_eq!((from.sequence, to.sequence), (18, 19));
+        assert!((factor - 0.5).abs() < 1e-4);
+
+        // and never goes past the latest snapshot.
+        let (from, to, factor) = client.advance(Duration::from_secs(1)).unwrap();
+        assert_eq!((from.sequence, to.sequence), (20, 20));
+        assert_eq!(factor, 1.0);
+    }
+}
@@ -0,0 +1,832 @@
+use std::collections::BTreeMap;
+
+use bevy::{
+    app::{FixedMain, RunFixedMainLoopSystem},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{local_input, ClientMessage, HostMessage, Netcode, NetworkClient, NetworkHost};
+use crate::{
+    game::{
+        arena::{Arena, Ball, LastHit, Paddle},
+        event::GameDataUpdated,
+        physics::{Collider, LinearVelocity},
+        player::{AIController, PaddleInput, Player, PlayerAI, PlayerSide, PlayerType},
+        power_up::{BallCurve, PaddleEffects, PowerUp, Shield},
+        resource::{
+            ExtraBallProgress, FourPlayers, GameActiveData, GameRng, MainPlayer, PlayerDevices,
+            PowerUpTimer, SecondPlayer, StartMatchTimer,
+        },
+        state::{GameActiveState, GameState, InGame},
+        stats::MatchStats,
+        system::read_player_input,
+        GameSystems,
+    },
+    replay::{record_player_input, side_index, ReplayRecorder},
+    settings::input::InputSettings,
+    ui::main_menu::spawn_main_menu,
+};
+
+/// Ticks simulated at most past the last tick with the inputs of the other peer,
+/// the simulation waits for them beyond.
+pub const MAX_PREDICTION: u64 = 12;
+/// Most inputs sent in a message, the oldest ones not received by the other peer first.
+const MAX_SENT_INPUTS: usize = 64;
+
+/// Role of the instance in a peer-to-peer match.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Peer {
+    /// Plays every human side but the right one.
+    Host,
+    /// Plays the right side.
+    Guest,
+}
+
+/// Paddle inputs of the sides played by a peer, sent every frame of a match.
+///
+/// The inputs the other peer did not receive yet are sent again, in case they were lost.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct PeerInputs {
+    pub match_id: u32,
+    /// Tick of the first input.
+    pub start: u64,
+    /// Inputs of the main, other, top and bottom side paddles, for each tick from `start`.
+    pub inputs: Vec<[f32; 4]>,
+    /// Ticks of which the sender has every input of the receiver.
+    pub received: u64,
+    /// Whether the sender paused the match.
+    pub paused: bool,
+}
+
+/// State of the match at the start of a tick, restored to simulate the next ticks again.
+#[derive(Clone)]
+pub struct RollbackState {
+    game_data: GameActiveData,
+    stats: MatchStats,
+    rng: GameRng,
+    match_timer: Option<StartMatchTimer>,
+    extra_ball: ExtraBallProgress,
+    power_up_timer: PowerUpTimer,
+    paddles: Vec<(Entity, Transform, PaddleEffects, Option<AIController>)>,
+    balls: Vec<(Transform, LinearVelocity, LastHit, BallCurve)>,
+    power_ups: Vec<(PowerUp, Transform)>,
+    shields: Vec<(Shield, Transform)>,
+    /// Fixed updates of the replay being recorded.
+    recorded_ticks: Option<u64>,
+}
+
+impl RollbackState {
+    /// Saves the balls, power-ups and shields in the order they are simulated in.
+    pub fn save(world: &mut World) -> Self {
+        let paddles = world
+            .query_filtered::<
+                (Entity, &Transform, &PaddleEffects, Option<&AIController>),
+                With<Paddle>,
+            >()
+            .iter(world)
+            .map(|(entity, transform, effects, controller)| {
+                (entity, *transform, *effects, controller.copied())
+            })
+            .collect();
+        let balls = world
+            .query_filtered::<(&Transform, &LinearVelocity, &LastHit, &BallCurve), With<Ball>>()
+            .iter(world)
+            .map(|(transform, velocity, last_hit, curve)| {
+                (*transform, *velocity, *last_hit, *curve)
+            })
+            .collect();
+        let power_ups = world
+            .query::<(&PowerUp, &Transform)>()
+            .iter(world)
+            .map(|(power_up, transform)| (*power_up, *transform))
+            .collect();
+        let shields = world
+            .query::<(&Shield, &Transform)>()
+            .iter(world)
+            .map(|(shield, transform)| (*shield, *transform))
+            .collect();
+
+        Self {
+            game_data: world.resource::<GameActiveData>().clone(),
+            stats: world.resource::<MatchStats>().clone(),
+            rng: world.resource::<GameRng>().clone(),
+            match_timer: world.get_resource::<StartMatchTimer>().cloned(),
+            extra_ball: world.resource::<ExtraBallProgress>().clone(),
+            power_up_timer: world.resource::<PowerUpTimer>().clone(),
+            paddles,
+            balls,
+            power_ups,
+            shields,
+            recorded_ticks: world
+                .get_resource::<ReplayRecorder>()
+                .map(|recorder| recorder.0.ticks()),
+        }
+    }
+
+    /// Puts the match back in the saved state, spawning again its balls, power-ups and shields.
+    pub fn restore(&self, world: &mut World) {
+        world.insert_resource(self.game_data.clone());
+        world.insert_resource(self.stats.clone());
+        world.insert_resource(self.rng.clone());
+        match &self.match_timer {
+            Some(timer) => world.insert_resource(timer.clone()),
+            None => {
+                world.remove_resource::<StartMatchTimer>();
+            }
+        }
+        world.insert_resource(self.extra_ball.clone());
+        world.insert_resource(self.power_up_timer.clone());
+
+        for (entity, transform, effects, controller) in &self.paddles {
+            let Ok(mut paddle) = world.get_entity_mut(*entity) else {
+                continue;
+            };
+            paddle.insert((*transform, *effects));
+            if let Some(controller) = controller {
+                paddle.insert(*controller);
+            }
+        }
+
+        let balls: Vec<Entity> = world
+            .query_filtered::<Entity, With<Ball>>()
+            .iter(world)
+            .collect();
+        for entity in balls {
+            world.despawn(entity);
+        }
+        for (transform, velocity, last_hit, curve) in &self.balls {
+            world.spawn((Ball, *velocity, *transform, *last_hit, *curve));
+        }
+
+        // the power-ups and shields are children of the arena.
+        let children: Vec<Entity> = world
+            .query_filtered::<Entity, Or<(With<PowerUp>, With<Shield>)>>()
+            .iter(world)
+            .collect();
+        for entity in children {
+            world.entity_mut(entity).despawn_recursive();
+        }
+        let arena = world
+            .query_filtered::<Entity, With<Arena>>()
+            .iter(world)
+            .next();
+        if let Some(arena) = arena {
+            for (power_up, transform) in &self.power_ups {
+                world.spawn((*power_up, *transform)).set_parent(arena);
+            }
+            for (shield, transform) in &self.shields {
+                world
+                    .spawn((*shield, Collider, *transform))
+                    .set_parent(arena);
+            }
+        }
+
+        if let (Some(ticks), Some(mut recorder)) = (
+            self.recorded_ticks,
+            world.get_resource_mut::<ReplayRecorder>(),
+        ) {
+            recorder.0.truncate(ticks);
+        }
+
+        world.flush();
+    }
+}
+
+/// Peer-to-peer match with the [`Netcode::Rollback`].
+///
+/// Both peers simulate the match, each tick with the local inputs given [`RollbackSession::input_delay`]
+/// ticks before and the remote inputs of the other peer. The missing remote inputs are predicted
+/// by repeating the last received ones, and the ticks simulated with a wrong prediction
+/// are simulated again once the remote inputs arrive.
+#[derive(Resource)]
+pub struct RollbackSession {
+    match_id: u32,
+    peer: Peer,
+    /// Sides played by this peer, by [`side_index`].
+    local_sides: [bool; 4],
+    input_delay: u64,
+    /// Tick simulated next.
+    tick: u64,
+    local_inputs: BTreeMap<u64, [f32; 4]>,
+    remote_inputs: BTreeMap<u64, [f32; 4]>,
+    /// Remote inputs each tick not received yet was simulated with.
+    predicted: BTreeMap<u64, [f32; 4]>,
+    /// Ticks with every remote input received.
+    received: u64,
+    /// Ticks with every local input received by the other peer.
+    peer_received: u64,
+    /// First tick to simulate again, as it was simulated with a wrong prediction.
+    rollback_to: Option<u64>,
+    states: BTreeMap<u64, RollbackState>,
+    /// Tick ending the match, which may still be rolled back.
+    match_over_tick: Option<u64>,
+    peer_paused: Option<bool>,
+    /// Pause of the other peer last followed.
+    followed_pause: Option<bool>,
+    rollbacks: u64,
+}
+
+impl RollbackSession {
+    pub fn new(match_id: u32, peer: Peer, local_sides: [bool; 4], input_delay: u32) -> Self {
+        let input_delay = u64::from(input_delay);
+        // no side moves during the first ticks, before the delayed inputs.
+        let initial: BTreeMap<u64, [f32; 4]> =
+            (0..input_delay).map(|tick| (tick, [0.0; 4])).collect();
+
+        Self {
+            match_id,
+            peer,
+            local_sides,
+            input_delay,
+            tick: 0,
+            local_inputs: initial.clone(),
+            remote_inputs: initial,
+            predicted: BTreeMap::new(),
+            received: input_delay,
+            peer_received: 0,
+            rollback_to: None,
+            states: BTreeMap::new(),
+            match_over_tick: None,
+            peer_paused: None,
+            followed_pause: None,
+            rollbacks: 0,
+        }
+    }
+
+    pub const fn match_id(&self) -> u32 {
+        self.match_id
+    }
+
+    pub const fn peer(&self) -> Peer {
+        self.peer
+    }
+
+    pub const fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub const fn input_delay(&self) -> u64 {
+        self.input_delay
+    }
+
+    /// Ticks with every remote input received.
+    pub const fn received(&self) -> u64 {
+        self.received
+    }
+
+    /// Times the match was rolled back.
+    pub const fn rollbacks(&self) -> u64 {
+        self.rollbacks
+    }
+
+    /// Whether the next tick can be simulated: it is not too far past the received remote inputs,
+    /// and the match did not end.
+    pub fn can_advance(&self) -> bool {
+        self.match_over_tick.is_none() && self.tick < self.received + MAX_PREDICTION
+    }
+
+    /// Whether the end of the match can not be rolled back anymore.
+    pub fn match_over_confirmed(&self) -> bool {
+        self.match_over_tick
+            .is_some_and(|tick| tick < self.received)
+    }
+
+    /// Keeps the `inputs` of the local sides, without the ones of the remote sides.
+    fn local_part(&self, inputs: [f32; 4]) -> [f32; 4] {
+        core::array::from_fn(|index| {
+            if self.local_sides[index] {
+                inputs[index]
+            } else {
+                0.0
+            }
+        })
+    }
+
+    /// Keeps the `inputs` of the remote sides, without the ones of the local sides.
+    fn remote_part(&self, inputs: [f32; 4]) -> [f32; 4] {
+        core::array::from_fn(|index| {
+            if self.local_sides[index] {
+                0.0
+            } else {
+                inputs[index]
+            }
+        })
+    }
+
+    /// Plays the local inputs read during the current tick after the input delay.
+    ///
+    /// The inputs of a tick simulated again are kept, as they were already sent.
+    fn add_local_inputs(&mut self, inputs: [f32; 4]) {
+        let inputs = self.local_part(inputs);
+        self.local_inputs
+            .entry(self.tick + self.input_delay)
+            .or_insert(inputs);
+    }
+
+    /// Received remote inputs of the `tick`, or the last received ones.
+    fn remote_inputs(&self, tick: u64) -> [f32; 4] {
+        self.remote_inputs
+            .get(&tick)
+            .or_else(|| {
+                self.remote_inputs
+                    .range(..self.received)
+                    .next_back()
+                    .map(|(_, inputs)| inputs)
+            })
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Inputs of every side for the current tick, remembering the remote ones used.
+    fn tick_inputs(&mut self) -> [f32; 4] {
+        let local = self
+            .local_inputs
+            .get(&self.tick)
+            .copied()
+            .unwrap_or_default();
+        let remote = self.remote_inputs(self.tick);
+        self.predicted.insert(self.tick, remote);
+
+        core::array::from_fn(|index| {
+            if self.local_sides[index] {
+                local[index]
+            } else {
+                remote[index]
+            }
+        })
+    }
+
+    /// Moves to the next tick, forgetting what can not be rolled back anymore.
+    fn advance(&mut self, match_over: bool) {
+        if match_over && self.match_over_tick.is_none() {
+            self.match_over_tick = Some(self.tick);
+        }
+        self.tick += 1;
+
+        // the ticks not simulated yet, or simulated without every remote input, are kept.
+        let kept = self
+            .received
+            .min(self.tick)
+            .min(self.rollback_to.unwrap_or(u64::MAX));
+        self.states = self.states.split_off(&kept);
+        self.predicted = self.predicted.split_off(&kept);
+        // the last received inputs are kept to predict the next ones.
+        self.remote_inputs = self.remote_inputs.split_off(&kept.saturating_sub(1));
+        self.local_inputs = self.local_inputs.split_off(&kept.min(self.peer_received));
+    }
+
+    /// Keeps the inputs of the other peer, rolling back to the first tick simulated with a wrong prediction.
+    pub fn receive(&mut self, message: &PeerInputs) {
+        if message.match_id != self.match_id {
+            return;
+        }
+        self.peer_received = self.peer_received.max(message.received);
+        self.peer_paused = Some(message.paused);
+
+        for (tick, inputs) in (message.start..).zip(&message.inputs) {
+            if tick < self.received {
+                continue;
+            }
+            let inputs = inputs.map(|input| {
+                if input.is_finite() {
+                    input.clamp(-1.0, 1.0)
+                } else {
+                    0.0
+                }
+            });
+            let inputs = self.remote_part(inputs);
+            self.remote_inputs.entry(tick).or_insert(inputs);
+        }
+
+        while let Some(inputs) = self.remote_inputs.get(&self.received).copied() {
+            let tick = self.received;
+            if tick < self.tick && self.predicted.get(&tick) != Some(&inputs) {
+                self.rollback_to = Some(self.rollback_to.map_or(tick, |first| first.min(tick)));
+            }
+            self.received += 1;
+        }
+    }
+
+    /// Local inputs the other peer did not receive yet.
+    pub fn outgoing(&self, paused: bool) -> PeerInputs {
+        PeerInputs {
+            match_id: self.match_id,
+            start: self.peer_received,
+            inputs: self
+                .local_inputs
+                .range(self.peer_received..)
+                .take(MAX_SENT_INPUTS)
+                .map(|(_, inputs)| *inputs)
+                .collect(),
+            received: self.received,
+            paused,
+        }
+    }
+}
+
+/// Whether the game systems can simulate the next tick, always true outside of a peer-to-peer match.
+pub fn session_can_advance(session: Option<Res<RollbackSession>>) -> bool {
+    session.is_none_or(|session| session.can_advance())
+}
+
+/// Whether the next tick of a peer-to-peer match is simulated.
+pub fn session_ticking(
+    session: Option<Res<RollbackSession>>,
+    active_state: Option<Res<State<GameActiveState>>>,
+) -> bool {
+    let playing = active_state.is_some_and(|state| *state.get() == GameActiveState::Playing);
+    playing && session.is_some_and(|session| session.can_advance())
+}
+
+/// Whether the client plays a match older than the last one started by the host.
+pub fn session_outdated(
+    session: Option<Res<RollbackSession>>,
+    client: Option<Res<NetworkClient>>,
+) -> bool {
+    match (session, client) {
+        (Some(session), Some(client)) => {
+            session.peer == Peer::Guest && client.match_id() != Some(session.match_id)
+        }
+        _ => false,
+    }
+}
+
+/// Starts the peer-to-peer session of a match with the [`Netcode::Rollback`].
+pub fn start_session(
+    host: Option<Res<NetworkHost>>,
+    client: Option<Res<NetworkClient>>,
+    main_player: Res<MainPlayer>,
+    second_player: Res<SecondPlayer>,
+    four_players: Res<FourPlayers>,
+    mut commands: Commands,
+) {
+    let host_match = host
+        .filter(|host| host.netcode == Netcode::Rollback)
+        .map(|host| (Peer::Host, host.match_id, host.input_delay));
+    let client_match = client
+        .and_then(|client| client.remote_match)
+        .filter(|remote_match| remote_match.netcode == Netcode::Rollback)
+        .map(|remote_match| (Peer::Guest, remote_match.id, remote_match.input_delay));
+
+    let Some((peer, match_id, input_delay)) = host_match.or(client_match) else {
+        commands.remove_resource::<RollbackSession>();
+        return;
+    };
+
+    let mut local_sides = [false; 4];
+    for side in four_players.sides() {
+        let player = side.to_player_type(main_player.kind, second_player.opponent, *four_players);
+        local_sides[side_index(*side)] = match peer {
+            Peer::Host => !matches!(player, PlayerType::AI | PlayerType::Remote),
+            Peer::Guest => *side == PlayerSide::Other,
+        };
+    }
+
+    commands.insert_resource(RollbackSession::new(
+        match_id,
+        peer,
+        local_sides,
+        input_delay,
+    ));
+}
+
+pub fn end_session(mut commands: Commands) {
+    commands.remove_resource::<RollbackSession>();
+}
+
+/// Restarts the match of the client when the host started another one during it.
+pub fn restart_remote_match(world: &mut World) {
+    world.run_schedule(OnExit(InGame));
+    world.run_schedule(OnEnter(InGame));
+}
+
+/// Saves the state at the start of each tick that may be rolled back.
+pub fn save_state(world: &mut World) {
+    let session = world.resource::<RollbackSession>();
+    if session.tick < session.received {
+        return;
+    }
+
+    let state = RollbackState::save(world);
+    let mut session = world.resource_mut::<RollbackSession>();
+    let tick = session.tick;
+    session.states.insert(tick, state);
+}
+
+/// Gives the human paddles their inputs of the tick: the local ones read
+/// [`RollbackSession::input_delay`] ticks before, and the received or predicted remote ones.
+#[expect(clippy::type_complexity)]
+pub fn apply_session_inputs(
+    mut paddles: Query<(&Player, &mut PaddleInput), (With<Paddle>, Without<PlayerAI>)>,
+    mut session: ResMut<RollbackSession>,
+    gamepads: Query<&Gamepad>,
+    devices: Res<PlayerDevices>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    settings: Res<InputSettings>,
+) {
+    // the host reads the inputs of its sides like in a local match,
+    // the client reads the input of its first player for the right side.
+    let mut read = [0.0; 4];
+    for (player, input) in &paddles {
+        read[side_index(player.get_side())] = input.0;
+    }
+    if session.peer == Peer::Guest {
+        read[side_index(PlayerSide::Other)] =
+            local_input(&devices, &keyboard, &gamepads, &settings).0;
+    }
+    session.add_local_inputs(read);
+
+    let inputs = session.tick_inputs();
+    for (player, mut input) in &mut paddles {
+        input.0 = inputs[side_index(player.get_side())];
+    }
+}
+
+pub fn advance_session(mut session: ResMut<RollbackSession>, game_data: Res<GameActiveData>) {
+    session.advance(game_data.is_match_over());
+}
+
+/// Restores the state of the first tick simulated with a wrong prediction,
+/// and simulates again the ticks up to the current one.
+pub fn roll_back(world: &mut World) {
+    let mut session = world.resource_mut::<RollbackSession>();
+    let Some(target) = session.rollback_to.take() else {
+        return;
+    };
+    let current = session.tick;
+    let Some(state) = session.states.get(&target).cloned() else {
+        warn!("no state saved at tick {target} to roll back to");
+        return;
+    };
+    session.tick = target;
+    session.rollbacks += 1;
+    if session.match_over_tick.is_some_and(|tick| tick >= target) {
+        session.match_over_tick = None;
+    }
+
+    state.restore(world);
+
+    let timestep = world.resource::<Time<Fixed>>().timestep();
+
+    while world.resource::<RollbackSession>().tick < current {
+        world.resource_mut::<Time<Fixed>>().advance_by(timestep);
+        *world.resource_mut::<Time>() = world.resource::<Time<Fixed>>().as_generic();
+        world.run_schedule(FixedMain);
+    }
+
+    *world.resource_mut::<Time>() = world.resource::<Time<Virtual>>().as_generic();
+
+    world.send_event(GameDataUpdated);
+}
+
+/// Shows the end of the match only once the remote inputs up to its last tick are received,
+/// as a rollback can still change its result before.
+pub fn hold_match_over(
+    session: Res<RollbackSession>,
+    game_state: Res<State<GameState>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    let in_match = matches!(game_state.get(), GameState::GameActive { .. });
+
+    if session.match_over_confirmed() {
+        if in_match {
+            next_game_state.set(GameState::MatchOver);
+        }
+    } else if matches!(*next_game_state, NextState::Pending(GameState::MatchOver)) {
+        next_game_state.reset();
+    }
+}
+
+/// Sends the local inputs to the other peer, also after the match until the next one.
+pub fn send_session_inputs(
+    session: Res<RollbackSession>,
+    host: Option<Res<NetworkHost>>,
+    client: Option<Res<NetworkClient>>,
+    game_state: Res<State<GameState>>,
+) {
+    let paused = *game_state.get() == GameState::GameActive { playing: false };
+    let inputs = session.outgoing(paused);
+
+    match session.peer {
+        Peer::Host => {
+            if let Some((host, remote)) = host
+                .as_ref()
+                .and_then(|host| host.remote.map(|remote| (host, remote)))
+            {
+                host.socket
+                    .send(&HostMessage::Inputs(inputs), remote.address);
+            }
+        }
+        Peer::Guest => {
+            if let Some(client) = client {
+                client.send(&ClientMessage::Inputs(inputs));
+            }
+        }
+    }
+}
+
+/// Pauses and resumes the match with the other peer.
+pub fn follow_peer_pause(
+    mut session: ResMut<RollbackSession>,
+    game_state: Res<State<GameState>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    let Some(paused) = session.peer_paused else {
+        return;
+    };
+    if session.followed_pause.replace(paused) == Some(paused) {
+        return;
+    }
+
+    match (paused, *game_state.get()) {
+        (true, GameState::GameActive { playing: true }) => {
+            next_game_state.set(GameState::GameActive { playing: false });
+        }
+        (false, GameState::GameActive { playing: false }) => {
+            next_game_state.set(GameState::playing());
+        }
+        _ => {}
+    }
+}
+
+/// Peer-to-peer matches with input delay and rollback, for the [`Netcode::Rollback`].
+///
+/// Both peers run the game systems of every tick, which wait for the remote inputs
+/// past [`MAX_PREDICTION`] ticks. The state is saved at the start of each tick and restored
+/// before the fixed updates of a frame when a prediction was wrong.
+pub fn plugin(app: &mut App) {
+    app.configure_sets(
+        FixedUpdate,
+        (
+            GameSystems::Input,
+            GameSystems::Simulation,
+            GameSystems::Scoring,
+        )
+            .run_if(session_can_advance),
+    );
+
+    app.add_systems(
+        OnEnter(InGame),
+        start_session.after(super::next_remote_match),
+    );
+    app.add_systems(
+        OnEnter(GameState::MainMenu),
+        end_session
+            .run_if(resource_exists::<RollbackSession>)
+            .before(spawn_main_menu),
+    );
+
+    app.add_systems(FixedFirst, save_state.run_if(session_ticking));
+    app.add_systems(
+        FixedUpdate,
+        apply_session_inputs
+            .run_if(resource_exists::<RollbackSession>)
+            .after(read_player_input)
+            .before(record_player_input)
+            .in_set(GameSystems::Input),
+    );
+    app.add_systems(FixedLast, advance_session.run_if(session_ticking));
+
+    app.add_systems(
+        RunFixedMainLoop,
+        roll_back
+            .run_if(resource_exists::<RollbackSession>.and(in_state(GameActiveState::Playing)))
+            .in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop),
+    );
+    app.add_systems(
+        RunFixedMainLoop,
+        hold_match_over
+            .run_if(resource_exists::<RollbackSession>)
+            .in_set(RunFixedMainLoopSystem::AfterFixedMainLoop),
+    );
+
+    app.add_systems(
+        Update,
+        (
+            restart_remote_match.run_if(session_outdated.and(in_state(InGame))),
+            (send_session_inputs, follow_peer_pause)
+                .distributive_run_if(resource_exists::<RollbackSession>),
+        )
+            .chain()
+            .after(super::receive_client_messages)
+            .after(super::receive_host_messages),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAIN: [bool; 4] = [true, false, false, false];
+
+    fn peer_inputs(start: u64, inputs: Vec<[f32; 4]>, received: u64) -> PeerInputs {
+        PeerInputs {
+            match_id: 1,
+            start,
+            inputs,
+            received,
+            paused: false,
+        }
+    }
+
+    /// Simulates the `ticks` with neutral local inputs.
+    fn simulate(session: &mut RollbackSession, ticks: u64) {
+        for _ in 0..ticks {
+            session.add_local_inputs([0.0; 4]);
+            session.tick_inputs();
+            session.advance(false);
+        }
+    }
+
+    #[test]
+    fn local_inputs_are_played_after_the_delay() {
+        let mut session = RollbackSession::new(1, Peer::Host, MAIN, 2);
+
+        session.add_local_inputs([1.0, 1.0, 0.0, 0.0]);
+        let outgoing = session.outgoing(false);
+
+        assert_eq!(session.received(), 2);
+        assert_eq!(outgoing.start, 0);
+        // the remote side input is not sent, as the other peer plays it.
+        assert_eq!(outgoing.inputs, [[0.0; 4], [0.0; 4], [1.0, 0.0, 0.0, 0.0]]);
+    }
+
+    #[test]
+    fn wrong_prediction_rolls_back() {
+        let mut session = RollbackSession::new(1, Peer::Host, MAIN, 2);
+        simulate(&mut session, 4);
+
+        let moving = [0.0, 1.0, 0.0, 0.0];
+        session.receive(&peer_inputs(0, vec![[0.0; 4], [0.0; 4], moving, moving], 0));
+
+        assert_eq!(session.received(), 4);
+        assert_eq!(session.rollback_to, Some(2));
+    }
+
+    #[test]
+    fn right_prediction_does_not_roll_back() {
+        let mut session = RollbackSession::new(1, Peer::Host, MAIN, 2);
+        simulate(&mut session, 4);
+
+        session.receive(&peer_inputs(0, vec![[0.0; 4]; 4], 0));
+
+        assert_eq!(session.received(), 4);
+        assert_eq!(session.rollback_to, None);
+    }
+
+    #[test]
+    fn missing_remote_inputs_repeat_the_last_received() {
+        let mut session = RollbackSession::new(1, Peer::Host, MAIN, 0);
+
+        session.receive(&peer_inputs(0, vec![[0.0, 1.0, 0.0, 0.0]], 0));
+
+        assert_eq!(session.remote_inputs(5), [0.0, 1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn received_inputs_are_sanitized() {
+        let mut session = RollbackSession::new(1, Peer::Host, MAIN, 0);
+
+        session.receive(&peer_inputs(0, vec![[0.5, 5.0, f32::NAN, -0.5]], 0));
+
+        // the local side input is not taken from the other peer.
+        assert_eq!(session.remote_inputs(0), [0.0, 1.0, 0.0, -0.5]);
+    }
+
+    #[test]
+    fn inputs_of_another_match_are_ignored() {
+        let mut session = RollbackSession::new(1, Peer::Host, MAIN, 0);
+        let mut inputs = peer_inputs(0, vec![[0.0, 1.0, 0.0, 0.0]], 0);
+        inputs.match_id = 2;
+
+        session.receive(&inputs);
+
+        assert_eq!(session.received(), 0);
+    }
+
+    #[test]
+    fn inputs_received_by_the_peer_are_not_sent_again() {
+        let mut session = RollbackSession::new(1, Peer::Guest, [false, true, false, false], 2);
+        simulate(&mut session, 2);
+
+        session.receive(&peer_inputs(0, Vec::new(), 3));
+
+        assert_eq!(session.outgoing(false).start, 3);
+        assert_eq!(session.outgoing(false).inputs, [[0.0; 4]]);
+    }
+
+    #[test]
+    fn prediction_is_limited() {
+        let mut session = RollbackSession::new(1, Peer::Host, MAIN, 0);
+
+        simulate(&mut session, MAX_PREDICTION - 1);
+        assert!(session.can_advance());
+
+        simulate(&mut session, 1);
+        assert!(!session.can_advance());
+
+        session.receive(&peer_inputs(0, vec![[0.0; 4]], 0));
+        assert!(session.can_advance());
+    }
+}
@@ -0,0 +1,761 @@
+use core::{fmt, time::Duration};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{app::FixedMain, ecs::system::SystemParam, prelude::*};
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+
+use crate::{
+    game::{
+        event::GameDataUpdated,
+        layout::ArenaLayout,
+        player::{
+            AIDifficulty, MainPlayerType, PaddleInput, Player, PlayerAI, PlayerSide,
+            SecondPlayerType,
+        },
+        resource::{
+            FourPlayers, GameActiveData, GameRng, MainPlayer, MatchRules, MultiBall, PaddleBounce,
+            PowerUps, SecondPlayer,
+        },
+        state::{GameActiveState, GameState, InGame},
+        system::{next_match_seed, read_player_input},
+        GameSystems,
+    },
+    settings::{storage, time::FIXED_UPDATE_HZ},
+};
+
+/// Gameplay settings of a recorded match.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ReplaySettings {
+    pub main: MainPlayerType,
+    pub opponent: SecondPlayerType,
+    pub difficulty: AIDifficulty,
+    pub rules: MatchRules,
+    pub bounce: PaddleBounce,
+    /// Added in the version 2.
+    #[serde(default)]
+    pub four_players: FourPlayers,
+    /// Added in the version 3.
+    #[serde(default)]
+    pub multi_ball: MultiBall,
+    /// Added in the version 4.
+    #[serde(default)]
+    pub power_ups: PowerUps,
+    /// The whole arena layout, so the replay does not depend on the layout files.
+    ///
+    /// Added in the version 5.
+    #[serde(default)]
+    pub layout: ArenaLayout,
+}
+
+/// Resources holding the [`ReplaySettings`].
+#[derive(SystemParam)]
+pub struct MatchSettings<'w> {
+    main_player: ResMut<'w, MainPlayer>,
+    second_player: ResMut<'w, SecondPlayer>,
+    difficulty: ResMut<'w, AIDifficulty>,
+    rules: ResMut<'w, MatchRules>,
+    bounce: ResMut<'w, PaddleBounce>,
+    four_players: ResMut<'w, FourPlayers>,
+    multi_ball: ResMut<'w, MultiBall>,
+    power_ups: ResMut<'w, PowerUps>,
+    layout: ResMut<'w, ArenaLayout>,
+}
+
+impl MatchSettings<'_> {
+    pub fn get(&self) -> ReplaySettings {
+        ReplaySettings {
+            main: self.main_player.kind,
+            opponent: self.second_player.opponent,
+            difficulty: *self.difficulty,
+            rules: *self.rules,
+            bounce: *self.bounce,
+            four_players: *self.four_players,
+            multi_ball: *self.multi_ball,
+            power_ups: *self.power_ups,
+            layout: self.layout.clone(),
+        }
+    }
+
+    pub fn set(&mut self, settings: ReplaySettings) {
+        self.main_player.kind = settings.main;
+        self.second_player.opponent = settings.opponent;
+        *self.difficulty = settings.difficulty;
+        *self.rules = settings.rules;
+        *self.bounce = settings.bounce;
+        *self.four_players = settings.four_players;
+        *self.multi_ball = settings.multi_ball;
+        *self.power_ups = settings.power_ups;
+        *self.layout = settings.layout;
+    }
+}
+
+/// Paddle inputs repeated for a number of fixed updates.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct InputRun {
+    pub ticks: u32,
+    /// Inputs of the main, other, top and bottom side paddles.
+    ///
+    /// The replays before the version 2 only have the main and other side inputs.
+    #[serde(deserialize_with = "InputRun::deserialize_inputs")]
+    pub inputs: [f32; 4],
+}
+
+impl InputRun {
+    fn deserialize_inputs<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<[f32; 4], D::Error> {
+        struct InputsVisitor;
+
+        impl<'de> Visitor<'de> for InputsVisitor {
+            type Value = [f32; 4];
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("2 or 4 paddle inputs")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut inputs = [0.0; 4];
+                let mut len = 0;
+                while let Some(input) = seq.next_element()? {
+                    if len == inputs.len() {
+                        return Err(de::Error::invalid_length(len + 1, &self));
+                    }
+                    inputs[len] = input;
+                    len += 1;
+                }
+
+                match len {
+                    2 | 4 => Ok(inputs),
+                    _ => Err(de::Error::invalid_length(len, &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_tuple(4, InputsVisitor)
+    }
+}
+
+/// A recorded match, replayed by simulating it again from its seed, settings and player inputs.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub seed: u64,
+    /// Duration of each fixed update.
+    #[serde(default = "Replay::default_timestep")]
+    pub timestep: Duration,
+    pub settings: ReplaySettings,
+    /// Paddle inputs of each fixed update, run-length encoded.
+    pub inputs: Vec<InputRun>,
+}
+
+impl Replay {
+    pub const VERSION: u32 = 5;
+    pub const EXTENSION: &str = "ron";
+
+    pub const fn new(seed: u64, timestep: Duration, settings: ReplaySettings) -> Self {
+        Self {
+            version: Self::VERSION,
+            seed,
+            timestep,
+            settings,
+            inputs: Vec::new(),
+        }
+    }
+
+    fn default_timestep() -> Duration {
+        Duration::from_secs_f64(FIXED_UPDATE_HZ.recip())
+    }
+
+    /// Adds the paddle inputs of the next fixed update.
+    pub fn push(&mut self, inputs: [f32; 4]) {
+        match self.inputs.last_mut() {
+            Some(run) if run.inputs == inputs && run.ticks < u32::MAX => run.ticks += 1,
+            _ => self.inputs.push(InputRun { ticks: 1, inputs }),
+        }
+    }
+
+    /// Number of fixed updates recorded.
+    pub fn ticks(&self) -> u64 {
+        self.inputs.iter().map(|run| u64::from(run.ticks)).sum()
+    }
+
+    /// Keeps only the first `ticks` fixed updates.
+    pub fn truncate(&mut self, ticks: u64) {
+        let mut kept = 0;
+        let mut runs = 0;
+
+        for run in &mut self.inputs {
+            if kept >= ticks {
+                break;
+            }
+            run.ticks = (ticks - kept).min(u64::from(run.ticks)) as u32;
+            kept += u64::from(run.ticks);
+            runs += 1;
+        }
+
+        self.inputs.truncate(runs);
+    }
+
+    /// Paddle inputs of each fixed update.
+    pub fn expand_inputs(&self) -> Vec<[f32; 4]> {
+        self.inputs
+            .iter()
+            .flat_map(|run| core::iter::repeat_n(run.inputs, run.ticks as usize))
+            .collect()
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ReplayError> {
+        let content = fs::read_to_string(path)?;
+        Self::parse(&content)
+    }
+
+    /// Reads a replay in the format of the replay files.
+    pub fn parse(content: &str) -> Result<Self, ReplayError> {
+        let replay: Self = ron::from_str(content)?;
+
+        // the older versions are read with the defaults of the settings added after them.
+        if !(1..=Self::VERSION).contains(&replay.version) {
+            return Err(ReplayError::Version(replay.version));
+        }
+
+        Ok(replay)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ReplayError> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let content = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        fs::write(path, content)?;
+
+        Ok(())
+    }
+
+    /// Directory where the matches are recorded.
+    pub fn dir() -> Option<PathBuf> {
+        storage::data_dir().map(|dir| dir.join("replays"))
+    }
+
+    /// Path of a new replay file, named after the current time and the seed.
+    pub fn new_path(&self) -> Option<PathBuf> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_secs())
+            .unwrap_or_default();
+
+        Self::dir().map(|dir| dir.join(format!("{timestamp}-{}.{}", self.seed, Self::EXTENSION)))
+    }
+
+    /// Path of the most recently recorded replay.
+    pub fn latest_path() -> Option<PathBuf> {
+        fs::read_dir(Self::dir()?)
+            .ok()?
+            .filter_map(Result::ok)
+            .filter(|entry| {
+                entry
+                    .path()
+                    .extension()
+                    .is_some_and(|ext| ext == Self::EXTENSION)
+            })
+            .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+            .max_by_key(|(modified, _)| *modified)
+            .map(|(_, path)| path)
+    }
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+    /// The replay was recorded by a newer version of the game.
+    Version(u32),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Parse(err) => write!(f, "invalid replay file: {err}"),
+            Self::Serialize(err) => write!(f, "could not write replay: {err}"),
+            Self::Version(version) => write!(
+                f,
+                "unsupported replay version {version}, expected {} or older",
+                Replay::VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<ron::error::SpannedError> for ReplayError {
+    fn from(err: ron::error::SpannedError) -> Self {
+        Self::Parse(err)
+    }
+}
+
+impl From<ron::Error> for ReplayError {
+    fn from(err: ron::Error) -> Self {
+        Self::Serialize(err)
+    }
+}
+
+/// Index of the side in the paddle inputs of each fixed update.
+pub const fn side_index(side: PlayerSide) -> usize {
+    match side {
+        PlayerSide::Main => 0,
+        PlayerSide::Other => 1,
+        PlayerSide::Top => 2,
+        PlayerSide::Bottom => 3,
+    }
+}
+
+/// Match being recorded.
+#[derive(Resource)]
+pub struct ReplayRecorder(pub Replay);
+
+/// Replay being watched.
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    inputs: Vec<[f32; 4]>,
+    /// Fixed updates already replayed.
+    tick: u64,
+    paused: bool,
+    speed: f32,
+    /// Fixed update the playback must jump to.
+    seek_to: Option<u64>,
+    timestep: Duration,
+    seed: u64,
+    /// Settings restored when the playback stops.
+    previous_seed: u64,
+    previous_timestep: Duration,
+    previous_settings: ReplaySettings,
+}
+
+impl ReplayPlayback {
+    pub const SPEEDS: [f32; 5] = [0.25, 0.5, 1.0, 2.0, 4.0];
+    /// Seconds skipped by each seek.
+    pub const SEEK_STEP: f32 = 5.0;
+
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.inputs.len() as u64
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.tick >= self.ticks()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Game time, in seconds, of a number of fixed updates.
+    pub fn seconds(&self, ticks: u64) -> f32 {
+        ticks as f32 * self.timestep.as_secs_f32()
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    /// Changes to the next faster (or slower) speed in [`ReplayPlayback::SPEEDS`].
+    pub fn change_speed(&mut self, faster: bool) {
+        let current = Self::SPEEDS
+            .iter()
+            .position(|speed| *speed == self.speed)
+            .unwrap_or(2);
+        let next = if faster {
+            (current + 1).min(Self::SPEEDS.len() - 1)
+        } else {
+            current.saturating_sub(1)
+        };
+
+        self.speed = Self::SPEEDS[next];
+    }
+
+    /// Jumps [`ReplayPlayback::SEEK_STEP`] seconds forward (or backward).
+    pub fn seek(&mut self, forward: bool) {
+        let step = (Self::SEEK_STEP / self.timestep.as_secs_f32()) as u64;
+        let target = if forward {
+            self.tick.saturating_add(step).min(self.ticks())
+        } else {
+            self.tick.saturating_sub(step)
+        };
+
+        self.seek_to = Some(target);
+    }
+}
+
+/// Requests to watch a recorded match.
+#[derive(Event)]
+pub struct WatchReplay {
+    /// Replay file, the most recent replay when not given.
+    pub path: Option<PathBuf>,
+}
+
+pub fn start_recording(
+    settings: MatchSettings,
+    rng: Res<GameRng>,
+    time: Res<Time<Fixed>>,
+    mut commands: Commands,
+) {
+    let replay = Replay::new(rng.seed(), time.timestep(), settings.get());
+
+    commands.insert_resource(ReplayRecorder(replay));
+}
+
+pub fn record_player_input(
+    paddles: Query<(&Player, &PaddleInput)>,
+    mut recorder: ResMut<ReplayRecorder>,
+) {
+    let mut inputs = [0.0; 4];
+    for (player, input) in &paddles {
+        inputs[side_index(player.get_side())] = input.0;
+    }
+
+    recorder.0.push(inputs);
+}
+
+pub fn save_recording(recorder: Res<ReplayRecorder>, mut commands: Commands) {
+    commands.remove_resource::<ReplayRecorder>();
+
+    let replay = &recorder.0;
+    if replay.inputs.is_empty() {
+        return;
+    }
+
+    let Some(path) = replay.new_path() else {
+        warn!("no data directory to save the replay");
+        return;
+    };
+
+    match replay.save(&path) {
+        Ok(()) => info!("replay saved to {}", path.display()),
+        Err(err) => warn!("could not save replay to {}: {err}", path.display()),
+    }
+}
+
+pub fn start_playback(
+    mut events: EventReader<WatchReplay>,
+    mut settings: MatchSettings,
+    rng: Res<GameRng>,
+    mut time: ResMut<Time<Fixed>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut commands: Commands,
+) {
+    let Some(event) = events.read().last() else {
+        return;
+    };
+
+    let Some(path) = event.path.clone().or_else(Replay::latest_path) else {
+        warn!("no replay to watch");
+        return;
+    };
+
+    let replay = match Replay::load(&path) {
+        Ok(replay) => replay,
+        Err(err) => {
+            warn!("could not load replay {}: {err}", path.display());
+            return;
+        }
+    };
+
+    let previous_settings = settings.get();
+    settings.set(replay.settings.clone());
+    commands.insert_resource(GameRng::new(replay.seed));
+
+    let previous_timestep = time.timestep();
+    time.set_timestep(replay.timestep);
+
+    commands.insert_resource(ReplayPlayback {
+        inputs: replay.expand_inputs(),
+        tick: 0,
+        paused: false,
+        speed: 1.0,
+        seek_to: None,
+        timestep: replay.timestep,
+        seed: replay.seed,
+        previous_seed: rng.seed(),
+        previous_timestep,
+        previous_settings,
+    });
+
+    next_game_state.set(GameState::playing());
+}
+
+pub fn restart_playback(mut playback: ResMut<ReplayPlayback>) {
+    playback.tick = 0;
+}
+
+/// Keeps the seed of the replay for its rematches, instead of the seed of the next match.
+pub fn keep_playback_seed(playback: Res<ReplayPlayback>, mut rng: ResMut<GameRng>) {
+    *rng = GameRng::new(playback.seed);
+}
+
+pub fn stop_playback(
+    playback: Res<ReplayPlayback>,
+    mut settings: MatchSettings,
+    mut time: ResMut<Time<Virtual>>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+    mut commands: Commands,
+) {
+    settings.set(playback.previous_settings.clone());
+    commands.insert_resource(GameRng::new(playback.previous_seed));
+
+    time.unpause();
+    time.set_relative_speed(1.0);
+    fixed_time.set_timestep(playback.previous_timestep);
+
+    commands.remove_resource::<ReplayPlayback>();
+}
+
+/// Replaces the player inputs with the recorded ones.
+pub fn apply_replay_input(
+    mut paddles: Query<(&Player, &mut PaddleInput), Without<PlayerAI>>,
+    mut playback: ResMut<ReplayPlayback>,
+) {
+    let inputs = playback
+        .inputs
+        .get(playback.tick as usize)
+        .copied()
+        .unwrap_or_default();
+
+    for (player, mut input) in &mut paddles {
+        input.0 = inputs[side_index(player.get_side())];
+    }
+
+    playback.tick += 1;
+}
+
+pub fn update_playback_time(mut playback: ResMut<ReplayPlayback>, mut time: ResMut<Time<Virtual>>) {
+    if playback.is_finished() && !playback.paused {
+        playback.paused = true;
+    }
+
+    if playback.paused {
+        time.pause();
+    } else {
+        time.unpause();
+    }
+    time.set_relative_speed(playback.speed);
+}
+
+/// Simulates the match up to the requested fixed update.
+///
+/// Seeking backward restarts the match from the beginning.
+pub fn seek_playback(world: &mut World) {
+    let Some(target) = world
+        .get_resource_mut::<ReplayPlayback>()
+        .and_then(|mut playback| playback.seek_to.take())
+    else {
+        return;
+    };
+
+    if target < world.resource::<ReplayPlayback>().tick {
+        world.run_schedule(OnExit(InGame));
+        world.run_schedule(OnEnter(InGame));
+    }
+
+    let timestep = world.resource::<Time<Fixed>>().timestep();
+
+    while world.resource::<ReplayPlayback>().tick < target
+        && !world.resource::<GameActiveData>().is_match_over()
+    {
+        world.resource_mut::<Time<Fixed>>().advance_by(timestep);
+        *world.resource_mut::<Time>() = world.resource::<Time<Fixed>>().as_generic();
+        world.run_schedule(FixedMain);
+    }
+
+    *world.resource_mut::<Time>() = world.resource::<Time<Virtual>>().as_generic();
+
+    world.send_event(GameDataUpdated);
+}
+
+pub fn plugin(app: &mut App) {
+    app.add_event::<WatchReplay>();
+
+    app.add_systems(
+        OnEnter(InGame),
+        (
+            start_recording.run_if(not(resource_exists::<ReplayPlayback>)),
+            restart_playback.run_if(resource_exists::<ReplayPlayback>),
+        ),
+    );
+    app.add_systems(
+        OnExit(InGame),
+        (
+            save_recording.run_if(resource_exists::<ReplayRecorder>),
+            keep_playback_seed
+                .after(next_match_seed)
+                .run_if(resource_exists::<ReplayPlayback>),
+        ),
+    );
+    app.add_systems(
+        OnEnter(GameState::MainMenu),
+        stop_playback.run_if(resource_exists::<ReplayPlayback>),
+    );
+
+    app.add_systems(
+        FixedUpdate,
+        (
+            apply_replay_input.run_if(resource_exists::<ReplayPlayback>),
+            record_player_input.run_if(resource_exists::<ReplayRecorder>),
+        )
+            .after(read_player_input)
+            .in_set(GameSystems::Input),
+    );
+
+    app.add_systems(
+        Update,
+        (
+            start_playback.run_if(in_state(GameState::MainMenu).and(on_event::<WatchReplay>)),
+            (update_playback_time, seek_playback)
+                .chain()
+                .run_if(in_state(GameActiveState::Playing).and(resource_exists::<ReplayPlayback>)),
+        ),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> ReplaySettings {
+        ReplaySettings {
+            main: MainPlayerType::default(),
+            opponent: SecondPlayerType::default(),
+            difficulty: AIDifficulty::default(),
+            rules: MatchRules::default(),
+            bounce: PaddleBounce::default(),
+            four_players: FourPlayers::default(),
+            multi_ball: MultiBall::default(),
+            power_ups: PowerUps::default(),
+            layout: ArenaLayout::default(),
+        }
+    }
+
+    fn replay(inputs: &[[f32; 4]]) -> Replay {
+        let mut replay = Replay::new(42, Replay::default_timestep(), settings());
+        for inputs in inputs {
+            replay.push(*inputs);
+        }
+        replay
+    }
+
+    #[test]
+    fn push_merges_repeated_inputs() {
+        let replay = replay(&[[1.0, 0.0, 0.0, 0.0]; 3]);
+
+        assert_eq!(
+            replay.inputs,
+            [InputRun {
+                ticks: 3,
+                inputs: [1.0, 0.0, 0.0, 0.0]
+            }]
+        );
+        assert_eq!(replay.ticks(), 3);
+    }
+
+    #[test]
+    fn truncate_keeps_the_first_ticks() {
+        let up = [1.0, 0.0, 0.0, 0.0];
+        let down = [-1.0, 0.0, 0.0, 0.0];
+        let mut replay = replay(&[up, up, up, down, down, up]);
+
+        replay.truncate(4);
+
+        assert_eq!(replay.ticks(), 4);
+        assert_eq!(replay.expand_inputs(), [up, up, up, down]);
+
+        replay.truncate(3);
+        assert_eq!(
+            replay.inputs,
+            [InputRun {
+                ticks: 3,
+                inputs: up
+            }]
+        );
+
+        replay.truncate(10);
+        assert_eq!(replay.ticks(), 3);
+
+        replay.truncate(0);
+        assert!(replay.inputs.is_empty());
+    }
+
+    #[test]
+    fn parses_the_saved_replay() {
+        let replay = replay(&[[1.0, -1.0, 0.0, 0.5], [0.0; 4]]);
+        let content =
+            ron::ser::to_string_pretty(&replay, ron::ser::PrettyConfig::default()).unwrap();
+
+        assert_eq!(Replay::parse(&content).unwrap(), replay);
+    }
+
+    #[test]
+    fn parses_the_first_version() {
+        let settings = settings();
+        let content = format!(
+            "(version: 1, seed: 42, settings: (main: {}, opponent: {}, difficulty: {}, rules: {}, bounce: {}), \
+             inputs: [(ticks: 2, inputs: (1.0, -1.0))])",
+            ron::to_string(&settings.main).unwrap(),
+            ron::to_string(&settings.opponent).unwrap(),
+            ron::to_string(&settings.difficulty).unwrap(),
+            ron::to_string(&settings.rules).unwrap(),
+            ron::to_string(&settings.bounce).unwrap(),
+        );
+
+        let replay = Replay::parse(&content).unwrap();
+
+        assert_eq!(replay.version, 1);
+        assert_eq!(replay.timestep, Replay::default_timestep());
+        assert_eq!(replay.settings, settings);
+        assert_eq!(replay.expand_inputs(), [[1.0, -1.0, 0.0, 0.0]; 2]);
+    }
+
+    #[test]
+    fn rejects_a_newer_version() {
+        let mut replay = replay(&[[0.0; 4]]);
+        replay.version = Replay::VERSION + 1;
+        let content = ron::to_string(&replay).unwrap();
+
+        assert!(matches!(
+            Replay::parse(&content),
+            Err(ReplayError::Version(version)) if version == Replay::VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_inputs() {
+        let mut content = ron::to_string(&replay(&[[0.0; 4]])).unwrap();
+        content = content.replace("(0.0,0.0,0.0,0.0)", "(0.0,0.0,0.0)");
+
+        assert!(matches!(
+            Replay::parse(&content),
+            Err(ReplayError::Parse(_))
+        ));
+    }
+}
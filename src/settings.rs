@@ -1,35 +1,110 @@
-pub mod scale {
-    pub const PHYSICS_LENGTH_UNIT: f32 = 1.0;
-    pub const PIXEL_PER_METER: f32 = 100.0;
-    #[expect(unused)]
-    pub const PIXEL_PER_CENTIMETER: f32 = PIXEL_PER_METER / 100.0;
+pub mod user;
+
+pub mod time {
+    use bevy::prelude::*;
+    use serde::{Deserialize, Serialize};
+
+    pub const FIXED_UPDATE_HZ: f64 = 64.0;
+    pub const MIN_FIXED_UPDATE_HZ: f64 = 16.0;
+    pub const MAX_FIXED_UPDATE_HZ: f64 = 1000.0;
+
+    #[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize, Resource)]
+    pub struct TimeSettings {
+        /// Fixed updates per second, in which the game logic runs.
+        pub fixed_update_hz: f64,
+    }
+
+    impl Default for TimeSettings {
+        fn default() -> Self {
+            Self {
+                fixed_update_hz: FIXED_UPDATE_HZ,
+            }
+        }
+    }
+}
+
+pub mod storage {
+    use std::path::PathBuf;
+
+    pub const APP_DIR: &str = "bevy_pong";
+
+    /// Directory of the files written by the game, such as replays.
+    pub fn data_dir() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(APP_DIR))
+    }
 }
 
 pub mod camera {
-    use bevy::render::camera::OrthographicProjection;
+    use bevy::{
+        math::{UVec2, Vec2},
+        render::camera::{OrthographicProjection, ScalingMode, Viewport},
+    };
+
+    /// Aspect ratio of the visible game area, letterboxed in windows of other aspect ratios.
+    pub const VIEW_ASPECT_RATIO: f32 = 16.0 / 9.0;
+    /// Space on the left and right of the arena, in arena units.
+    pub const VIEW_HORIZONTAL_MARGIN: f32 = 14.0;
+    /// Space above and below the arena, in arena units.
+    pub const VIEW_VERTICAL_MARGIN: f32 = 11.0;
+    /// Height of the visible game area, in logical pixels, for which the UI is designed.
+    pub const UI_REFERENCE_HEIGHT: f32 = 720.0;
 
-    use super::scale::{PHYSICS_LENGTH_UNIT, PIXEL_PER_METER};
+    /// Size of the visible game area, in arena units, fitting an `arena` size with its margins.
+    pub fn view_size(arena: Vec2) -> Vec2 {
+        let width = (arena.x + VIEW_HORIZONTAL_MARGIN * 2.0)
+            .max((arena.y + VIEW_VERTICAL_MARGIN * 2.0) * VIEW_ASPECT_RATIO);
+        Vec2::new(width, width / VIEW_ASPECT_RATIO)
+    }
 
-    pub const ORTHOGRAPHIC_PROJECTION_SCALE: f32 = PHYSICS_LENGTH_UNIT / PIXEL_PER_METER;
-    pub const DEFAULT_CAMERA_2D_SCALE: f32 = 10.0;
+    pub fn orthographic_projection(arena: Vec2) -> OrthographicProjection {
+        let size = view_size(arena);
 
-    pub fn orthographic_projection() -> OrthographicProjection {
         OrthographicProjection {
-            scale: ORTHOGRAPHIC_PROJECTION_SCALE * DEFAULT_CAMERA_2D_SCALE,
+            scaling_mode: ScalingMode::Fixed {
+                width: size.x,
+                height: size.y,
+            },
             ..OrthographicProjection::default_2d()
         }
     }
+
+    /// Largest area of the window with the [`VIEW_ASPECT_RATIO`], centered in the window.
+    ///
+    /// Returns `None` for an empty window, such as a minimized one.
+    pub fn letterbox_viewport(window_size: UVec2) -> Option<Viewport> {
+        if window_size.cmpeq(UVec2::ZERO).any() {
+            return None;
+        }
+
+        let window = window_size.as_vec2();
+        let size = if window.x / window.y > VIEW_ASPECT_RATIO {
+            Vec2::new(window.y * VIEW_ASPECT_RATIO, window.y)
+        } else {
+            Vec2::new(window.x, window.x / VIEW_ASPECT_RATIO)
+        };
+        let size = size.round().as_uvec2().clamp(UVec2::ONE, window_size);
+
+        Some(Viewport {
+            physical_position: (window_size - size) / 2,
+            physical_size: size,
+            ..Viewport::default()
+        })
+    }
 }
 
 pub mod window {
-    use bevy::window::{Window, WindowResizeConstraints, WindowResolution};
+    use bevy::{
+        prelude::*,
+        window::{WindowResizeConstraints, WindowResolution},
+    };
+    use serde::{Deserialize, Serialize};
 
     pub const WINDOW_WIDTH: f32 = 1280.0;
     pub const WINDOW_HEIGHT: f32 = 720.0;
 
     pub const WINDOW_RESIZE_CONSTRAINTS: WindowResizeConstraints = WindowResizeConstraints {
-        min_width: WINDOW_WIDTH,
-        min_height: WINDOW_HEIGHT,
+        min_width: 320.0,
+        min_height: 180.0,
         max_width: f32::INFINITY,
         max_height: f32::INFINITY,
     };
@@ -38,23 +113,115 @@ pub mod window {
         WindowResolution::new(WINDOW_WIDTH, WINDOW_HEIGHT)
     }
 
+    #[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize, Resource)]
+    pub struct WindowSettings {
+        pub width: f32,
+        pub height: f32,
+    }
+
+    impl Default for WindowSettings {
+        fn default() -> Self {
+            Self {
+                width: WINDOW_WIDTH,
+                height: WINDOW_HEIGHT,
+            }
+        }
+    }
+
     pub fn primary_window() -> Window {
         Window {
             resolution: default_window_resolution(),
             resize_constraints: WINDOW_RESIZE_CONSTRAINTS,
-            resizable: false,
+            resizable: true,
             ..Default::default()
         }
     }
 }
 
 pub mod input {
-    use bevy::input::{gamepad::GamepadButton, keyboard::KeyCode};
+    use bevy::{
+        input::{
+            gamepad::{GamepadAxis, GamepadButton},
+            keyboard::KeyCode,
+        },
+        prelude::Resource,
+    };
+    use serde::{Deserialize, Serialize};
+
+    use crate::game::player::PlayerSide;
 
-    #[derive(PartialEq, Eq)]
+    /// Player actions bound to a key or gamepad button.
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    pub enum InputAction {
+        /// Moves the paddle up, or right for the top and bottom paddles.
+        PaddleUp,
+        /// Moves the paddle down, or left for the top and bottom paddles.
+        PaddleDown,
+        /// Pauses the game, or starts it in the main menu.
+        Pause,
+        /// Moves the focus of the menus up.
+        MenuUp,
+        /// Moves the focus of the menus down.
+        MenuDown,
+        /// Moves the focus of the menus left.
+        MenuLeft,
+        /// Moves the focus of the menus right.
+        MenuRight,
+        /// Presses the focused button.
+        MenuConfirm,
+        /// Goes back to the previous menu.
+        MenuBack,
+    }
+
+    impl InputAction {
+        pub const ALL: [Self; 9] = [
+            Self::PaddleUp,
+            Self::PaddleDown,
+            Self::Pause,
+            Self::MenuUp,
+            Self::MenuDown,
+            Self::MenuLeft,
+            Self::MenuRight,
+            Self::MenuConfirm,
+            Self::MenuBack,
+        ];
+
+        pub const fn is_menu(self) -> bool {
+            matches!(
+                self,
+                Self::MenuUp
+                    | Self::MenuDown
+                    | Self::MenuLeft
+                    | Self::MenuRight
+                    | Self::MenuConfirm
+                    | Self::MenuBack
+            )
+        }
+
+        /// Whether the actions can not share a key or button.
+        ///
+        /// The menu actions are only used in the menus, and the other actions in the game.
+        /// The menu actions of every player do the same, so the players can share them.
+        fn conflicts_with(self, side: PlayerSide, other: Self, other_side: PlayerSide) -> bool {
+            if self.is_menu() != other.is_menu() {
+                return false;
+            }
+
+            self != other || (!self.is_menu() && side != other_side)
+        }
+    }
+
+    #[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
     pub struct KeyboardInputSettings {
         pub paddle_up: KeyCode,
         pub paddle_down: KeyCode,
+        pub pause: KeyCode,
+        pub menu_up: KeyCode,
+        pub menu_down: KeyCode,
+        pub menu_left: KeyCode,
+        pub menu_right: KeyCode,
+        pub menu_confirm: KeyCode,
+        pub menu_back: KeyCode,
     }
 
     impl KeyboardInputSettings {
@@ -62,6 +229,13 @@ pub mod input {
             Self {
                 paddle_up: KeyCode::KeyW,
                 paddle_down: KeyCode::KeyS,
+                pause: KeyCode::Escape,
+                menu_up: KeyCode::ArrowUp,
+                menu_down: KeyCode::ArrowDown,
+                menu_left: KeyCode::ArrowLeft,
+                menu_right: KeyCode::ArrowRight,
+                menu_confirm: KeyCode::Enter,
+                menu_back: KeyCode::Escape,
             }
         }
 
@@ -69,14 +243,63 @@ pub mod input {
             Self {
                 paddle_up: KeyCode::ArrowUp,
                 paddle_down: KeyCode::ArrowDown,
+                pause: KeyCode::Enter,
+                ..Self::new_main_settings()
+            }
+        }
+
+        pub const fn get(&self, action: InputAction) -> KeyCode {
+            match action {
+                InputAction::PaddleUp => self.paddle_up,
+                InputAction::PaddleDown => self.paddle_down,
+                InputAction::Pause => self.pause,
+                InputAction::MenuUp => self.menu_up,
+                InputAction::MenuDown => self.menu_down,
+                InputAction::MenuLeft => self.menu_left,
+                InputAction::MenuRight => self.menu_right,
+                InputAction::MenuConfirm => self.menu_confirm,
+                InputAction::MenuBack => self.menu_back,
+            }
+        }
+
+        pub fn set(&mut self, action: InputAction, key: KeyCode) {
+            match action {
+                InputAction::PaddleUp => self.paddle_up = key,
+                InputAction::PaddleDown => self.paddle_down = key,
+                InputAction::Pause => self.pause = key,
+                InputAction::MenuUp => self.menu_up = key,
+                InputAction::MenuDown => self.menu_down = key,
+                InputAction::MenuLeft => self.menu_left = key,
+                InputAction::MenuRight => self.menu_right = key,
+                InputAction::MenuConfirm => self.menu_confirm = key,
+                InputAction::MenuBack => self.menu_back = key,
             }
         }
     }
 
-    #[derive(PartialEq, Eq)]
+    /// Range of the gamepad axis deadzone.
+    pub const AXIS_DEADZONE_RANGE: std::ops::Range<f32> = 0.0..1.0;
+    /// Range of the gamepad axis response curve exponent.
+    pub const AXIS_CURVE_RANGE: std::ops::RangeInclusive<f32> = 0.25..=4.0;
+
+    /// Gamepad bindings, with the paddle moved by the buttons or proportionally by an axis.
+    #[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
     pub struct GamepadInputSettings {
         pub paddle_up: GamepadButton,
         pub paddle_down: GamepadButton,
+        pub pause: GamepadButton,
+        pub menu_up: GamepadButton,
+        pub menu_down: GamepadButton,
+        pub menu_left: GamepadButton,
+        pub menu_right: GamepadButton,
+        pub menu_confirm: GamepadButton,
+        pub menu_back: GamepadButton,
+        /// Axis moving the paddle up (or right) when positive, like the stick pushed up.
+        pub paddle_axis: GamepadAxis,
+        /// Axis values closer to the center are ignored.
+        pub axis_deadzone: f32,
+        /// Exponent of the axis response, `1.0` being linear and higher values slower near the center.
+        pub axis_curve: f32,
     }
 
     impl GamepadInputSettings {
@@ -84,6 +307,64 @@ pub mod input {
             Self {
                 paddle_up: GamepadButton::DPadUp,
                 paddle_down: GamepadButton::DPadDown,
+                pause: GamepadButton::Start,
+                menu_up: GamepadButton::DPadUp,
+                menu_down: GamepadButton::DPadDown,
+                menu_left: GamepadButton::DPadLeft,
+                menu_right: GamepadButton::DPadRight,
+                menu_confirm: GamepadButton::South,
+                menu_back: GamepadButton::East,
+                paddle_axis: GamepadAxis::LeftStickY,
+                axis_deadzone: 0.125,
+                axis_curve: 1.5,
+            }
+        }
+
+        /// Default settings of the top and bottom paddles, moving horizontally.
+        pub const fn horizontal_settings() -> Self {
+            Self {
+                paddle_up: GamepadButton::DPadRight,
+                paddle_down: GamepadButton::DPadLeft,
+                paddle_axis: GamepadAxis::LeftStickX,
+                ..Self::default_settings()
+            }
+        }
+
+        /// Paddle input of the axis `value`, from `-1.0` to `1.0`.
+        pub fn axis_input(&self, value: f32) -> f32 {
+            let magnitude = (value.abs() - self.axis_deadzone) / (1.0 - self.axis_deadzone);
+            if magnitude <= 0.0 {
+                return 0.0;
+            }
+
+            magnitude.min(1.0).powf(self.axis_curve).copysign(value)
+        }
+
+        pub const fn get(&self, action: InputAction) -> GamepadButton {
+            match action {
+                InputAction::PaddleUp => self.paddle_up,
+                InputAction::PaddleDown => self.paddle_down,
+                InputAction::Pause => self.pause,
+                InputAction::MenuUp => self.menu_up,
+                InputAction::MenuDown => self.menu_down,
+                InputAction::MenuLeft => self.menu_left,
+                InputAction::MenuRight => self.menu_right,
+                InputAction::MenuConfirm => self.menu_confirm,
+                InputAction::MenuBack => self.menu_back,
+            }
+        }
+
+        pub fn set(&mut self, action: InputAction, button: GamepadButton) {
+            match action {
+                InputAction::PaddleUp => self.paddle_up = button,
+                InputAction::PaddleDown => self.paddle_down = button,
+                InputAction::Pause => self.pause = button,
+                InputAction::MenuUp => self.menu_up = button,
+                InputAction::MenuDown => self.menu_down = button,
+                InputAction::MenuLeft => self.menu_left = button,
+                InputAction::MenuRight => self.menu_right = button,
+                InputAction::MenuConfirm => self.menu_confirm = button,
+                InputAction::MenuBack => self.menu_back = button,
             }
         }
     }
@@ -100,4 +381,196 @@ pub mod input {
         KeyboardInputSettings::new_second_settings();
 
     pub const GAMEPAD_SETTINGS: GamepadInputSettings = GamepadInputSettings::default_settings();
+    pub const HORIZONTAL_GAMEPAD_SETTINGS: GamepadInputSettings =
+        GamepadInputSettings::horizontal_settings();
+
+    /// Input bindings of the players, which can be changed by the user.
+    ///
+    /// The third and fourth players, of the four players mode, only have gamepad bindings.
+    #[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize, Resource)]
+    pub struct InputSettings {
+        pub main_keyboard: KeyboardInputSettings,
+        pub second_keyboard: KeyboardInputSettings,
+        pub main_gamepad: GamepadInputSettings,
+        pub second_gamepad: GamepadInputSettings,
+        pub third_gamepad: GamepadInputSettings,
+        pub fourth_gamepad: GamepadInputSettings,
+    }
+
+    impl InputSettings {
+        /// Sides of the keyboard halves.
+        pub const KEYBOARD_SIDES: [PlayerSide; 2] = PlayerSide::CLASSIC;
+
+        /// Bindings of the keyboard half of the `side`.
+        ///
+        /// The top and bottom sides have no keyboard half of their own,
+        /// and use the halves of the main and other sides.
+        pub const fn keyboard(&self, side: PlayerSide) -> &KeyboardInputSettings {
+            match side {
+                PlayerSide::Main | PlayerSide::Top => &self.main_keyboard,
+                PlayerSide::Other | PlayerSide::Bottom => &self.second_keyboard,
+            }
+        }
+
+        pub fn keyboard_mut(&mut self, side: PlayerSide) -> &mut KeyboardInputSettings {
+            match side {
+                PlayerSide::Main | PlayerSide::Top => &mut self.main_keyboard,
+                PlayerSide::Other | PlayerSide::Bottom => &mut self.second_keyboard,
+            }
+        }
+
+        pub const fn gamepad(&self, side: PlayerSide) -> &GamepadInputSettings {
+            match side {
+                PlayerSide::Main => &self.main_gamepad,
+                PlayerSide::Other => &self.second_gamepad,
+                PlayerSide::Top => &self.third_gamepad,
+                PlayerSide::Bottom => &self.fourth_gamepad,
+            }
+        }
+
+        pub fn gamepad_mut(&mut self, side: PlayerSide) -> &mut GamepadInputSettings {
+            match side {
+                PlayerSide::Main => &mut self.main_gamepad,
+                PlayerSide::Other => &mut self.second_gamepad,
+                PlayerSide::Top => &mut self.third_gamepad,
+                PlayerSide::Bottom => &mut self.fourth_gamepad,
+            }
+        }
+
+        /// Whether the key of the `action` is also bound to a conflicting action, of any player.
+        ///
+        /// Both players share the same keyboard.
+        pub fn keyboard_conflict(&self, side: PlayerSide, action: InputAction) -> bool {
+            let key = self.keyboard(side).get(action);
+
+            Self::KEYBOARD_SIDES.into_iter().any(|other_side| {
+                InputAction::ALL.into_iter().any(|other_action| {
+                    action.conflicts_with(side, other_action, other_side)
+                        && self.keyboard(other_side).get(other_action) == key
+                })
+            })
+        }
+
+        /// Whether the gamepad button of the `action` is also bound to a conflicting action of the player.
+        ///
+        /// Each player has its own gamepad.
+        pub fn gamepad_conflict(&self, side: PlayerSide, action: InputAction) -> bool {
+            let gamepad = self.gamepad(side);
+            let button = gamepad.get(action);
+
+            InputAction::ALL.into_iter().any(|other_action| {
+                action.conflicts_with(side, other_action, side)
+                    && gamepad.get(other_action) == button
+            })
+        }
+
+        pub fn has_conflicts(&self) -> bool {
+            InputAction::ALL.into_iter().any(|action| {
+                Self::KEYBOARD_SIDES
+                    .into_iter()
+                    .any(|side| self.keyboard_conflict(side, action))
+                    || PlayerSide::ALL
+                        .into_iter()
+                        .any(|side| self.gamepad_conflict(side, action))
+            })
+        }
+    }
+
+    impl Default for InputSettings {
+        fn default() -> Self {
+            Self {
+                main_keyboard: MAIN_PLAYER_KEYBOARD,
+                second_keyboard: SECOND_PLAYER_KEYBOARD,
+                main_gamepad: GAMEPAD_SETTINGS,
+                second_gamepad: GAMEPAD_SETTINGS,
+                third_gamepad: HORIZONTAL_GAMEPAD_SETTINGS,
+                fourth_gamepad: HORIZONTAL_GAMEPAD_SETTINGS,
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn axis_input_ignores_the_deadzone() {
+            let settings = GamepadInputSettings::default_settings();
+
+            assert_eq!(settings.axis_input(0.0), 0.0);
+            assert_eq!(settings.axis_input(settings.axis_deadzone), 0.0);
+            assert_eq!(settings.axis_input(-settings.axis_deadzone / 2.0), 0.0);
+        }
+
+        #[test]
+        fn full_deflection_gives_the_full_input() {
+            let settings = GamepadInputSettings::default_settings();
+
+            assert_eq!(settings.axis_input(1.0), 1.0);
+            assert_eq!(settings.axis_input(-1.0), -1.0);
+        }
+
+        #[test]
+        fn axis_input_follows_the_response_curve() {
+            let settings = GamepadInputSettings {
+                axis_deadzone: 0.2,
+                axis_curve: 2.0,
+                ..GamepadInputSettings::default_settings()
+            };
+            let linear = GamepadInputSettings {
+                axis_curve: 1.0,
+                ..settings
+            };
+
+            // halfway between the deadzone and the full deflection.
+            assert!((linear.axis_input(0.6) - 0.5).abs() < 1e-5);
+            assert!((settings.axis_input(0.6) - 0.25).abs() < 1e-5);
+            assert!((settings.axis_input(-0.6) + 0.25).abs() < 1e-5);
+            // slower near the center, so the input never decreases with the deflection.
+            let mut previous = 0.0;
+            for step in 0..=10 {
+                let input = settings.axis_input(step as f32 / 10.0);
+                assert!(input >= previous);
+                previous = input;
+            }
+        }
+
+        #[test]
+        fn default_bindings_have_no_conflicts() {
+            assert!(!InputSettings::default().has_conflicts());
+        }
+
+        #[test]
+        fn menu_actions_can_share_the_paddle_keys() {
+            let mut settings = InputSettings::default();
+            settings.main_keyboard.menu_up = settings.main_keyboard.paddle_up;
+            settings.main_gamepad.menu_confirm = settings.main_gamepad.pause;
+
+            assert!(!settings.has_conflicts());
+        }
+
+        #[test]
+        fn players_share_the_menu_actions_but_not_the_paddle_ones() {
+            let mut settings = InputSettings::default();
+            settings.second_keyboard.menu_back = KeyCode::KeyQ;
+            settings.main_keyboard.menu_back = KeyCode::KeyQ;
+            assert!(!settings.keyboard_conflict(PlayerSide::Main, InputAction::MenuBack));
+
+            settings.second_keyboard.paddle_up = settings.main_keyboard.paddle_up;
+            assert!(settings.keyboard_conflict(PlayerSide::Main, InputAction::PaddleUp));
+            assert!(settings.keyboard_conflict(PlayerSide::Other, InputAction::PaddleUp));
+        }
+
+        #[test]
+        fn menu_actions_conflict_with_each_other() {
+            let mut settings = InputSettings::default();
+            settings.main_keyboard.menu_confirm = settings.second_keyboard.menu_back;
+            settings.third_gamepad.menu_up = settings.third_gamepad.menu_down;
+
+            assert!(settings.keyboard_conflict(PlayerSide::Main, InputAction::MenuConfirm));
+            assert!(settings.keyboard_conflict(PlayerSide::Other, InputAction::MenuBack));
+            assert!(settings.gamepad_conflict(PlayerSide::Top, InputAction::MenuUp));
+            assert!(!settings.gamepad_conflict(PlayerSide::Main, InputAction::MenuUp));
+        }
+    }
 }
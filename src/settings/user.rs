@@ -0,0 +1,317 @@
+use std::{fs, io, path::PathBuf};
+
+use bevy::{prelude::*, window::PrimaryWindow};
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{
+    input::{
+        GamepadInputSettings, InputSettings, KeyboardInputSettings, AXIS_CURVE_RANGE,
+        AXIS_DEADZONE_RANGE,
+    },
+    storage::APP_DIR,
+    time::{TimeSettings, MAX_FIXED_UPDATE_HZ, MIN_FIXED_UPDATE_HZ},
+    window::{WindowSettings, WINDOW_RESIZE_CONSTRAINTS},
+};
+
+/// Settings kept in the user settings file.
+#[derive(Clone, Copy, Default, PartialEq, Debug, Serialize)]
+pub struct UserSettings {
+    pub input: InputSettings,
+    pub window: WindowSettings,
+    pub time: TimeSettings,
+}
+
+impl UserSettings {
+    pub const FILE_NAME: &str = "settings.toml";
+
+    /// Path of the settings file, in the user config directory.
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(APP_DIR).join(Self::FILE_NAME))
+    }
+
+    /// Reads the settings file, writing the default settings when it does not exist.
+    ///
+    /// Missing or invalid settings fall back to their defaults, with a warning.
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            warn!("no config directory, using the default settings");
+            return Self::default();
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(content) => Self::parse(&content),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                let settings = Self::default();
+                settings.save();
+                settings
+            }
+            Err(err) => {
+                warn!(
+                    "could not read {}: {err}, using the default settings",
+                    path.display()
+                );
+                Self::default()
+            }
+        }
+    }
+
+    /// Parses the settings, using the default of each missing or invalid setting.
+    pub fn parse(content: &str) -> Self {
+        let table = match content.parse::<toml::Table>() {
+            Ok(table) => table,
+            Err(err) => {
+                warn!("invalid settings file, using the default settings: {err}");
+                return Self::default();
+            }
+        };
+
+        let mut settings = Self::default();
+        let root = SettingsTable::root(&table);
+
+        let input = root.section("input");
+        read_keyboard(
+            &input.section("main_keyboard"),
+            &mut settings.input.main_keyboard,
+        );
+        read_keyboard(
+            &input.section("second_keyboard"),
+            &mut settings.input.second_keyboard,
+        );
+        read_gamepad(
+            &input.section("main_gamepad"),
+            &mut settings.input.main_gamepad,
+        );
+        read_gamepad(
+            &input.section("second_gamepad"),
+            &mut settings.input.second_gamepad,
+        );
+        read_gamepad(
+            &input.section("third_gamepad"),
+            &mut settings.input.third_gamepad,
+        );
+        read_gamepad(
+            &input.section("fourth_gamepad"),
+            &mut settings.input.fourth_gamepad,
+        );
+
+        let window = root.section("window");
+        window.read_valid("width", &mut settings.window.width, |width| {
+            (WINDOW_RESIZE_CONSTRAINTS.min_width..=WINDOW_RESIZE_CONSTRAINTS.max_width)
+                .contains(width)
+        });
+        window.read_valid("height", &mut settings.window.height, |height| {
+            (WINDOW_RESIZE_CONSTRAINTS.min_height..=WINDOW_RESIZE_CONSTRAINTS.max_height)
+                .contains(height)
+        });
+
+        let time = root.section("time");
+        time.read_valid(
+            "fixed_update_hz",
+            &mut settings.time.fixed_update_hz,
+            |hz| (MIN_FIXED_UPDATE_HZ..=MAX_FIXED_UPDATE_HZ).contains(hz),
+        );
+
+        settings
+    }
+
+    pub fn save(&self) {
+        let Some(path) = Self::path() else {
+            warn!("no config directory to save the settings");
+            return;
+        };
+
+        let result = toml::to_string_pretty(self)
+            .map_err(|err| err.to_string())
+            .and_then(|content| {
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+                }
+                fs::write(&path, content).map_err(|err| err.to_string())
+            });
+
+        if let Err(err) = result {
+            warn!("could not save the settings to {}: {err}", path.display());
+        }
+    }
+}
+
+fn read_keyboard(table: &SettingsTable<'_>, keyboard: &mut KeyboardInputSettings) {
+    table.read("paddle_up", &mut keyboard.paddle_up);
+    table.read("paddle_down", &mut keyboard.paddle_down);
+    table.read("pause", &mut keyboard.pause);
+    table.read("menu_up", &mut keyboard.menu_up);
+    table.read("menu_down", &mut keyboard.menu_down);
+    table.read("menu_left", &mut keyboard.menu_left);
+    table.read("menu_right", &mut keyboard.menu_right);
+    table.read("menu_confirm", &mut keyboard.menu_confirm);
+    table.read("menu_back", &mut keyboard.menu_back);
+}
+
+fn read_gamepad(table: &SettingsTable<'_>, gamepad: &mut GamepadInputSettings) {
+    table.read("paddle_up", &mut gamepad.paddle_up);
+    table.read("paddle_down", &mut gamepad.paddle_down);
+    table.read("pause", &mut gamepad.pause);
+    table.read("menu_up", &mut gamepad.menu_up);
+    table.read("menu_down", &mut gamepad.menu_down);
+    table.read("menu_left", &mut gamepad.menu_left);
+    table.read("menu_right", &mut gamepad.menu_right);
+    table.read("menu_confirm", &mut gamepad.menu_confirm);
+    table.read("menu_back", &mut gamepad.menu_back);
+    table.read("paddle_axis", &mut gamepad.paddle_axis);
+    table.read_valid("axis_deadzone", &mut gamepad.axis_deadzone, |deadzone| {
+        AXIS_DEADZONE_RANGE.contains(deadzone)
+    });
+    table.read_valid("axis_curve", &mut gamepad.axis_curve, |curve| {
+        AXIS_CURVE_RANGE.contains(curve)
+    });
+}
+
+/// Section of the settings file, read one setting at a time.
+struct SettingsTable<'a> {
+    table: Option<&'a toml::Table>,
+    path: String,
+}
+
+impl<'a> SettingsTable<'a> {
+    fn root(table: &'a toml::Table) -> Self {
+        Self {
+            table: Some(table),
+            path: String::new(),
+        }
+    }
+
+    fn key_path(&self, key: &str) -> String {
+        if self.path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{key}", self.path)
+        }
+    }
+
+    fn section(&self, key: &str) -> Self {
+        let path = self.key_path(key);
+        let table = match self.table.and_then(|table| table.get(key)) {
+            Some(toml::Value::Table(table)) => Some(table),
+            Some(_) => {
+                warn!("setting \"{path}\" is not a table, using its defaults");
+                None
+            }
+            None => None,
+        };
+
+        Self { table, path }
+    }
+
+    /// Replaces `value` by the setting, keeping it when the setting is missing or invalid.
+    fn read<T: DeserializeOwned>(&self, key: &str, value: &mut T) {
+        self.read_valid(key, value, |_| true);
+    }
+
+    /// Same as [`SettingsTable::read`], also keeping `value` when the setting is not `valid`.
+    fn read_valid<T: DeserializeOwned>(
+        &self,
+        key: &str,
+        value: &mut T,
+        valid: impl Fn(&T) -> bool,
+    ) {
+        let path = self.key_path(key);
+
+        let Some(setting) = self.table.and_then(|table| table.get(key)) else {
+            warn!("missing setting \"{path}\", using the default");
+            return;
+        };
+
+        match setting.clone().try_into::<T>() {
+            Ok(setting) if valid(&setting) => *value = setting,
+            Ok(_) => warn!("setting \"{path}\" is out of range, using the default"),
+            Err(err) => warn!("invalid setting \"{path}\", using the default: {err}"),
+        }
+    }
+}
+
+pub fn save_user_settings(
+    input: Res<InputSettings>,
+    window: Res<WindowSettings>,
+    time: Res<TimeSettings>,
+) {
+    UserSettings {
+        input: *input,
+        window: *window,
+        time: *time,
+    }
+    .save();
+}
+
+/// Loads the user settings file into the settings resources.
+///
+/// Must be added after the window plugin, to resize the primary window.
+pub fn plugin(app: &mut App) {
+    let settings = UserSettings::load();
+
+    app.insert_resource(settings.input);
+    app.insert_resource(settings.window);
+    app.insert_resource(settings.time);
+    app.insert_resource(Time::<Fixed>::from_hz(settings.time.fixed_update_hz));
+
+    let world = app.world_mut();
+    let mut windows = world.query_filtered::<&mut Window, With<PrimaryWindow>>();
+    for mut window in windows.iter_mut(world) {
+        window
+            .resolution
+            .set(settings.window.width, settings.window.height);
+    }
+
+    app.add_systems(
+        Update,
+        save_user_settings.run_if(
+            (resource_changed::<InputSettings>
+                .or(resource_changed::<WindowSettings>)
+                .or(resource_changed::<TimeSettings>))
+            .and(not(resource_added::<InputSettings>)),
+        ),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_settings_use_the_defaults() {
+        let settings = UserSettings::parse("[window]\nwidth = 800.0\n");
+
+        assert_eq!(settings.window.width, 800.0);
+        assert_eq!(settings.window.height, WindowSettings::default().height);
+        assert_eq!(settings.input, InputSettings::default());
+        assert_eq!(settings.time, TimeSettings::default());
+    }
+
+    #[test]
+    fn settings_of_the_wrong_type_use_the_defaults() {
+        let settings = UserSettings::parse(
+            "[window]\nwidth = \"wide\"\nheight = 600.0\n\n[time]\nfixed_update_hz = true\n",
+        );
+
+        assert_eq!(settings.window.width, WindowSettings::default().width);
+        assert_eq!(settings.window.height, 600.0);
+        assert_eq!(settings.time, TimeSettings::default());
+    }
+
+    #[test]
+    fn settings_out_of_range_use_the_defaults() {
+        let settings = UserSettings::parse(
+            "[window]\nwidth = 100.0\nheight = 90.0\n\n[time]\nfixed_update_hz = 0.0\n",
+        );
+
+        assert_eq!(settings.window, WindowSettings::default());
+        assert_eq!(settings.time, TimeSettings::default());
+    }
+
+    #[test]
+    fn window_size_fits_the_resize_constraints() {
+        let settings = UserSettings::parse("[window]\nwidth = 320.0\nheight = 180.0\n");
+
+        assert_eq!(settings.window.width, WINDOW_RESIZE_CONSTRAINTS.min_width);
+        assert_eq!(settings.window.height, WINDOW_RESIZE_CONSTRAINTS.min_height);
+    }
+}
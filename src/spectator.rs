@@ -0,0 +1,649 @@
+use core::time::Duration;
+use std::{
+    io::{self, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::{
+        arena::{Ball, Paddle},
+        event::{GameDataUpdated, MatchOver, PointMarked},
+        physics::LinearVelocity,
+        player::{Player, PlayerSide},
+        resource::{GameActiveData, GameScore, StartMatchTimer},
+        state::{GameState, InGame},
+        GameSystems,
+    },
+    network::discard_recording,
+    replay::{start_recording, MatchSettings, ReplaySettings},
+};
+
+/// Bytes held for a spectator not reading the stream, before disconnecting it.
+const MAX_PENDING: usize = 1 << 20;
+/// Bytes read from the stream at once.
+const READ_SIZE: usize = 16 * 1024;
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct StreamPaddle {
+    pub side: PlayerSide,
+    pub position: Vec2,
+    pub scale: Vec2,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct StreamBall {
+    pub position: Vec2,
+    pub velocity: Vec2,
+}
+
+/// Line of the spectator stream, serialized as JSON with its `type`.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamMessage {
+    /// A match started with the settings, also sent on connection during a match.
+    MatchStart { settings: ReplaySettings },
+    /// The [`GameState`] changed, also sent on connection.
+    State { state: String },
+    /// State of the match, sent every fixed update of the match.
+    Frame {
+        state: String,
+        #[serde(flatten)]
+        game_data: GameActiveData,
+        /// Elapsed seconds of the serve countdown, while it runs.
+        countdown: Option<f32>,
+        paddles: Vec<StreamPaddle>,
+        balls: Vec<StreamBall>,
+    },
+    Point {
+        winner: PlayerSide,
+        score: GameScore,
+    },
+    MatchOver {
+        winner: PlayerSide,
+        score: GameScore,
+    },
+}
+
+impl StreamMessage {
+    pub fn state(state: &GameState) -> Self {
+        Self::State {
+            state: state.to_string(),
+        }
+    }
+}
+
+/// Connection of a spectator, with the bytes it did not read yet.
+struct Spectator {
+    stream: TcpStream,
+    address: SocketAddr,
+    pending: Vec<u8>,
+}
+
+impl Spectator {
+    /// Writes the pending bytes the connection accepts, returning false once disconnected.
+    fn flush(&mut self) -> bool {
+        while !self.pending.is_empty() {
+            match self.stream.write(&self.pending) {
+                Ok(0) => return false,
+                Ok(written) => {
+                    self.pending.drain(..written);
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(_) => return false,
+            }
+        }
+
+        self.pending.len() <= MAX_PENDING
+    }
+}
+
+/// Read-only stream of the game, as newline-delimited JSON [`StreamMessage`] over TCP.
+#[derive(Resource)]
+pub struct SpectatorStream {
+    listener: TcpListener,
+    spectators: Vec<Spectator>,
+}
+
+impl SpectatorStream {
+    /// Listens for spectators on the `address`, such as `127.0.0.1:7879`.
+    pub fn bind(address: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+
+        Ok(Self {
+            listener,
+            spectators: Vec::new(),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    pub fn spectators(&self) -> usize {
+        self.spectators.len()
+    }
+
+    /// Sends the message to every spectator, disconnecting the ones not reading the stream.
+    pub fn send(&mut self, message: &StreamMessage) {
+        self.send_from(0, message);
+    }
+
+    /// Sends the message to the spectators from the index, in their connection order.
+    fn send_from(&mut self, first: usize, message: &StreamMessage) {
+        let line = match serde_json::to_string(message) {
+            Ok(line) => line,
+            Err(err) => {
+                warn!("could not serialize stream message: {err}");
+                return;
+            }
+        };
+
+        let mut index = 0;
+        self.spectators.retain_mut(|spectator| {
+            index += 1;
+            if index <= first {
+                return true;
+            }
+
+            spectator.pending.extend_from_slice(line.as_bytes());
+            spectator.pending.push(b'\n');
+            let connected = spectator.flush();
+            if !connected {
+                info!("spectator {} disconnected", spectator.address);
+            }
+            connected
+        });
+    }
+
+    /// Accepts the new connections, returning how many.
+    fn accept(&mut self) -> usize {
+        let mut accepted = 0;
+
+        loop {
+            match self.listener.accept() {
+                Ok((stream, address)) => {
+                    if let Err(err) = stream.set_nonblocking(true) {
+                        warn!("could not accept spectator {address}: {err}");
+                        continue;
+                    }
+                    info!("spectator {address} connected");
+                    self.spectators.push(Spectator {
+                        stream,
+                        address,
+                        pending: Vec::new(),
+                    });
+                    accepted += 1;
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) => {
+                    warn!("could not accept spectators: {err}");
+                    break;
+                }
+            }
+        }
+
+        accepted
+    }
+}
+
+/// Connection to the [`SpectatorStream`] of another instance, showing its matches.
+#[derive(Resource)]
+pub struct SpectatorClient {
+    stream: TcpStream,
+    address: SocketAddr,
+    /// Bytes received after the last complete line.
+    buffer: Vec<u8>,
+    /// Latest frame not shown yet.
+    frame: Option<StreamMessage>,
+    /// Settings restored when the client leaves the stream.
+    previous: Option<ReplaySettings>,
+}
+
+impl SpectatorClient {
+    /// Connects to the stream at the `address`, such as `127.0.0.1:7879`.
+    pub fn connect(address: &str) -> io::Result<Self> {
+        let address = address.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "the address has no host")
+        })?;
+        let stream = TcpStream::connect_timeout(&address, Duration::from_secs(5))?;
+        stream.set_nonblocking(true)?;
+
+        Ok(Self {
+            stream,
+            address,
+            buffer: Vec::new(),
+            frame: None,
+            previous: None,
+        })
+    }
+
+    pub const fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Messages received since the last call, and whether the stream is still open.
+    ///
+    /// Invalid lines are skipped.
+    fn receive(&mut self) -> (Vec<StreamMessage>, bool) {
+        let mut open = true;
+        let mut chunk = [0; READ_SIZE];
+
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => {
+                    open = false;
+                    break;
+                }
+                Ok(len) => self.buffer.extend_from_slice(&chunk[..len]),
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => {
+                    warn!("could not read the stream: {err}");
+                    open = false;
+                    break;
+                }
+            }
+        }
+
+        let mut messages = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            match serde_json::from_slice(&line) {
+                Ok(message) => messages.push(message),
+                Err(err) => warn!("invalid stream message: {err}"),
+            }
+        }
+
+        (messages, open)
+    }
+}
+
+/// Whether the game shows the stream of another instance instead of simulating its matches.
+pub fn spectating(client: Option<Res<SpectatorClient>>) -> bool {
+    client.is_some()
+}
+
+/// Sends the current match and state to the new spectators.
+pub fn accept_spectators(
+    mut stream: ResMut<SpectatorStream>,
+    game_state: Res<State<GameState>>,
+    settings: MatchSettings,
+) {
+    let connected = stream.spectators.len();
+    if stream.accept() == 0 {
+        return;
+    }
+
+    if matches!(game_state.get(), GameState::GameActive { .. }) {
+        stream.send_from(
+            connected,
+            &StreamMessage::MatchStart {
+                settings: settings.get(),
+            },
+        );
+    }
+    stream.send_from(connected, &StreamMessage::state(game_state.get()));
+}
+
+pub fn stream_match_start(mut stream: ResMut<SpectatorStream>, settings: MatchSettings) {
+    stream.send(&StreamMessage::MatchStart {
+        settings: settings.get(),
+    });
+}
+
+pub fn stream_game_state(mut stream: ResMut<SpectatorStream>, game_state: Res<State<GameState>>) {
+    stream.send(&StreamMessage::state(game_state.get()));
+}
+
+pub fn stream_match_events(
+    mut stream: ResMut<SpectatorStream>,
+    mut point_event: EventReader<PointMarked>,
+    mut match_over_event: EventReader<MatchOver>,
+    game_data: Res<GameActiveData>,
+) {
+    for event in point_event.read() {
+        stream.send(&StreamMessage::Point {
+            winner: event.winner,
+            score: game_data.score(),
+        });
+    }
+    for event in match_over_event.read() {
+        stream.send(&StreamMessage::MatchOver {
+            winner: event.winner,
+            score: game_data.score(),
+        });
+    }
+}
+
+pub fn stream_frame(
+    paddles: Query<(&Player, &Transform), With<Paddle>>,
+    balls: Query<(&Transform, &LinearVelocity), With<Ball>>,
+    game_data: Res<GameActiveData>,
+    match_timer: Option<Res<StartMatchTimer>>,
+    game_state: Res<State<GameState>>,
+    mut stream: ResMut<SpectatorStream>,
+) {
+    if stream.spectators.is_empty() {
+        return;
+    }
+
+    stream.send(&StreamMessage::Frame {
+        state: game_state.get().to_string(),
+        game_data: game_data.clone(),
+        countdown: match_timer.map(|timer| timer.0.elapsed_secs()),
+        paddles: paddles
+            .iter()
+            .map(|(player, transform)| StreamPaddle {
+                side: player.get_side(),
+                position: transform.translation.truncate(),
+                scale: transform.scale.truncate(),
+            })
+            .collect(),
+        balls: balls
+            .iter()
+            .map(|(transform, velocity)| StreamBall {
+                position: transform.translation.truncate(),
+                velocity: velocity.0,
+            })
+            .collect(),
+    });
+}
+
+/// Restores the settings changed by the stream and disconnects.
+fn leave_stream(client: &SpectatorClient, settings: &mut MatchSettings, commands: &mut Commands) {
+    if let Some(previous) = client.previous.clone() {
+        settings.set(previous);
+    }
+
+    commands.remove_resource::<SpectatorClient>();
+}
+
+/// Follows the matches and state of the streamed game, going back to the main menu when it ends.
+pub fn receive_stream(
+    mut client: ResMut<SpectatorClient>,
+    mut settings: MatchSettings,
+    mut game_data: ResMut<GameActiveData>,
+    mut match_over_event: EventWriter<MatchOver>,
+    game_state: Res<State<GameState>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut commands: Commands,
+) {
+    let (messages, open) = client.receive();
+
+    for message in messages {
+        match message {
+            StreamMessage::MatchStart { settings: start } => {
+                if client.previous.is_none() {
+                    client.previous = Some(settings.get());
+                }
+                settings.set(start);
+            }
+            StreamMessage::State { state } => {
+                let Ok(state) = state.parse::<GameState>() else {
+                    continue;
+                };
+                // the spectator has no lobby, and waits in the main menu between the matches.
+                let shown = match (state, game_state.get()) {
+                    (GameState::Lobby | GameState::Tournament, _) => GameState::MainMenu,
+                    (GameState::MatchOver, GameState::MainMenu) => continue,
+                    (state, _) => state,
+                };
+                if shown != *game_state.get() {
+                    next_game_state.set(shown);
+                }
+            }
+            StreamMessage::MatchOver { winner, .. } => {
+                game_data.finish_match(winner);
+                match_over_event.send(MatchOver::new(winner));
+            }
+            frame @ StreamMessage::Frame { .. } => client.frame = Some(frame),
+            StreamMessage::Point { .. } => {}
+        }
+    }
+
+    if !open {
+        info!("the stream of {} ended", client.address);
+        leave_stream(&client, &mut settings, &mut commands);
+        if *game_state.get() != GameState::MainMenu {
+            next_game_state.set(GameState::MainMenu);
+        }
+    }
+}
+
+/// Shows the latest frame of the stream.
+#[expect(clippy::type_complexity)]
+pub fn apply_stream_frame(
+    mut paddles: Query<(&Player, &mut Transform), With<Paddle>>,
+    mut balls: Query<(Entity, &mut Transform, &mut LinearVelocity), (With<Ball>, Without<Paddle>)>,
+    mut game_data: ResMut<GameActiveData>,
+    mut data_event: EventWriter<GameDataUpdated>,
+    match_timer: Option<ResMut<StartMatchTimer>>,
+    mut client: ResMut<SpectatorClient>,
+    mut commands: Commands,
+) {
+    let Some(StreamMessage::Frame {
+        game_data: data,
+        countdown,
+        paddles: paddle_states,
+        balls: ball_states,
+        ..
+    }) = client.frame.take()
+    else {
+        return;
+    };
+
+    if *game_data != data {
+        *game_data = data;
+        data_event.send(GameDataUpdated);
+    }
+
+    for (player, mut transform) in &mut paddles {
+        let side = player.get_side();
+        if let Some(state) = paddle_states.iter().find(|state| state.side == side) {
+            transform.translation = state.position.extend(transform.translation.z);
+            transform.scale = state.scale.extend(transform.scale.z);
+        }
+    }
+
+    // the balls are matched in order, spawning or despawning the ones in excess.
+    let mut states = ball_states.iter();
+    for (entity, mut transform, mut velocity) in &mut balls {
+        match states.next() {
+            Some(state) => {
+                transform.translation = state.position.extend(transform.translation.z);
+                velocity.0 = state.velocity;
+            }
+            None => commands.entity(entity).despawn(),
+        }
+    }
+    for state in states {
+        let mut transform = Ball::initial_transform();
+        transform.translation = state.position.extend(transform.translation.z);
+        commands.spawn((Ball, LinearVelocity(state.velocity), transform));
+    }
+
+    match (countdown, match_timer) {
+        (Some(elapsed), Some(mut timer)) => {
+            timer.0.set_elapsed(Duration::from_secs_f32(elapsed));
+        }
+        (Some(elapsed), None) => {
+            let mut timer = StartMatchTimer::default();
+            timer.0.set_elapsed(Duration::from_secs_f32(elapsed));
+            commands.insert_resource(timer);
+        }
+        (None, Some(_)) => commands.remove_resource::<StartMatchTimer>(),
+        (None, None) => {}
+    }
+}
+
+/// Read-only stream of the game for spectators and broadcast overlays, and the spectator mode showing it.
+///
+/// A [`SpectatorStream`] resource streams the game, and a [`SpectatorClient`] resource
+/// shows the stream of another instance without simulating the matches.
+pub fn plugin(app: &mut App) {
+    app.configure_sets(
+        FixedUpdate,
+        (
+            GameSystems::Input,
+            GameSystems::Simulation,
+            GameSystems::Scoring,
+        )
+            .run_if(not(spectating)),
+    );
+
+    app.add_systems(
+        OnEnter(InGame),
+        (
+            stream_match_start.run_if(resource_exists::<SpectatorStream>),
+            discard_recording.after(start_recording).run_if(spectating),
+        ),
+    );
+    app.add_systems(
+        Update,
+        (
+            accept_spectators,
+            stream_game_state.run_if(state_changed::<GameState>),
+        )
+            .chain()
+            .distributive_run_if(resource_exists::<SpectatorStream>),
+    );
+    app.add_systems(
+        FixedUpdate,
+        (stream_match_events, stream_frame.run_if(in_state(InGame)))
+            .chain()
+            .distributive_run_if(resource_exists::<SpectatorStream>)
+            .after(GameSystems::Scoring),
+    );
+
+    app.add_systems(
+        Update,
+        (receive_stream, apply_stream_frame.run_if(in_state(InGame)))
+            .chain()
+            .distributive_run_if(spectating),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    fn game_data() -> GameActiveData {
+        let mut game_data = GameActiveData::default();
+        game_data.register_point(PlayerSide::Main);
+        game_data
+    }
+
+    #[test]
+    fn stream_messages_are_tagged_json_lines() {
+        let message = StreamMessage::state(&GameState::playing());
+        assert_eq!(
+            serde_json::to_string(&message).unwrap(),
+            r#"{"type":"state","state":"game_active.playing"}"#
+        );
+
+        let message = StreamMessage::Frame {
+            state: GameState::playing().to_string(),
+            game_data: game_data(),
+            countdown: None,
+            paddles: Vec::new(),
+            balls: Vec::new(),
+        };
+        let value: serde_json::Value = serde_json::to_value(&message).unwrap();
+        assert_eq!(value["type"], "frame");
+        // the game data fields are at the top level of the frame.
+        assert!(value.get("score").is_some());
+    }
+
+    #[test]
+    fn stream_messages_round_trip() {
+        let score = game_data().score();
+
+        for message in [
+            StreamMessage::state(&GameState::MainMenu),
+            StreamMessage::Frame {
+                state: GameState::playing().to_string(),
+                game_data: game_data(),
+                countdown: Some(1.5),
+                paddles: vec![StreamPaddle {
+                    side: PlayerSide::Other,
+                    position: Vec2::new(45.0, -3.0),
+                    scale: Vec2::new(1.2, 10.0),
+                }],
+                balls: vec![StreamBall {
+                    position: Vec2::new(1.0, 2.0),
+                    velocity: Vec2::new(-18.0, 4.0),
+                }],
+            },
+            StreamMessage::Point {
+                winner: PlayerSide::Main,
+                score,
+            },
+            StreamMessage::MatchOver {
+                winner: PlayerSide::Main,
+                score,
+            },
+        ] {
+            let line = serde_json::to_string(&message).unwrap();
+            assert!(!line.contains('\n'));
+            assert_eq!(
+                serde_json::from_str::<StreamMessage>(&line).unwrap(),
+                message
+            );
+        }
+    }
+
+    /// Receives from the stream until `done`, giving up after a second.
+    fn receive_until(
+        client: &mut SpectatorClient,
+        done: impl Fn(&SpectatorClient, &[StreamMessage]) -> bool,
+    ) -> Vec<StreamMessage> {
+        let mut messages = Vec::new();
+
+        for _ in 0..100 {
+            let (received, open) = client.receive();
+            assert!(open);
+            messages.extend(received);
+            if done(client, &messages) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        messages
+    }
+
+    #[test]
+    fn receive_keeps_the_partial_lines() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client =
+            SpectatorClient::connect(&listener.local_addr().unwrap().to_string()).unwrap();
+        let (mut stream, _) = listener.accept().unwrap();
+
+        let first = StreamMessage::state(&GameState::playing());
+        let second = StreamMessage::state(&GameState::MatchOver);
+        let line = serde_json::to_string(&first).unwrap();
+        let (start, end) = line.split_at(line.len() / 2);
+
+        stream.write_all(start.as_bytes()).unwrap();
+        let messages = receive_until(&mut client, |client, _| client.buffer.len() == start.len());
+        assert!(messages.is_empty());
+
+        stream.write_all(end.as_bytes()).unwrap();
+        stream.write_all(b"\n").unwrap();
+        stream
+            .write_all(serde_json::to_string(&second).unwrap().as_bytes())
+            .unwrap();
+        stream.write_all(b"\n").unwrap();
+        let messages = receive_until(&mut client, |_, messages| messages.len() == 2);
+
+        assert_eq!(messages, [first, second]);
+        assert!(client.buffer.is_empty());
+    }
+}
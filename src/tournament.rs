@@ -0,0 +1,523 @@
+use core::fmt;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::{
+        player::{MainPlayerType, PlayerSide, SecondPlayerType},
+        resource::{FourPlayers, GameActiveData, MainPlayer, SecondPlayer},
+        state::GameState,
+    },
+    settings::storage,
+    ui::main_menu::spawn_main_menu,
+};
+
+/// Player taking part in a tournament.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Entrant {
+    pub name: String,
+    pub ai: bool,
+}
+
+impl Entrant {
+    /// Longest name, in characters.
+    pub const MAX_NAME_LEN: usize = 16;
+
+    /// Entrant named after its position, such as `Player 3` or `AI 3`.
+    pub fn numbered(number: usize, ai: bool) -> Self {
+        let kind = if ai { "AI" } else { "Player" };
+
+        Self {
+            name: format!("{kind} {number}"),
+            ai,
+        }
+    }
+}
+
+/// Match of the bracket, between the entrants of two matches of the previous round.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct BracketMatch {
+    /// Entrants playing on the left and right sides, once known.
+    pub entrants: [Option<usize>; 2],
+    pub winner: Option<usize>,
+}
+
+/// A match of the bracket, by its round and its index in the round.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Resource)]
+pub struct TournamentMatch {
+    pub round: usize,
+    pub index: usize,
+}
+
+/// Version of the tournament file, read before the rest of the file.
+#[derive(Deserialize)]
+#[serde(rename = "Tournament")]
+struct TournamentHeader {
+    version: u32,
+}
+
+/// Single-elimination tournament, saved after each match so it can be resumed.
+///
+/// The first round is filled up to a power of two with byes, given to the first entrants.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Resource)]
+pub struct Tournament {
+    pub version: u32,
+    pub entrants: Vec<Entrant>,
+    /// Matches of each round, the last round being the final.
+    pub rounds: Vec<Vec<BracketMatch>>,
+}
+
+impl Tournament {
+    pub const VERSION: u32 = 1;
+    pub const FILE_NAME: &str = "tournament.ron";
+    pub const MIN_ENTRANTS: usize = 4;
+    pub const MAX_ENTRANTS: usize = 16;
+
+    /// Generates the bracket of the `entrants`, seeded in their order.
+    ///
+    /// Returns `None` without [`Tournament::MIN_ENTRANTS`] to [`Tournament::MAX_ENTRANTS`] entrants.
+    pub fn new(entrants: Vec<Entrant>) -> Option<Self> {
+        if !(Self::MIN_ENTRANTS..=Self::MAX_ENTRANTS).contains(&entrants.len()) {
+            return None;
+        }
+
+        let size = entrants.len().next_power_of_two();
+        let seeds = Self::seed_order(size);
+        let slot = |seed: usize| (seed < entrants.len()).then_some(seed);
+
+        let mut rounds = vec![seeds
+            .chunks(2)
+            .map(|pair| BracketMatch {
+                entrants: [slot(pair[0]), slot(pair[1])],
+                winner: None,
+            })
+            .collect::<Vec<_>>()];
+        while rounds.last().is_some_and(|round| round.len() > 1) {
+            let matches = rounds.last().map_or(0, Vec::len) / 2;
+            rounds.push(vec![BracketMatch::default(); matches]);
+        }
+
+        let mut tournament = Self {
+            version: Self::VERSION,
+            entrants,
+            rounds,
+        };
+
+        // the entrants without an opponent in the first round go through.
+        for index in 0..tournament.rounds[0].len() {
+            if let [Some(entrant), None] = tournament.rounds[0][index].entrants {
+                tournament.set_winner(TournamentMatch { round: 0, index }, entrant);
+            }
+        }
+
+        Some(tournament)
+    }
+
+    /// Seeds in the order of the first round slots, so the best seeds meet as late as possible.
+    fn seed_order(size: usize) -> Vec<usize> {
+        let mut seeds = vec![0];
+
+        while seeds.len() < size {
+            let count = seeds.len() * 2;
+            seeds = seeds
+                .iter()
+                .flat_map(|seed| [*seed, count - 1 - seed])
+                .collect();
+        }
+
+        seeds
+    }
+
+    /// Name of each round, such as `Round 1`, `Semi-finals` or `Final`.
+    pub fn round_name(&self, round: usize) -> String {
+        match self.rounds.len() - round {
+            1 => "Final".to_string(),
+            2 => "Semi-finals".to_string(),
+            3 => "Quarter-finals".to_string(),
+            _ => format!("Round {}", round + 1),
+        }
+    }
+
+    pub fn get(&self, id: TournamentMatch) -> Option<&BracketMatch> {
+        self.rounds.get(id.round)?.get(id.index)
+    }
+
+    /// First match not played yet, with both entrants known.
+    pub fn next_match(&self) -> Option<TournamentMatch> {
+        self.rounds.iter().enumerate().find_map(|(round, matches)| {
+            matches
+                .iter()
+                .position(|bracket_match| {
+                    bracket_match.winner.is_none()
+                        && bracket_match.entrants.iter().all(Option::is_some)
+                })
+                .map(|index| TournamentMatch { round, index })
+        })
+    }
+
+    /// Winner of the final.
+    pub fn champion(&self) -> Option<&Entrant> {
+        let winner = self.rounds.last()?.first()?.winner?;
+        self.entrants.get(winner)
+    }
+
+    /// Ends a match with the `winner`, who advances to the next round.
+    pub fn set_winner(&mut self, id: TournamentMatch, winner: usize) {
+        let Some(bracket_match) = self
+            .rounds
+            .get_mut(id.round)
+            .and_then(|round| round.get_mut(id.index))
+        else {
+            return;
+        };
+        bracket_match.winner = Some(winner);
+
+        if let Some(next) = self
+            .rounds
+            .get_mut(id.round + 1)
+            .and_then(|round| round.get_mut(id.index / 2))
+        {
+            next.entrants[id.index % 2] = Some(winner);
+        }
+    }
+
+    /// Players of the match, the first entrant playing on the left side.
+    ///
+    /// When only the second entrant is human, they play on the right side against the AI.
+    pub fn players(&self, id: TournamentMatch) -> Option<(MainPlayerType, SecondPlayerType)> {
+        let [Some(left), Some(right)] = self.get(id)?.entrants else {
+            return None;
+        };
+        let left_ai = self.entrants.get(left)?.ai;
+        let right_ai = self.entrants.get(right)?.ai;
+
+        Some(match (left_ai, right_ai) {
+            (false, false) => (MainPlayerType::Player, SecondPlayerType::Player),
+            (false, true) => (MainPlayerType::Player, SecondPlayerType::AI),
+            (true, false) => (MainPlayerType::AI, SecondPlayerType::Player),
+            (true, true) => (MainPlayerType::AI, SecondPlayerType::AI),
+        })
+    }
+
+    /// Entrant playing on a side of the match.
+    pub fn entrant(&self, id: TournamentMatch, side: PlayerSide) -> Option<usize> {
+        let entrants = self.get(id)?.entrants;
+
+        match side {
+            PlayerSide::Main => entrants[0],
+            PlayerSide::Other => entrants[1],
+            PlayerSide::Top | PlayerSide::Bottom => None,
+        }
+    }
+
+    /// Path of the tournament file, in the user data directory.
+    pub fn path() -> Option<PathBuf> {
+        storage::data_dir().map(|dir| dir.join(Self::FILE_NAME))
+    }
+
+    pub fn parse(content: &str) -> Result<Self, TournamentError> {
+        let header: TournamentHeader = ron::from_str(content)?;
+
+        match header.version {
+            Self::VERSION => Ok(ron::from_str(content)?),
+            version => Err(TournamentError::Version(version)),
+        }
+    }
+
+    /// Reads the saved tournament, `None` when there is none.
+    pub fn load(path: &Path) -> Result<Option<Self>, TournamentError> {
+        match fs::read_to_string(path) {
+            Ok(content) => Self::parse(&content).map(Some),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), TournamentError> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let content = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        fs::write(path, content)?;
+
+        Ok(())
+    }
+
+    /// Saves the tournament to its file, with a warning when it fails.
+    pub fn save_or_warn(&self) {
+        let Some(path) = Self::path() else {
+            warn!("no data directory to save the tournament");
+            return;
+        };
+
+        if let Err(err) = self.save(&path) {
+            warn!("could not save the tournament to {}: {err}", path.display());
+        }
+    }
+
+    /// Deletes the saved tournament, once abandoned or replaced.
+    pub fn remove_saved() {
+        let Some(path) = Self::path() else {
+            return;
+        };
+
+        match fs::remove_file(&path) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => warn!("could not remove {}: {err}", path.display()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum TournamentError {
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+    /// The tournament was saved by a newer version of the game.
+    Version(u32),
+}
+
+impl fmt::Display for TournamentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Parse(err) => write!(f, "invalid tournament file: {err}"),
+            Self::Serialize(err) => write!(f, "could not write tournament: {err}"),
+            Self::Version(version) => write!(
+                f,
+                "unsupported tournament version {version}, expected {} or older",
+                Tournament::VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TournamentError {}
+
+impl From<io::Error> for TournamentError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<ron::error::SpannedError> for TournamentError {
+    fn from(err: ron::error::SpannedError) -> Self {
+        Self::Parse(err)
+    }
+}
+
+impl From<ron::Error> for TournamentError {
+    fn from(err: ron::Error) -> Self {
+        Self::Serialize(err)
+    }
+}
+
+/// Players chosen in the main menu, restored when leaving the tournament.
+#[derive(Resource)]
+pub struct TournamentPreviousPlayers {
+    main: MainPlayerType,
+    opponent: SecondPlayerType,
+    four_players: bool,
+}
+
+/// Reads the saved tournament, if any, when opening the tournament for the first time.
+pub fn load_tournament(
+    main_player: Res<MainPlayer>,
+    second_player: Res<SecondPlayer>,
+    four_players: Res<FourPlayers>,
+    tournament: Option<Res<Tournament>>,
+    mut commands: Commands,
+) {
+    commands.remove_resource::<TournamentMatch>();
+
+    if tournament.is_some() {
+        return;
+    }
+
+    commands.insert_resource(TournamentPreviousPlayers {
+        main: main_player.kind,
+        opponent: second_player.opponent,
+        four_players: four_players.enabled,
+    });
+
+    let Some(path) = Tournament::path() else {
+        return;
+    };
+    match Tournament::load(&path) {
+        Ok(Some(tournament)) => commands.insert_resource(tournament),
+        Ok(None) => {}
+        Err(err) => warn!("could not read the tournament {}: {err}", path.display()),
+    }
+}
+
+/// Plays the next match of the tournament, with the human entrants claiming their side in the lobby.
+pub fn start_tournament_match(
+    tournament: &Tournament,
+    main_player: &mut MainPlayer,
+    second_player: &mut SecondPlayer,
+    four_players: &mut FourPlayers,
+    next_game_state: &mut NextState<GameState>,
+    commands: &mut Commands,
+) {
+    let Some(id) = tournament.next_match() else {
+        return;
+    };
+    let Some((main, opponent)) = tournament.players(id) else {
+        return;
+    };
+
+    main_player.kind = main;
+    second_player.opponent = opponent;
+    four_players.enabled = false;
+    commands.insert_resource(id);
+
+    next_game_state.set(if main.is_ai() && opponent.is_ai() {
+        GameState::playing()
+    } else {
+        GameState::Lobby
+    });
+}
+
+/// Advances the winner of the match just played, and saves the tournament.
+pub fn record_tournament_result(
+    id: Res<TournamentMatch>,
+    game_data: Res<GameActiveData>,
+    mut tournament: ResMut<Tournament>,
+) {
+    let Some(winner) = game_data
+        .match_winner()
+        .and_then(|side| tournament.entrant(*id, side))
+    else {
+        return;
+    };
+
+    tournament.set_winner(*id, winner);
+    tournament.save_or_warn();
+}
+
+/// Restores the players of the main menu, keeping the tournament saved to resume it later.
+pub fn leave_tournament(
+    previous: Res<TournamentPreviousPlayers>,
+    mut main_player: ResMut<MainPlayer>,
+    mut second_player: ResMut<SecondPlayer>,
+    mut four_players: ResMut<FourPlayers>,
+    mut commands: Commands,
+) {
+    main_player.kind = previous.main;
+    second_player.opponent = previous.opponent;
+    four_players.enabled = previous.four_players;
+
+    commands.remove_resource::<TournamentPreviousPlayers>();
+    commands.remove_resource::<Tournament>();
+    commands.remove_resource::<TournamentMatch>();
+}
+
+/// Single-elimination tournaments between named entrants, played one match at a time.
+pub fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Tournament), load_tournament);
+    app.add_systems(
+        OnEnter(GameState::MainMenu),
+        leave_tournament
+            .run_if(resource_exists::<TournamentPreviousPlayers>)
+            .before(spawn_main_menu),
+    );
+    app.add_systems(
+        OnEnter(GameState::MatchOver),
+        record_tournament_result
+            .run_if(resource_exists::<TournamentMatch>.and(resource_exists::<Tournament>)),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entrants(count: usize) -> Vec<Entrant> {
+        (1..=count)
+            .map(|number| Entrant::numbered(number, number % 2 == 0))
+            .collect()
+    }
+
+    #[test]
+    fn needs_four_to_sixteen_entrants() {
+        assert_eq!(Tournament::new(entrants(3)), None);
+        assert_eq!(Tournament::new(entrants(17)), None);
+        assert!(Tournament::new(entrants(4)).is_some());
+        assert!(Tournament::new(entrants(16)).is_some());
+    }
+
+    #[test]
+    fn seeds_meet_as_late_as_possible() {
+        assert_eq!(Tournament::seed_order(8), [0, 7, 3, 4, 1, 6, 2, 5]);
+    }
+
+    #[test]
+    fn byes_go_to_the_first_entrants() {
+        let tournament = Tournament::new(entrants(5)).unwrap();
+
+        assert_eq!(tournament.rounds.len(), 3);
+        assert_eq!(tournament.rounds[0].len(), 4);
+        // the first entrant has a bye, and waits for its opponent in the second round.
+        assert_eq!(tournament.rounds[0][0].winner, Some(0));
+        assert_eq!(tournament.rounds[1][0].entrants, [Some(0), None]);
+        assert_eq!(tournament.rounds[0][1].entrants, [Some(3), Some(4)]);
+        assert_eq!(
+            tournament.next_match(),
+            Some(TournamentMatch { round: 0, index: 1 })
+        );
+    }
+
+    #[test]
+    fn winners_advance_to_the_final() {
+        let mut tournament = Tournament::new(entrants(4)).unwrap();
+
+        tournament.set_winner(TournamentMatch { round: 0, index: 0 }, 3);
+        tournament.set_winner(TournamentMatch { round: 0, index: 1 }, 2);
+        let final_match = TournamentMatch { round: 1, index: 0 };
+
+        assert_eq!(tournament.next_match(), Some(final_match));
+        assert_eq!(tournament.entrant(final_match, PlayerSide::Main), Some(3));
+        assert_eq!(tournament.entrant(final_match, PlayerSide::Other), Some(2));
+        // a human entrant plays on the right side against the AI.
+        assert_eq!(
+            tournament.players(final_match),
+            Some((MainPlayerType::AI, SecondPlayerType::Player))
+        );
+        assert_eq!(tournament.champion(), None);
+
+        tournament.set_winner(final_match, 2);
+        assert_eq!(tournament.next_match(), None);
+        assert_eq!(
+            tournament.champion().map(|entrant| entrant.name.as_str()),
+            Some("Player 3")
+        );
+    }
+
+    #[test]
+    fn parses_the_saved_tournament() {
+        let tournament = Tournament::new(entrants(6)).unwrap();
+        let content =
+            ron::ser::to_string_pretty(&tournament, ron::ser::PrettyConfig::default()).unwrap();
+
+        assert_eq!(Tournament::parse(&content).unwrap(), tournament);
+    }
+
+    #[test]
+    fn rejects_a_newer_version() {
+        let mut tournament = Tournament::new(entrants(4)).unwrap();
+        tournament.version = Tournament::VERSION + 1;
+        let content = ron::to_string(&tournament).unwrap();
+
+        assert!(matches!(
+            Tournament::parse(&content),
+            Err(TournamentError::Version(version)) if version == Tournament::VERSION + 1
+        ));
+    }
+}
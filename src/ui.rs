@@ -1,4 +1,10 @@
 pub mod component;
+pub mod controls;
+pub mod history;
 pub mod in_game;
+pub mod lobby;
 pub mod main_menu;
+pub mod match_over;
 pub mod pause_menu;
+pub mod replay;
+pub mod tournament;
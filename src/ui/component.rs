@@ -50,3 +50,297 @@ pub mod button {
         }
     }
 }
+
+pub mod focus {
+    use bevy::{ecs::system::SystemParam, prelude::*, ui::UiSystem};
+
+    use crate::{
+        game::{
+            player::PlayerSide,
+            resource::{PlayerDevice, PlayerDevices},
+        },
+        settings::input::{GamepadInputSettings, InputAction, InputSettings},
+    };
+
+    pub const RING_WIDTH: f32 = 3.0;
+    pub const RING_OFFSET: f32 = 3.0;
+    pub const RING_COLOR: Color = Color::srgb(1.0, 0.8, 0.0);
+
+    /// Left stick deflection that moves the focus.
+    pub const STICK_PRESS: f32 = 0.6;
+    /// Left stick deflection below which the stick is back to the center.
+    pub const STICK_RELEASE: f32 = 0.3;
+
+    /// Node navigated with the menu actions or the left stick, and pressed with the menu confirm action.
+    #[derive(Default, Component)]
+    #[require(Interaction, Outline(ring))]
+    pub struct Focusable;
+
+    /// The focused [`Focusable`], shown with a focus ring.
+    #[derive(Default, Component)]
+    pub struct Focused;
+
+    /// Root node of a menu.
+    ///
+    /// Only the focusables of the scope on top (like the controls menu over the main menu) are navigated.
+    #[derive(Default, Component)]
+    pub struct FocusScope;
+
+    #[derive(SystemSet, Clone, PartialEq, Eq, Hash, Debug)]
+    pub struct FocusSystems;
+
+    fn ring() -> Outline {
+        Outline::new(Val::Px(RING_WIDTH), Val::Px(RING_OFFSET), Color::NONE)
+    }
+
+    #[derive(SystemParam)]
+    pub struct FocusTargets<'w, 's> {
+        focusables: Query<
+            'w,
+            's,
+            (
+                Entity,
+                &'static GlobalTransform,
+                &'static ComputedNode,
+                Has<Focused>,
+            ),
+            With<Focusable>,
+        >,
+        scopes: Query<'w, 's, (Entity, &'static ComputedNode), With<FocusScope>>,
+        parents: Query<'w, 's, &'static Parent>,
+    }
+
+    impl FocusTargets<'_, '_> {
+        /// Shown focusables in the top scope, with their center and if they are focused.
+        fn get(&self) -> Vec<(Entity, Vec2, bool)> {
+            let scope = self
+                .scopes
+                .iter()
+                .max_by_key(|(_, node)| node.stack_index())
+                .map(|(entity, _)| entity);
+
+            self.focusables
+                .iter()
+                .filter(|(entity, _, node, _)| {
+                    !node.is_empty()
+                        && scope.is_none_or(|scope| {
+                            self.parents
+                                .iter_ancestors(*entity)
+                                .any(|ancestor| ancestor == scope)
+                        })
+                })
+                .map(|(entity, transform, _, focused)| {
+                    (entity, transform.translation().truncate(), focused)
+                })
+                .collect()
+        }
+    }
+
+    fn set_focus(commands: &mut Commands, focused: &Query<Entity, With<Focused>>, entity: Entity) {
+        for previous in focused {
+            commands.entity(previous).remove::<Focused>();
+        }
+        commands.entity(entity).insert(Focused);
+    }
+
+    /// Bindings of the `gamepad`, the ones of its player or of the main player when it has none.
+    pub fn gamepad_menu_settings<'a>(
+        settings: &'a InputSettings,
+        devices: &PlayerDevices,
+        gamepad: Entity,
+    ) -> &'a GamepadInputSettings {
+        let side = devices
+            .side_of(PlayerDevice::Gamepad(gamepad))
+            .unwrap_or(PlayerSide::Main);
+        settings.gamepad(side)
+    }
+
+    /// Whether the menu `action` was just pressed in any keyboard half or gamepad.
+    pub fn menu_just_pressed(
+        action: InputAction,
+        keyboard: &ButtonInput<KeyCode>,
+        gamepads: &Query<(Entity, &Gamepad)>,
+        settings: &InputSettings,
+        devices: &PlayerDevices,
+    ) -> bool {
+        InputSettings::KEYBOARD_SIDES
+            .into_iter()
+            .any(|half| keyboard.just_pressed(settings.keyboard(half).get(action)))
+            || gamepads.iter().any(|(entity, gamepad)| {
+                gamepad.just_pressed(gamepad_menu_settings(settings, devices, entity).get(action))
+            })
+    }
+
+    /// Direction pressed in the menu actions or left stick, with the `y` axis down like the UI.
+    fn navigation_direction(
+        keyboard: &ButtonInput<KeyCode>,
+        gamepads: &Query<(Entity, &Gamepad)>,
+        settings: &InputSettings,
+        devices: &PlayerDevices,
+        stick_pressed: &mut bool,
+    ) -> Option<Vec2> {
+        const DIRECTIONS: [(InputAction, Vec2); 4] = [
+            (InputAction::MenuUp, Vec2::NEG_Y),
+            (InputAction::MenuDown, Vec2::Y),
+            (InputAction::MenuLeft, Vec2::NEG_X),
+            (InputAction::MenuRight, Vec2::X),
+        ];
+
+        let pressed = DIRECTIONS
+            .into_iter()
+            .find(|(action, _)| menu_just_pressed(*action, keyboard, gamepads, settings, devices));
+        if let Some((_, direction)) = pressed {
+            return Some(direction);
+        }
+
+        // the stick moves the focus once, until it is released.
+        let stick = gamepads
+            .iter()
+            .map(|(_, gamepad)| gamepad.left_stick())
+            .max_by(|a, b| a.length_squared().total_cmp(&b.length_squared()))
+            .unwrap_or_default();
+
+        if stick.length() < STICK_RELEASE {
+            *stick_pressed = false;
+        }
+        if *stick_pressed || stick.length() < STICK_PRESS {
+            return None;
+        }
+
+        *stick_pressed = true;
+        let direction = if stick.x.abs() > stick.y.abs() {
+            Vec2::new(stick.x.signum(), 0.0)
+        } else {
+            Vec2::new(0.0, -stick.y.signum())
+        };
+        Some(direction)
+    }
+
+    /// Moves the focus to the node under the mouse.
+    #[expect(clippy::type_complexity)]
+    pub fn focus_hovered(
+        hovered: Query<(Entity, &Interaction), (Changed<Interaction>, With<Focusable>)>,
+        focused: Query<Entity, With<Focused>>,
+        mut commands: Commands,
+    ) {
+        for (entity, interaction) in &hovered {
+            if *interaction == Interaction::Hovered && !focused.contains(entity) {
+                set_focus(&mut commands, &focused, entity);
+            }
+        }
+    }
+
+    /// Focuses the first node of the top scope, and moves the focus to the nearest node in the pressed direction.
+    #[expect(clippy::too_many_arguments)]
+    pub fn navigate_focus(
+        keyboard: Res<ButtonInput<KeyCode>>,
+        gamepads: Query<(Entity, &Gamepad)>,
+        settings: Res<InputSettings>,
+        devices: Res<PlayerDevices>,
+        targets: FocusTargets,
+        focused: Query<Entity, With<Focused>>,
+        mut stick_pressed: Local<bool>,
+        mut commands: Commands,
+    ) {
+        let direction = navigation_direction(
+            &keyboard,
+            &gamepads,
+            &settings,
+            &devices,
+            &mut stick_pressed,
+        );
+        let targets = targets.get();
+
+        let Some(&(current, from, _)) = targets.iter().find(|(_, _, focused)| *focused) else {
+            let first = targets
+                .iter()
+                .min_by(|(_, a, _), (_, b, _)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+            if let Some(&(entity, _, _)) = first {
+                set_focus(&mut commands, &focused, entity);
+            }
+            return;
+        };
+        let Some(direction) = direction else {
+            return;
+        };
+
+        // nodes away in the other axis are further than nodes away in the direction.
+        let distance = |to: Vec2| {
+            let offset = to - from;
+            offset.dot(direction) + 2.0 * offset.perp_dot(direction).abs()
+        };
+
+        let next = targets
+            .iter()
+            .filter(|(entity, to, _)| *entity != current && (*to - from).dot(direction) > 1.0)
+            .min_by(|(_, a, _), (_, b, _)| distance(*a).total_cmp(&distance(*b)));
+
+        if let Some(&(entity, _, _)) = next {
+            set_focus(&mut commands, &focused, entity);
+        }
+    }
+
+    /// Presses the focused node with the menu confirm action, as a mouse click.
+    ///
+    /// The key or button pressed is consumed, so it does not trigger other actions (like the pause key).
+    pub fn activate_focus(
+        mut keyboard: ResMut<ButtonInput<KeyCode>>,
+        mut gamepads: Query<(Entity, &mut Gamepad)>,
+        settings: Res<InputSettings>,
+        devices: Res<PlayerDevices>,
+        targets: FocusTargets,
+        mut interactions: Query<&mut Interaction, With<Focusable>>,
+        mut pressed: Local<Option<Entity>>,
+    ) {
+        // the node pressed is released in the next frame.
+        if let Some(entity) = pressed.take() {
+            if let Ok(mut interaction) = interactions.get_mut(entity) {
+                if *interaction == Interaction::Pressed {
+                    *interaction = Interaction::None;
+                }
+            }
+        }
+
+        let Some((entity, _, _)) = targets.get().into_iter().find(|(_, _, focused)| *focused)
+        else {
+            return;
+        };
+
+        let mut activated = false;
+        for half in InputSettings::KEYBOARD_SIDES {
+            let key = settings.keyboard(half).get(InputAction::MenuConfirm);
+            activated |= keyboard.clear_just_pressed(key);
+        }
+        for (entity, mut gamepad) in &mut gamepads {
+            let button = gamepad_menu_settings(&settings, &devices, entity).menu_confirm;
+            activated |= gamepad.digital_mut().clear_just_pressed(button);
+        }
+
+        if activated {
+            if let Ok(mut interaction) = interactions.get_mut(entity) {
+                *interaction = Interaction::Pressed;
+                *pressed = Some(entity);
+            }
+        }
+    }
+
+    pub fn update_focus_ring(mut query: Query<(&mut Outline, Has<Focused>), With<Focusable>>) {
+        for (mut outline, focused) in &mut query {
+            let color = if focused { RING_COLOR } else { Color::NONE };
+            if outline.color != color {
+                outline.color = color;
+            }
+        }
+    }
+
+    pub fn plugin(app: &mut App) {
+        app.add_systems(
+            PreUpdate,
+            (focus_hovered, navigate_focus, activate_focus)
+                .chain()
+                .in_set(FocusSystems)
+                .after(UiSystem::Focus),
+        );
+        app.add_systems(PostUpdate, update_focus_ring);
+    }
+}
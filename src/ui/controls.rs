@@ -0,0 +1,401 @@
+use bevy::{prelude::*, ui::FocusPolicy};
+
+use crate::{
+    game::player::PlayerSide,
+    settings::input::{InputAction, InputSettings},
+    ui::component::{
+        button,
+        focus::{FocusScope, FocusSystems, Focusable},
+        screen,
+    },
+};
+
+/// Whether the controls menu is open, over the main menu or the pause menu.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug, States)]
+pub enum ControlsMenuState {
+    #[default]
+    Closed,
+    Open,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InputDevice {
+    Keyboard,
+    Gamepad,
+}
+
+/// An action of a player on a device.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Binding {
+    pub side: PlayerSide,
+    pub device: InputDevice,
+    pub action: InputAction,
+}
+
+impl Binding {
+    /// Columns of the controls menu.
+    ///
+    /// The third and fourth players only have gamepad bindings.
+    const COLUMNS: [(PlayerSide, InputDevice); 6] = [
+        (PlayerSide::Main, InputDevice::Keyboard),
+        (PlayerSide::Main, InputDevice::Gamepad),
+        (PlayerSide::Other, InputDevice::Keyboard),
+        (PlayerSide::Other, InputDevice::Gamepad),
+        (PlayerSide::Top, InputDevice::Gamepad),
+        (PlayerSide::Bottom, InputDevice::Gamepad),
+    ];
+
+    const fn column_text(side: PlayerSide, device: InputDevice) -> &'static str {
+        match (side, device) {
+            (PlayerSide::Main, InputDevice::Keyboard) => "Player 1 Keyboard",
+            (PlayerSide::Main, InputDevice::Gamepad) => "Player 1 Gamepad",
+            (PlayerSide::Other, InputDevice::Keyboard) => "Player 2 Keyboard",
+            (PlayerSide::Other, InputDevice::Gamepad) => "Player 2 Gamepad",
+            (PlayerSide::Top, _) => "Player 3 Gamepad",
+            (PlayerSide::Bottom, _) => "Player 4 Gamepad",
+        }
+    }
+
+    const fn action_text(action: InputAction) -> &'static str {
+        match action {
+            InputAction::PaddleUp => "Paddle up",
+            InputAction::PaddleDown => "Paddle down",
+            InputAction::Pause => "Pause / Start",
+            InputAction::MenuUp => "Menu up",
+            InputAction::MenuDown => "Menu down",
+            InputAction::MenuLeft => "Menu left",
+            InputAction::MenuRight => "Menu right",
+            InputAction::MenuConfirm => "Menu confirm",
+            InputAction::MenuBack => "Menu back",
+        }
+    }
+
+    fn get_text(&self, settings: &InputSettings) -> String {
+        match self.device {
+            InputDevice::Keyboard => format!("{:?}", settings.keyboard(self.side).get(self.action)),
+            InputDevice::Gamepad => format!("{:?}", settings.gamepad(self.side).get(self.action)),
+        }
+    }
+
+    fn is_conflict(&self, settings: &InputSettings) -> bool {
+        match self.device {
+            InputDevice::Keyboard => settings.keyboard_conflict(self.side, self.action),
+            InputDevice::Gamepad => settings.gamepad_conflict(self.side, self.action),
+        }
+    }
+}
+
+#[derive(Default, Component)]
+#[require(Node, FocusScope)]
+pub struct ControlsMenu;
+
+impl ControlsMenu {
+    pub const BUTTON_TEXT: &str = "Controls";
+
+    const TITLE_FONT_SIZE: f32 = 32.0;
+    const CELL_WIDTH: f32 = 152.0;
+    const CAPTURE_TEXT: &str = "Press...";
+    const CONFLICT_TEXT: &str = "Bindings in red are used by more than one action";
+    const CONFLICT_COLOR: Color = Color::srgb(0.9, 0.1, 0.1);
+
+    fn row_node() -> Node {
+        Node {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(8.0),
+            ..default()
+        }
+    }
+
+    fn cell_node() -> Node {
+        Node {
+            width: Val::Px(Self::CELL_WIDTH),
+            justify_content: JustifyContent::Center,
+            ..default()
+        }
+    }
+
+    fn binding_node() -> Node {
+        Node {
+            width: Val::Px(Self::CELL_WIDTH),
+            ..button::node()
+        }
+    }
+}
+
+/// Button that rebinds the action of a player.
+#[derive(Component)]
+#[require(Button, Focusable)]
+pub struct BindingButton(pub Binding);
+
+#[derive(Component)]
+#[require(Text)]
+pub struct BindingButtonText(pub Binding);
+
+#[derive(Default, Component)]
+#[require(Text)]
+pub struct ConflictText;
+
+#[derive(Default, Component)]
+#[require(Button, Focusable)]
+pub struct ResetControlsButton;
+
+impl ResetControlsButton {
+    const TEXT: &str = "Reset to defaults";
+}
+
+#[derive(Default, Component)]
+#[require(Button, Focusable)]
+pub struct CloseControlsButton;
+
+impl CloseControlsButton {
+    const TEXT: &str = "Back";
+}
+
+/// Binding waiting for the next key or gamepad button pressed.
+#[derive(Resource)]
+pub struct RebindCapture(pub Binding);
+
+pub fn spawn_controls_menu(settings: Res<InputSettings>, mut commands: Commands) {
+    commands
+        .spawn((
+            ControlsMenu,
+            screen::node(),
+            BackgroundColor(Color::BLACK),
+            FocusPolicy::Block,
+            GlobalZIndex(1),
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                Text::new(ControlsMenu::BUTTON_TEXT),
+                TextFont {
+                    font_size: ControlsMenu::TITLE_FONT_SIZE,
+                    ..default()
+                },
+            ));
+            build_header_row(builder);
+            for action in InputAction::ALL {
+                build_action_row(builder, action, &settings);
+            }
+            builder.spawn((
+                ConflictText,
+                Text::new(ControlsMenu::CONFLICT_TEXT),
+                button::text_font(),
+                TextColor(ControlsMenu::CONFLICT_COLOR),
+            ));
+            builder
+                .spawn((
+                    ResetControlsButton,
+                    button::node(),
+                    BackgroundColor(button::BG_COLOR),
+                ))
+                .with_child((
+                    Text::new(ResetControlsButton::TEXT),
+                    button::text_font(),
+                    TextColor(button::TEXT_COLOR),
+                ));
+            builder
+                .spawn((
+                    CloseControlsButton,
+                    button::node(),
+                    BackgroundColor(button::BG_COLOR),
+                ))
+                .with_child((
+                    Text::new(CloseControlsButton::TEXT),
+                    button::text_font(),
+                    TextColor(button::TEXT_COLOR),
+                ));
+        });
+}
+
+pub fn build_header_row(builder: &mut ChildBuilder<'_>) {
+    builder
+        .spawn(ControlsMenu::row_node())
+        .with_children(|builder| {
+            builder.spawn(ControlsMenu::cell_node());
+            for (side, device) in Binding::COLUMNS {
+                builder.spawn(ControlsMenu::cell_node()).with_child((
+                    Text::new(Binding::column_text(side, device)),
+                    button::text_font(),
+                ));
+            }
+        });
+}
+
+pub fn build_action_row(
+    builder: &mut ChildBuilder<'_>,
+    action: InputAction,
+    settings: &InputSettings,
+) {
+    builder
+        .spawn(ControlsMenu::row_node())
+        .with_children(|builder| {
+            builder
+                .spawn(ControlsMenu::cell_node())
+                .with_child((Text::new(Binding::action_text(action)), button::text_font()));
+
+            for (side, device) in Binding::COLUMNS {
+                let binding = Binding {
+                    side,
+                    device,
+                    action,
+                };
+
+                builder
+                    .spawn((
+                        BindingButton(binding),
+                        ControlsMenu::binding_node(),
+                        BackgroundColor(button::BG_COLOR),
+                    ))
+                    .with_child((
+                        BindingButtonText(binding),
+                        Text::new(binding.get_text(settings)),
+                        button::text_font(),
+                        TextColor(button::TEXT_COLOR),
+                    ));
+            }
+        });
+}
+
+pub fn despawn_controls_menu(query: Single<Entity, With<ControlsMenu>>, mut commands: Commands) {
+    let entity = query.into_inner();
+    commands.entity(entity).despawn_recursive();
+    commands.remove_resource::<RebindCapture>();
+}
+
+pub fn binding_button(
+    buttons: Query<(&Interaction, &BindingButton), Changed<Interaction>>,
+    capture: Option<Res<RebindCapture>>,
+    mut commands: Commands,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        // pressing the button again cancels the capture.
+        if capture
+            .as_ref()
+            .is_some_and(|capture| capture.0 == button.0)
+        {
+            commands.remove_resource::<RebindCapture>();
+        } else {
+            commands.insert_resource(RebindCapture(button.0));
+        }
+    }
+}
+
+pub fn capture_binding(
+    capture: Res<RebindCapture>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut settings: ResMut<InputSettings>,
+    mut commands: Commands,
+) {
+    let Binding {
+        side,
+        device,
+        action,
+    } = capture.0;
+
+    match device {
+        InputDevice::Keyboard => {
+            let Some(key) = keyboard.get_just_pressed().next() else {
+                return;
+            };
+            settings.keyboard_mut(side).set(action, *key);
+        }
+        InputDevice::Gamepad => {
+            let Some(button) = gamepads
+                .iter()
+                .find_map(|gamepad| gamepad.get_just_pressed().next())
+            else {
+                return;
+            };
+            settings.gamepad_mut(side).set(action, *button);
+        }
+    }
+
+    commands.remove_resource::<RebindCapture>();
+}
+
+pub fn update_binding_texts(
+    mut texts: Query<(&BindingButtonText, &mut Text, &mut TextColor)>,
+    conflict_text: Single<&mut Node, With<ConflictText>>,
+    settings: Res<InputSettings>,
+    capture: Option<Res<RebindCapture>>,
+) {
+    for (BindingButtonText(binding), mut text, mut color) in &mut texts {
+        let capturing = capture
+            .as_ref()
+            .is_some_and(|capture| capture.0 == *binding);
+
+        text.0 = if capturing {
+            ControlsMenu::CAPTURE_TEXT.to_string()
+        } else {
+            binding.get_text(&settings)
+        };
+        color.0 = if binding.is_conflict(&settings) {
+            ControlsMenu::CONFLICT_COLOR
+        } else {
+            button::TEXT_COLOR
+        };
+    }
+
+    let mut node = conflict_text.into_inner();
+    node.display = if settings.has_conflicts() {
+        Display::Flex
+    } else {
+        Display::None
+    };
+}
+
+pub fn reset_controls_button(
+    button: Single<&Interaction, (Changed<Interaction>, With<ResetControlsButton>)>,
+    mut settings: ResMut<InputSettings>,
+    mut commands: Commands,
+) {
+    let interaction = button.into_inner();
+
+    if *interaction == Interaction::Pressed {
+        *settings = InputSettings::default();
+        commands.remove_resource::<RebindCapture>();
+    }
+}
+
+pub fn close_controls_button(
+    button: Single<&Interaction, (Changed<Interaction>, With<CloseControlsButton>)>,
+    mut next_controls_state: ResMut<NextState<ControlsMenuState>>,
+) {
+    let interaction = button.into_inner();
+
+    if *interaction == Interaction::Pressed {
+        next_controls_state.set(ControlsMenuState::Closed);
+    }
+}
+
+pub fn plugin(app: &mut App) {
+    app.init_state::<ControlsMenuState>();
+
+    // the keys and buttons pressed while capturing are the new bindings.
+    app.configure_sets(
+        PreUpdate,
+        FocusSystems.run_if(not(resource_exists::<RebindCapture>)),
+    );
+
+    app.add_systems(OnEnter(ControlsMenuState::Open), spawn_controls_menu);
+    app.add_systems(OnExit(ControlsMenuState::Open), despawn_controls_menu);
+
+    app.add_systems(
+        Update,
+        (
+            binding_button,
+            capture_binding.run_if(resource_exists::<RebindCapture>),
+            reset_controls_button,
+            close_controls_button,
+            update_binding_texts,
+        )
+            .chain()
+            .run_if(in_state(ControlsMenuState::Open)),
+    );
+}
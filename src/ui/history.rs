@@ -0,0 +1,336 @@
+use bevy::{prelude::*, ui::FocusPolicy};
+
+use crate::{
+    game::state::GameState,
+    history::{MatchHistory, MatchMode, MatchRecord},
+    ui::component::{
+        button,
+        focus::{FocusScope, Focusable},
+        screen,
+    },
+};
+
+/// Whether the match history is open, over the main menu.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug, States)]
+pub enum HistoryMenuState {
+    #[default]
+    Closed,
+    Open,
+}
+
+/// History shown in the menu, read from the history file when the menu opens.
+#[derive(Default, Resource)]
+pub struct LoadedHistory {
+    pub history: MatchHistory,
+    /// Mode of the matches shown, every mode when `None`.
+    pub mode: Option<MatchMode>,
+}
+
+impl LoadedHistory {
+    /// Changes to the next mode of [`MatchMode::ALL`], then back to every mode.
+    fn next_mode(&mut self) {
+        self.mode = match self.mode {
+            None => Some(MatchMode::ALL[0]),
+            Some(mode) => MatchMode::ALL
+                .iter()
+                .position(|other| *other == mode)
+                .and_then(|index| MatchMode::ALL.get(index + 1))
+                .copied(),
+        };
+    }
+}
+
+#[derive(Default, Component)]
+#[require(Node, FocusScope)]
+pub struct HistoryMenu;
+
+impl HistoryMenu {
+    pub const BUTTON_TEXT: &str = "History";
+
+    const TITLE_FONT_SIZE: f32 = 32.0;
+    const HEADER_FONT_SIZE: f32 = 20.0;
+    /// Best players listed.
+    const WIN_RATES: usize = 6;
+    /// Most recent matches listed.
+    const RECENT_MATCHES: usize = 8;
+    const CELL_WIDTH: f32 = 136.0;
+    const EMPTY_TEXT: &str = "No matches played yet";
+    const HEADER_COLOR: Color = Color::srgb(1.0, 0.8, 0.0);
+
+    const COLUMNS: [&str; 5] = ["Date", "Mode", "Score", "Time", "Winner"];
+
+    fn row_node() -> Node {
+        Node {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(8.0),
+            ..default()
+        }
+    }
+
+    fn cell_node() -> Node {
+        Node {
+            width: Val::Px(Self::CELL_WIDTH),
+            justify_content: JustifyContent::Center,
+            ..default()
+        }
+    }
+
+    fn section_node() -> Node {
+        Node {
+            margin: UiRect::top(Val::Px(16.0)),
+            ..default()
+        }
+    }
+
+    fn mode_text(mode: Option<MatchMode>) -> String {
+        format!("Mode: {}", mode.map_or("All", |mode| mode.as_str()))
+    }
+
+    fn cells(record: &MatchRecord) -> [String; 5] {
+        let seconds = record.duration.as_secs();
+
+        [
+            record.date_text(),
+            record.mode.to_string(),
+            record.score.format(&record.sides()),
+            format!("{}:{:02}", seconds / 60, seconds % 60),
+            record.winner_name().unwrap_or("Abandoned").to_string(),
+        ]
+    }
+}
+
+/// Win rates and recent matches of the selected mode, rebuilt when the mode changes.
+#[derive(Default, Component)]
+#[require(Node)]
+pub struct HistoryContent;
+
+impl HistoryContent {
+    fn node() -> Node {
+        Node {
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(4.0),
+            ..default()
+        }
+    }
+}
+
+#[derive(Default, Component)]
+#[require(Button, Focusable)]
+pub struct HistoryModeButton;
+
+#[derive(Default, Component)]
+#[require(Text)]
+pub struct HistoryModeButtonText;
+
+#[derive(Default, Component)]
+#[require(Button, Focusable)]
+pub struct CloseHistoryButton;
+
+impl CloseHistoryButton {
+    const TEXT: &str = "Back";
+}
+
+pub fn load_history(mut commands: Commands) {
+    let history = match MatchHistory::path().map(|path| (MatchHistory::load(&path), path)) {
+        Some((Ok(history), _)) => history,
+        Some((Err(err), path)) => {
+            warn!("could not read the match history {}: {err}", path.display());
+            MatchHistory::default()
+        }
+        None => MatchHistory::default(),
+    };
+
+    commands.insert_resource(LoadedHistory {
+        history,
+        mode: None,
+    });
+}
+
+pub fn spawn_history_menu(loaded: Res<LoadedHistory>, mut commands: Commands) {
+    commands
+        .spawn((
+            HistoryMenu,
+            screen::node(),
+            BackgroundColor(Color::BLACK),
+            FocusPolicy::Block,
+            GlobalZIndex(1),
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                Text::new(HistoryMenu::BUTTON_TEXT),
+                TextFont {
+                    font_size: HistoryMenu::TITLE_FONT_SIZE,
+                    ..default()
+                },
+            ));
+            builder
+                .spawn((
+                    HistoryModeButton,
+                    button::node(),
+                    BackgroundColor(button::BG_COLOR),
+                ))
+                .with_child((
+                    HistoryModeButtonText,
+                    Text::new(HistoryMenu::mode_text(loaded.mode)),
+                    button::text_font(),
+                    TextColor(button::TEXT_COLOR),
+                ));
+            builder
+                .spawn((HistoryContent, HistoryContent::node()))
+                .with_children(|builder| build_history_content(builder, &loaded));
+            builder
+                .spawn((
+                    CloseHistoryButton,
+                    button::node(),
+                    BackgroundColor(button::BG_COLOR),
+                ))
+                .with_child((
+                    Text::new(CloseHistoryButton::TEXT),
+                    button::text_font(),
+                    TextColor(button::TEXT_COLOR),
+                ));
+        });
+}
+
+/// Builds the win rates of the players and the most recent matches of the selected mode.
+pub fn build_history_content(builder: &mut ChildBuilder<'_>, loaded: &LoadedHistory) {
+    let history = &loaded.history;
+    let played = history.matches(loaded.mode).count();
+
+    if played == 0 {
+        builder.spawn((Text::new(HistoryMenu::EMPTY_TEXT), button::text_font()));
+        return;
+    }
+
+    let abandoned = history
+        .matches(loaded.mode)
+        .filter(|record| record.winner.is_none())
+        .count();
+    builder.spawn((
+        Text::new(format!("Matches: {played}, abandoned: {abandoned}")),
+        button::text_font(),
+    ));
+
+    builder.spawn((
+        Text::new("Win rates"),
+        TextFont {
+            font_size: HistoryMenu::HEADER_FONT_SIZE,
+            ..default()
+        },
+        TextColor(HistoryMenu::HEADER_COLOR),
+        HistoryMenu::section_node(),
+    ));
+    for rate in history
+        .win_rates(loaded.mode)
+        .into_iter()
+        .take(HistoryMenu::WIN_RATES)
+    {
+        builder.spawn((
+            Text::new(format!(
+                "{}: {:.0}% (won {} of {})",
+                rate.name,
+                rate.rate() * 100.0,
+                rate.wins,
+                rate.matches
+            )),
+            button::text_font(),
+        ));
+    }
+
+    builder.spawn((
+        Text::new("Recent matches"),
+        TextFont {
+            font_size: HistoryMenu::HEADER_FONT_SIZE,
+            ..default()
+        },
+        TextColor(HistoryMenu::HEADER_COLOR),
+        HistoryMenu::section_node(),
+    ));
+    builder
+        .spawn(HistoryMenu::row_node())
+        .with_children(|builder| {
+            for column in HistoryMenu::COLUMNS {
+                builder.spawn(HistoryMenu::cell_node()).with_child((
+                    Text::new(column),
+                    button::text_font(),
+                    TextColor(HistoryMenu::HEADER_COLOR),
+                ));
+            }
+        });
+    for record in history
+        .matches(loaded.mode)
+        .rev()
+        .take(HistoryMenu::RECENT_MATCHES)
+    {
+        builder
+            .spawn(HistoryMenu::row_node())
+            .with_children(|builder| {
+                for cell in HistoryMenu::cells(record) {
+                    builder
+                        .spawn(HistoryMenu::cell_node())
+                        .with_child((Text::new(cell), button::text_font()));
+                }
+            });
+    }
+}
+
+pub fn despawn_history_menu(query: Single<Entity, With<HistoryMenu>>, mut commands: Commands) {
+    let entity = query.into_inner();
+    commands.entity(entity).despawn_recursive();
+    commands.remove_resource::<LoadedHistory>();
+}
+
+pub fn history_mode_button(
+    button: Single<&Interaction, (Changed<Interaction>, With<HistoryModeButton>)>,
+    button_text: Single<&mut Text, With<HistoryModeButtonText>>,
+    content: Single<Entity, With<HistoryContent>>,
+    mut loaded: ResMut<LoadedHistory>,
+    mut commands: Commands,
+) {
+    let interaction = button.into_inner();
+    let mut text = button_text.into_inner();
+
+    if *interaction == Interaction::Pressed {
+        loaded.next_mode();
+
+        text.0 = HistoryMenu::mode_text(loaded.mode);
+        commands
+            .entity(content.into_inner())
+            .despawn_descendants()
+            .with_children(|builder| build_history_content(builder, &loaded));
+    }
+}
+
+pub fn close_history_button(
+    button: Single<&Interaction, (Changed<Interaction>, With<CloseHistoryButton>)>,
+    mut next_history_state: ResMut<NextState<HistoryMenuState>>,
+) {
+    let interaction = button.into_inner();
+
+    if *interaction == Interaction::Pressed {
+        next_history_state.set(HistoryMenuState::Closed);
+    }
+}
+
+/// Closes the history when leaving the main menu, such as when joining the match of a host.
+pub fn close_history(mut next_history_state: ResMut<NextState<HistoryMenuState>>) {
+    next_history_state.set(HistoryMenuState::Closed);
+}
+
+pub fn plugin(app: &mut App) {
+    app.init_state::<HistoryMenuState>();
+
+    app.add_systems(
+        OnEnter(HistoryMenuState::Open),
+        (load_history, spawn_history_menu).chain(),
+    );
+    app.add_systems(OnExit(HistoryMenuState::Open), despawn_history_menu);
+    app.add_systems(OnExit(GameState::MainMenu), close_history);
+
+    app.add_systems(
+        Update,
+        (history_mode_button, close_history_button).run_if(in_state(HistoryMenuState::Open)),
+    );
+}
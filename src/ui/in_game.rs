@@ -2,8 +2,9 @@ use bevy::prelude::*;
 
 use crate::game::{
     event::GameDataUpdated,
-    player::PlayerSide,
-    resource::{GameActiveData, StartMatchTimer},
+    player::{Player, PlayerSide},
+    power_up::PaddleEffects,
+    resource::{FourPlayers, GameActiveData, GameRng, PowerUps, StartMatchTimer},
     state::InGame,
 };
 
@@ -12,21 +13,15 @@ use crate::game::{
 pub struct GameOSD;
 
 impl GameOSD {
+    /// Fills the camera viewport, which shows the arena.
     pub fn node() -> Node {
         Node {
-            left: Val::ZERO,
-            right: Val::ZERO,
-            top: Val::ZERO,
-            bottom: Val::ZERO,
-            justify_self: JustifySelf::Center,
-            align_self: AlignSelf::Center,
             display: Display::Flex,
             flex_direction: FlexDirection::Column,
             justify_content: JustifyContent::FlexStart,
             align_items: AlignItems::Stretch,
-            aspect_ratio: Some(16.0 / 9.0),
-            height: Val::Vh(100.0),
-            margin: UiRect::axes(Val::Auto, Val::ZERO),
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
             ..Node::DEFAULT
         }
     }
@@ -50,6 +45,25 @@ impl GameScore {
     }
 }
 
+/// Score of the bottom player, in the four players mode.
+#[derive(Default, Component)]
+#[require(Node)]
+pub struct BottomScore;
+
+impl BottomScore {
+    pub fn node() -> Node {
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::ZERO,
+            right: Val::ZERO,
+            bottom: Val::Px(24.0),
+            display: Display::Flex,
+            justify_content: JustifyContent::Center,
+            ..Node::DEFAULT
+        }
+    }
+}
+
 #[derive(Default, Component)]
 #[require(Text)]
 pub struct PlayerScore {
@@ -57,18 +71,101 @@ pub struct PlayerScore {
 }
 
 impl PlayerScore {
+    pub const FONT_SIZE: f32 = 32.0;
+
+    pub const fn new(side: PlayerSide) -> Self {
+        Self { side }
+    }
+
     pub const fn new_main() -> Self {
-        Self {
-            side: PlayerSide::Main,
-        }
+        Self::new(PlayerSide::Main)
     }
+
     pub const fn new_second() -> Self {
-        Self {
-            side: PlayerSide::Other,
+        Self::new(PlayerSide::Other)
+    }
+
+    fn bundle(side: PlayerSide) -> impl Bundle {
+        (
+            Self::new(side),
+            Text::new("0"),
+            TextFont {
+                font_size: Self::FONT_SIZE,
+                ..default()
+            },
+        )
+    }
+}
+
+/// Power-up effects of the left, top and right players, under their scores.
+#[derive(Default, Component)]
+#[require(Node)]
+pub struct PaddleEffectsPanel;
+
+impl PaddleEffectsPanel {
+    pub fn node() -> Node {
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(24.0),
+            right: Val::Px(24.0),
+            top: Val::Px(72.0),
+            display: Display::Flex,
+            flex_direction: FlexDirection::Row,
+            justify_content: JustifyContent::SpaceBetween,
+            ..Node::DEFAULT
+        }
+    }
+}
+
+/// Power-up effects of the bottom player, above its score.
+#[derive(Default, Component)]
+#[require(Node)]
+pub struct BottomEffects;
+
+impl BottomEffects {
+    pub fn node() -> Node {
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::ZERO,
+            right: Val::ZERO,
+            bottom: Val::Px(64.0),
+            display: Display::Flex,
+            justify_content: JustifyContent::Center,
+            ..Node::DEFAULT
         }
     }
 }
 
+/// Active power-up effects of a side, with their remaining seconds.
+#[derive(Default, Component)]
+#[require(Text)]
+pub struct PaddleEffectsText {
+    side: PlayerSide,
+}
+
+impl PaddleEffectsText {
+    pub const FONT_SIZE: f32 = 16.0;
+
+    fn bundle(side: PlayerSide) -> impl Bundle {
+        (
+            Self { side },
+            Text::new(String::new()),
+            TextFont {
+                font_size: Self::FONT_SIZE,
+                ..default()
+            },
+        )
+    }
+
+    fn format(effects: &PaddleEffects) -> String {
+        effects
+            .active()
+            .map(|(effect, remaining)| format!("{} {}", effect.as_str(), remaining.ceil()))
+            .collect::<Vec<_>>()
+            .join("  ")
+    }
+}
+
 #[derive(Default, Component)]
 #[require(Node)]
 pub struct StartMatchCountdown;
@@ -96,12 +193,39 @@ impl StartMatchCountdown {
 #[require(Text)]
 pub struct StartMatchCountdownText;
 
-pub fn spawn_osd(mut commands: Commands) {
+#[derive(Default, Component)]
+#[require(Text)]
+pub struct GameSeedText;
+
+impl GameSeedText {
+    pub const FONT_SIZE: f32 = 12.0;
+    pub const COLOR: Color = Color::srgb(0.5, 0.5, 0.5);
+
+    pub fn node() -> Node {
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(24.0),
+            bottom: Val::Px(8.0),
+            ..Node::DEFAULT
+        }
+    }
+}
+
+pub fn spawn_osd(
+    mut commands: Commands,
+    rng: Res<GameRng>,
+    four_players: Res<FourPlayers>,
+    power_ups: Res<PowerUps>,
+) {
     commands
         .spawn((GameOSD, GameOSD::node()))
         .with_children(|builder| {
-            build_game_score(builder);
+            build_game_score(builder, &four_players);
+            if power_ups.enabled {
+                build_paddle_effects(builder, &four_players);
+            }
             build_start_match_countdown(builder);
+            build_game_seed(builder, &rng);
         });
 }
 
@@ -110,27 +234,55 @@ pub fn despawn_osd(osd: Single<Entity, With<GameOSD>>, mut commands: Commands) {
     commands.entity(entity).despawn_recursive();
 }
 
-pub fn build_game_score(builder: &mut ChildBuilder<'_>) {
+/// Shows the score of each side next to its edge of the arena.
+pub fn build_game_score(builder: &mut ChildBuilder<'_>, four_players: &FourPlayers) {
     builder
         .spawn((GameScore, GameScore::node()))
         .with_children(|builder| {
-            builder.spawn((
-                PlayerScore::new_main(),
-                Text::new("0"),
-                TextFont {
-                    font_size: 32.0,
-                    ..default()
-                },
-            ));
-            builder.spawn((
-                PlayerScore::new_second(),
-                Text::new("0"),
-                TextFont {
-                    font_size: 32.0,
-                    ..default()
-                },
-            ));
+            builder.spawn(PlayerScore::bundle(PlayerSide::Main));
+            if four_players.enabled {
+                builder.spawn(PlayerScore::bundle(PlayerSide::Top));
+            }
+            builder.spawn(PlayerScore::bundle(PlayerSide::Other));
+        });
+
+    if four_players.enabled {
+        builder
+            .spawn((BottomScore, BottomScore::node()))
+            .with_child(PlayerScore::bundle(PlayerSide::Bottom));
+    }
+}
+
+/// Shows the power-up effects of each side next to its score.
+pub fn build_paddle_effects(builder: &mut ChildBuilder<'_>, four_players: &FourPlayers) {
+    builder
+        .spawn((PaddleEffectsPanel, PaddleEffectsPanel::node()))
+        .with_children(|builder| {
+            builder.spawn(PaddleEffectsText::bundle(PlayerSide::Main));
+            if four_players.enabled {
+                builder.spawn(PaddleEffectsText::bundle(PlayerSide::Top));
+            }
+            builder.spawn(PaddleEffectsText::bundle(PlayerSide::Other));
         });
+
+    if four_players.enabled {
+        builder
+            .spawn((BottomEffects, BottomEffects::node()))
+            .with_child(PaddleEffectsText::bundle(PlayerSide::Bottom));
+    }
+}
+
+pub fn build_game_seed(builder: &mut ChildBuilder<'_>, rng: &GameRng) {
+    builder.spawn((
+        GameSeedText,
+        GameSeedText::node(),
+        Text::new(format!("Seed {}", rng.seed())),
+        TextFont {
+            font_size: GameSeedText::FONT_SIZE,
+            ..default()
+        },
+        TextColor(GameSeedText::COLOR),
+    ));
 }
 
 pub fn update_game_score(
@@ -138,12 +290,23 @@ pub fn update_game_score(
     game_data: Res<GameActiveData>,
 ) {
     for (mut text, player_score) in &mut score_text {
-        match player_score.side {
-            PlayerSide::Main => {
-                text.0 = game_data.score().player1().to_string();
-            }
-            PlayerSide::Other => {
-                text.0 = game_data.score().player2().to_string();
+        text.0 = game_data.score().points(player_score.side).to_string();
+    }
+}
+
+pub fn update_paddle_effects_text(
+    mut effects_text: Query<(&mut Text, &PaddleEffectsText)>,
+    paddles: Query<(&Player, &PaddleEffects)>,
+) {
+    for (mut text, effects_text) in &mut effects_text {
+        let effects = paddles
+            .iter()
+            .find(|(player, _)| player.get_side() == effects_text.side);
+
+        if let Some((_, effects)) = effects {
+            let effects = PaddleEffectsText::format(effects);
+            if text.0 != effects {
+                text.0 = effects;
             }
         }
     }
@@ -186,6 +349,7 @@ pub fn plugin(app: &mut App) {
         Update,
         update_game_score.run_if(on_event::<GameDataUpdated>),
     );
+    app.add_systems(Update, update_paddle_effects_text.run_if(in_state(InGame)));
     app.add_systems(
         Update,
         update_start_match_countdown.run_if(resource_exists::<StartMatchTimer>),
@@ -0,0 +1,427 @@
+use bevy::prelude::*;
+
+use crate::{
+    game::{
+        player::PlayerSide,
+        resource::{FourPlayers, MainPlayer, PlayerDevice, PlayerDevices, SecondPlayer},
+        state::GameState,
+    },
+    network::{remote_player_ready, NetworkHost, NetworkSettings},
+    settings::input::{InputAction, InputSettings},
+    tournament::TournamentMatch,
+    ui::component::{
+        button,
+        focus::{gamepad_menu_settings, menu_just_pressed},
+        screen,
+    },
+};
+
+/// Human player of the match, claimed by a device in the lobby.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LobbySlot {
+    /// Side of the input settings and device of the player.
+    pub input: PlayerSide,
+    /// Side of the arena of the player paddle.
+    pub arena: PlayerSide,
+}
+
+impl LobbySlot {
+    /// Human players of the match, in the order of their player number.
+    pub fn get_slots(
+        main_player: &MainPlayer,
+        second_player: &SecondPlayer,
+        four_players: &FourPlayers,
+    ) -> Vec<Self> {
+        let mut slots: Vec<Self> = four_players
+            .sides()
+            .iter()
+            .filter_map(|arena| {
+                let player =
+                    arena.to_player_type(main_player.kind, second_player.opponent, *four_players);
+                player.input_side().map(|input| Self {
+                    input,
+                    arena: *arena,
+                })
+            })
+            .collect();
+
+        slots.sort_by_key(|slot| Self::player_number(slot.input));
+        slots
+    }
+
+    const fn player_number(input: PlayerSide) -> u8 {
+        match input {
+            PlayerSide::Main => 1,
+            PlayerSide::Other => 2,
+            PlayerSide::Top => 3,
+            PlayerSide::Bottom => 4,
+        }
+    }
+
+    const fn arena_text(arena: PlayerSide) -> &'static str {
+        match arena {
+            PlayerSide::Main => "left",
+            PlayerSide::Other => "right",
+            PlayerSide::Top => "top",
+            PlayerSide::Bottom => "bottom",
+        }
+    }
+
+    fn get_text(&self) -> String {
+        format!(
+            "Player {} ({})",
+            Self::player_number(self.input),
+            Self::arena_text(self.arena)
+        )
+    }
+
+    /// Slot claimed by a device moving up (or down) from the slot at `current`.
+    ///
+    /// A device without a slot joins the first slot moving up, and the last moving down.
+    fn next_index(current: Option<usize>, up: bool, len: usize) -> usize {
+        match (current, up) {
+            (Some(index), true) => index.saturating_sub(1),
+            (Some(index), false) => (index + 1).min(len - 1),
+            (None, true) => 0,
+            (None, false) => len - 1,
+        }
+    }
+}
+
+#[derive(Default, Component)]
+#[require(Node)]
+pub struct LobbyMenu;
+
+impl LobbyMenu {
+    const TITLE_TEXT: &str = "Choose your controllers";
+    const TITLE_FONT_SIZE: f32 = 32.0;
+    const SLOT_FONT_SIZE: f32 = 24.0;
+    const HINT_COLOR: Color = Color::srgb(0.5, 0.5, 0.5);
+    const NO_DEVICE_TEXT: &str = "Press to join";
+    const DISCONNECTED_TEXT: &str = " (disconnected)";
+
+    const ONE_SLOT_HINT_TEXT: &str =
+        "Press the paddle up or down key of a keyboard half, or the DPad of a gamepad, to join";
+    const SLOTS_HINT_TEXT: &str =
+        "Press the paddle up key of a keyboard half, or DPadUp, to join the first player, and down to join the last. Press again to change player";
+    const START_HINT_TEXT: &str = "Enter, Start or South to play, Escape or East to go back";
+
+    fn row_node() -> Node {
+        Node {
+            flex_direction: FlexDirection::Row,
+            column_gap: Val::Px(8.0),
+            margin: UiRect::top(Val::Px(16.0)),
+            ..default()
+        }
+    }
+
+    fn device_text(
+        device: Option<PlayerDevice>,
+        settings: &InputSettings,
+        gamepads: &Query<(Option<&Name>, Has<Gamepad>)>,
+    ) -> String {
+        match device {
+            Some(PlayerDevice::Keyboard(half)) => {
+                let keyboard = settings.keyboard(half);
+                format!(
+                    "Keyboard {:?}/{:?}",
+                    keyboard.paddle_up, keyboard.paddle_down
+                )
+            }
+            Some(PlayerDevice::Gamepad(entity)) => {
+                let (name, connected) = gamepads.get(entity).unwrap_or_default();
+                let name = name.map_or("Gamepad", Name::as_str);
+                let status = if connected {
+                    ""
+                } else {
+                    Self::DISCONNECTED_TEXT
+                };
+                format!("{name}{status}")
+            }
+            None => Self::NO_DEVICE_TEXT.to_string(),
+        }
+    }
+}
+
+#[derive(Component)]
+#[require(Text)]
+pub struct LobbySlotText(pub LobbySlot);
+
+/// Connection of the remote player, when hosting a match.
+#[derive(Default, Component)]
+#[require(Text)]
+pub struct RemotePlayerText;
+
+impl RemotePlayerText {
+    fn get_text(host: Option<&NetworkHost>, settings: &NetworkSettings) -> String {
+        let status = match host.and_then(NetworkHost::remote) {
+            Some(remote) => format!("Connected from {}", remote.address),
+            None if host.is_some() => format!("Waiting on port {}", settings.port),
+            None => "Not hosting".to_string(),
+        };
+
+        format!("Player 2 (right): {status}")
+    }
+}
+
+#[derive(Default, Component)]
+#[require(Text)]
+pub struct ConnectedGamepadsText;
+
+/// Starts the match, also with `Enter`, `Start` or `South`.
+///
+/// The lobby buttons are not focusable, as the arrows and `DPad` claim the players.
+#[derive(Default, Component)]
+#[require(Button)]
+pub struct StartMatchButton;
+
+impl StartMatchButton {
+    const TEXT: &str = "Play";
+}
+
+/// Goes back to the main menu, also with `Escape` or `East`.
+#[derive(Default, Component)]
+#[require(Button)]
+pub struct LobbyBackButton;
+
+impl LobbyBackButton {
+    const TEXT: &str = "Back";
+}
+
+pub fn spawn_lobby_menu(
+    main_player: Res<MainPlayer>,
+    second_player: Res<SecondPlayer>,
+    four_players: Res<FourPlayers>,
+    mut commands: Commands,
+) {
+    let slots = LobbySlot::get_slots(&main_player, &second_player, &four_players);
+    let hint = if slots.len() > 1 {
+        LobbyMenu::SLOTS_HINT_TEXT
+    } else {
+        LobbyMenu::ONE_SLOT_HINT_TEXT
+    };
+
+    commands
+        .spawn((LobbyMenu, screen::node(), BackgroundColor(screen::BG_COLOR)))
+        .with_children(|builder| {
+            builder.spawn((
+                Text::new(LobbyMenu::TITLE_TEXT),
+                TextFont {
+                    font_size: LobbyMenu::TITLE_FONT_SIZE,
+                    ..default()
+                },
+            ));
+            for slot in slots {
+                builder.spawn((
+                    LobbySlotText(slot),
+                    TextFont {
+                        font_size: LobbyMenu::SLOT_FONT_SIZE,
+                        ..default()
+                    },
+                ));
+            }
+            if second_player.opponent.is_remote() {
+                builder.spawn((
+                    RemotePlayerText,
+                    TextFont {
+                        font_size: LobbyMenu::SLOT_FONT_SIZE,
+                        ..default()
+                    },
+                ));
+            }
+            builder.spawn((ConnectedGamepadsText, button::text_font()));
+            for text in [hint, LobbyMenu::START_HINT_TEXT] {
+                builder.spawn((
+                    Text::new(text),
+                    button::text_font(),
+                    TextColor(LobbyMenu::HINT_COLOR),
+                ));
+            }
+            builder
+                .spawn(LobbyMenu::row_node())
+                .with_children(|builder| {
+                    builder
+                        .spawn((
+                            LobbyBackButton,
+                            button::node(),
+                            BackgroundColor(button::BG_COLOR),
+                        ))
+                        .with_child((
+                            Text::new(LobbyBackButton::TEXT),
+                            button::text_font(),
+                            TextColor(button::TEXT_COLOR),
+                        ));
+                    builder
+                        .spawn((
+                            StartMatchButton,
+                            button::node(),
+                            BackgroundColor(button::BG_COLOR),
+                        ))
+                        .with_child((
+                            Text::new(StartMatchButton::TEXT),
+                            button::text_font(),
+                            TextColor(button::TEXT_COLOR),
+                        ));
+                });
+        });
+}
+
+pub fn despawn_lobby_menu(query: Single<Entity, With<LobbyMenu>>, mut commands: Commands) {
+    let entity = query.into_inner();
+    commands.entity(entity).despawn_recursive();
+}
+
+/// Claims the previous player with the paddle up input of a device, and the next with paddle down.
+pub fn claim_player(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<(Entity, &Gamepad)>,
+    settings: Res<InputSettings>,
+    main_player: Res<MainPlayer>,
+    second_player: Res<SecondPlayer>,
+    four_players: Res<FourPlayers>,
+    mut devices: ResMut<PlayerDevices>,
+) {
+    let slots = LobbySlot::get_slots(&main_player, &second_player, &four_players);
+    if slots.is_empty() {
+        return;
+    }
+
+    // moves of each device, paddle up being `true`.
+    let mut moves = Vec::new();
+    for half in InputSettings::KEYBOARD_SIDES {
+        let keys = settings.keyboard(half);
+        if keyboard.just_pressed(keys.paddle_up) {
+            moves.push((PlayerDevice::Keyboard(half), true));
+        } else if keyboard.just_pressed(keys.paddle_down) {
+            moves.push((PlayerDevice::Keyboard(half), false));
+        }
+    }
+    for (entity, gamepad) in &gamepads {
+        if gamepad.just_pressed(GamepadButton::DPadUp) {
+            moves.push((PlayerDevice::Gamepad(entity), true));
+        } else if gamepad.just_pressed(GamepadButton::DPadDown) {
+            moves.push((PlayerDevice::Gamepad(entity), false));
+        }
+    }
+
+    for (device, up) in moves {
+        let current = devices
+            .side_of(device)
+            .and_then(|side| slots.iter().position(|slot| slot.input == side));
+        let slot = slots[LobbySlot::next_index(current, up, slots.len())];
+
+        devices.claim(slot.input, device);
+    }
+}
+
+pub fn update_lobby_texts(
+    mut slot_texts: Query<(&LobbySlotText, &mut Text)>,
+    connected_text: Single<&mut Text, (With<ConnectedGamepadsText>, Without<LobbySlotText>)>,
+    gamepads: Query<(Option<&Name>, Has<Gamepad>)>,
+    connected: Query<(), With<Gamepad>>,
+    devices: Res<PlayerDevices>,
+    settings: Res<InputSettings>,
+) {
+    for (LobbySlotText(slot), mut text) in &mut slot_texts {
+        let device = LobbyMenu::device_text(devices.get(slot.input), &settings, &gamepads);
+        text.0 = format!("{}: {device}", slot.get_text());
+    }
+
+    let mut text = connected_text.into_inner();
+    text.0 = match connected.iter().count() {
+        1 => "1 gamepad connected".to_string(),
+        count => format!("{count} gamepads connected"),
+    };
+}
+
+pub fn update_remote_player_text(
+    text: Single<&mut Text, With<RemotePlayerText>>,
+    host: Option<Res<NetworkHost>>,
+    settings: Res<NetworkSettings>,
+) {
+    let mut text = text.into_inner();
+    text.0 = RemotePlayerText::get_text(host.as_deref(), &settings);
+}
+
+/// State left to from the lobby, the bracket of a tournament or the main menu.
+fn back_state(tournament_match: Option<&TournamentMatch>) -> GameState {
+    if tournament_match.is_some() {
+        GameState::Tournament
+    } else {
+        GameState::MainMenu
+    }
+}
+
+/// Starts the match with the menu confirm action or the gamepad pause button, and goes back with the menu back action.
+///
+/// The match only starts once the remote player, if any, is connected.
+#[expect(clippy::too_many_arguments)]
+pub fn lobby_keys(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<(Entity, &Gamepad)>,
+    settings: Res<InputSettings>,
+    devices: Res<PlayerDevices>,
+    second_player: Res<SecondPlayer>,
+    host: Option<Res<NetworkHost>>,
+    tournament_match: Option<Res<TournamentMatch>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    let just_pressed =
+        |action| menu_just_pressed(action, &keyboard, &gamepads, &settings, &devices);
+
+    let start = just_pressed(InputAction::MenuConfirm)
+        || gamepads.iter().any(|(entity, gamepad)| {
+            gamepad.just_pressed(gamepad_menu_settings(&settings, &devices, entity).pause)
+        });
+
+    if start && remote_player_ready(&second_player, host.as_deref()) {
+        next_game_state.set(GameState::playing());
+    } else if just_pressed(InputAction::MenuBack) {
+        next_game_state.set(back_state(tournament_match.as_deref()));
+    }
+}
+
+pub fn start_match_button(
+    button: Single<&Interaction, (Changed<Interaction>, With<StartMatchButton>)>,
+    second_player: Res<SecondPlayer>,
+    host: Option<Res<NetworkHost>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    let interaction = button.into_inner();
+
+    if *interaction == Interaction::Pressed && remote_player_ready(&second_player, host.as_deref())
+    {
+        next_game_state.set(GameState::playing());
+    }
+}
+
+pub fn lobby_back_button(
+    button: Single<&Interaction, (Changed<Interaction>, With<LobbyBackButton>)>,
+    tournament_match: Option<Res<TournamentMatch>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    let interaction = button.into_inner();
+
+    if *interaction == Interaction::Pressed {
+        next_game_state.set(back_state(tournament_match.as_deref()));
+    }
+}
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Lobby), spawn_lobby_menu);
+    app.add_systems(OnExit(GameState::Lobby), despawn_lobby_menu);
+
+    app.add_systems(
+        Update,
+        (
+            claim_player,
+            update_lobby_texts,
+            update_remote_player_text.run_if(any_with_component::<RemotePlayerText>),
+            lobby_keys,
+            start_match_button,
+            lobby_back_button,
+        )
+            .chain()
+            .run_if(in_state(GameState::Lobby)),
+    );
+}
@@ -1,34 +1,88 @@
-use bevy::prelude::*;
+use bevy::{asset::LoadedFolder, prelude::*};
 
 use crate::game::{
-    player::SecondPlayerType,
-    resource::{SecondPlayer, UserGamepad},
-    state::{GameActiveState, GameState, InGame},
+    layout::{ArenaLayout, ArenaLayouts},
+    player::{AIDifficulty, MainPlayerType, PlayerSide, SecondPlayerType},
+    resource::{
+        FourPlayers, MainPlayer, MatchRules, MultiBall, PaddleBounce, PaddleBounceMode, PowerUps,
+        SecondPlayer,
+    },
+    state::GameState,
 };
-use crate::ui::component::{button, screen};
+use crate::replay::{self, WatchReplay};
+use crate::ui::component::{
+    button,
+    focus::{FocusScope, Focusable},
+    screen,
+};
+use crate::ui::controls::{self, ControlsMenuState};
+use crate::ui::history::{HistoryMenu, HistoryMenuState};
+use crate::ui::lobby::LobbySlot;
+use crate::ui::tournament::TournamentMenu;
 
 #[derive(Default, Component)]
-#[require(Node)]
+#[require(Node, FocusScope)]
 pub struct MainMenu;
 
 #[derive(Default, Component)]
-#[require(Button)]
+#[require(Button, Focusable)]
 pub struct PlayButton;
 
 #[derive(Default, Component)]
-#[require(Button)]
+#[require(Button, Focusable)]
+pub struct WatchReplayButton;
+
+impl WatchReplayButton {
+    const TEXT: &str = "Watch replay";
+}
+
+#[derive(Default, Component)]
+#[require(Button, Focusable)]
+pub struct HistoryButton;
+
+#[derive(Default, Component)]
+#[require(Button, Focusable)]
+pub struct TournamentButton;
+
+#[derive(Default, Component)]
+#[require(Button, Focusable)]
 pub struct ChangePlayerButton;
 
 impl ChangePlayerButton {
     const TWO_PLAYERS_TEXT: &str = "2 Players";
     const AI_TEXT: &str = "AI";
-
-    const fn get_text(player: SecondPlayerType) -> &'static str {
-        match player {
-            SecondPlayerType::Player => Self::TWO_PLAYERS_TEXT,
-            SecondPlayerType::AI => Self::AI_TEXT,
+    const AI_LEFT_TEXT: &str = "AI vs Player";
+    const AI_VS_AI_TEXT: &str = "AI vs AI";
+    const REMOTE_TEXT: &str = "Host online";
+
+    /// Order in which the button changes between the players.
+    const MODES: [(MainPlayerType, SecondPlayerType); 5] = [
+        (MainPlayerType::Player, SecondPlayerType::Player),
+        (MainPlayerType::Player, SecondPlayerType::AI),
+        (MainPlayerType::AI, SecondPlayerType::Player),
+        (MainPlayerType::AI, SecondPlayerType::AI),
+        (MainPlayerType::Player, SecondPlayerType::Remote),
+    ];
+
+    const fn get_text(main: MainPlayerType, opponent: SecondPlayerType) -> &'static str {
+        match (main, opponent) {
+            (MainPlayerType::Player, SecondPlayerType::Player) => Self::TWO_PLAYERS_TEXT,
+            (MainPlayerType::Player, SecondPlayerType::AI) => Self::AI_TEXT,
+            (MainPlayerType::AI, SecondPlayerType::Player) => Self::AI_LEFT_TEXT,
+            (MainPlayerType::AI, SecondPlayerType::AI) => Self::AI_VS_AI_TEXT,
+            (_, SecondPlayerType::Remote) => Self::REMOTE_TEXT,
         }
     }
+
+    /// Changes to the next players in [`ChangePlayerButton::MODES`].
+    fn change_players(main: &mut MainPlayer, second: &mut SecondPlayer) {
+        let modes = Self::MODES;
+        let current = modes
+            .iter()
+            .position(|mode| *mode == (main.kind, second.opponent))
+            .unwrap_or(0);
+        (main.kind, second.opponent) = modes[(current + 1) % modes.len()];
+    }
 }
 
 #[derive(Default, Component)]
@@ -36,7 +90,240 @@ impl ChangePlayerButton {
 pub struct ChangePlayerButtonText;
 
 #[derive(Default, Component)]
-#[require(Button)]
+#[require(Node)]
+pub struct OpponentRow;
+
+impl OpponentRow {
+    fn node() -> Node {
+        Node {
+            flex_direction: FlexDirection::Row,
+            column_gap: Val::Px(8.0),
+            ..default()
+        }
+    }
+}
+
+#[derive(Default, Component)]
+#[require(Button, Focusable)]
+pub struct DifficultyButton;
+
+impl DifficultyButton {
+    const fn get_text(difficulty: AIDifficulty) -> &'static str {
+        match difficulty {
+            AIDifficulty::Easy => "Easy",
+            AIDifficulty::Normal => "Normal",
+            AIDifficulty::Hard => "Hard",
+            AIDifficulty::Perfect => "Perfect",
+        }
+    }
+
+    fn display(
+        main_player: &MainPlayer,
+        second_player: &SecondPlayer,
+        four_players: &FourPlayers,
+    ) -> Display {
+        let extra_ai = four_players.enabled
+            && !(four_players.top.is_player() && four_players.bottom.is_player());
+
+        if main_player.kind.is_ai() || second_player.opponent.is_ai() || extra_ai {
+            Display::Flex
+        } else {
+            Display::None
+        }
+    }
+
+    fn node(
+        main_player: &MainPlayer,
+        second_player: &SecondPlayer,
+        four_players: &FourPlayers,
+    ) -> Node {
+        let mut node = button::node();
+        node.display = Self::display(main_player, second_player, four_players);
+        node
+    }
+}
+
+#[derive(Default, Component)]
+#[require(Text)]
+pub struct DifficultyButtonText;
+
+/// Toggles the four players mode.
+#[derive(Default, Component)]
+#[require(Button, Focusable)]
+pub struct FourPlayersButton;
+
+impl FourPlayersButton {
+    const ON_TEXT: &str = "4 Players: On";
+    const OFF_TEXT: &str = "4 Players: Off";
+
+    const fn get_text(four_players: &FourPlayers) -> &'static str {
+        if four_players.enabled {
+            Self::ON_TEXT
+        } else {
+            Self::OFF_TEXT
+        }
+    }
+}
+
+#[derive(Default, Component)]
+#[require(Text)]
+pub struct FourPlayersButtonText;
+
+/// Changes between a human player and the AI on the top or bottom side, in the four players mode.
+#[derive(Component)]
+#[require(Button, Focusable)]
+pub struct ExtraPlayerButton(pub PlayerSide);
+
+impl ExtraPlayerButton {
+    const SIDES: [PlayerSide; 2] = [PlayerSide::Top, PlayerSide::Bottom];
+
+    fn get_text(side: PlayerSide, four_players: &FourPlayers) -> String {
+        let (name, player) = match side {
+            PlayerSide::Bottom => ("Bottom", four_players.bottom),
+            _ => ("Top", four_players.top),
+        };
+        let kind = if player.is_player() { "Player" } else { "AI" };
+
+        format!("{name}: {kind}")
+    }
+
+    fn display(four_players: &FourPlayers) -> Display {
+        if four_players.enabled {
+            Display::Flex
+        } else {
+            Display::None
+        }
+    }
+
+    fn node(four_players: &FourPlayers) -> Node {
+        let mut node = button::node();
+        node.display = Self::display(four_players);
+        node
+    }
+}
+
+#[derive(Component)]
+#[require(Text)]
+pub struct ExtraPlayerButtonText(pub PlayerSide);
+
+#[derive(Default, Component)]
+#[require(Button, Focusable)]
+pub struct PointsToWinButton;
+
+impl PointsToWinButton {
+    fn get_text(rules: &MatchRules) -> String {
+        format!("First to {}", rules.points_to_win)
+    }
+}
+
+#[derive(Default, Component)]
+#[require(Text)]
+pub struct PointsToWinButtonText;
+
+#[derive(Default, Component)]
+#[require(Button, Focusable)]
+pub struct WinByTwoButton;
+
+impl WinByTwoButton {
+    const ON_TEXT: &str = "Win by 2: On";
+    const OFF_TEXT: &str = "Win by 2: Off";
+
+    const fn get_text(rules: &MatchRules) -> &'static str {
+        if rules.win_by_two {
+            Self::ON_TEXT
+        } else {
+            Self::OFF_TEXT
+        }
+    }
+}
+
+#[derive(Default, Component)]
+#[require(Text)]
+pub struct WinByTwoButtonText;
+
+#[derive(Default, Component)]
+#[require(Button, Focusable)]
+pub struct BounceModeButton;
+
+impl BounceModeButton {
+    const CONTACT_POINT_TEXT: &str = "Bounce: Aim";
+    const RANDOM_TEXT: &str = "Bounce: Random";
+
+    const fn get_text(mode: PaddleBounceMode) -> &'static str {
+        match mode {
+            PaddleBounceMode::ContactPoint => Self::CONTACT_POINT_TEXT,
+            PaddleBounceMode::Random => Self::RANDOM_TEXT,
+        }
+    }
+}
+
+#[derive(Default, Component)]
+#[require(Text)]
+pub struct BounceModeButtonText;
+
+#[derive(Default, Component)]
+#[require(Button, Focusable)]
+pub struct MultiBallButton;
+
+impl MultiBallButton {
+    const OFF_TEXT: &str = "Multi-ball: Off";
+    const TIMER_TEXT: &str = "Multi-ball: Timer";
+    const RALLY_TEXT: &str = "Multi-ball: Rally";
+
+    const fn get_text(multi_ball: MultiBall) -> &'static str {
+        match multi_ball {
+            MultiBall::Off => Self::OFF_TEXT,
+            MultiBall::Timer => Self::TIMER_TEXT,
+            MultiBall::Rally => Self::RALLY_TEXT,
+        }
+    }
+}
+
+#[derive(Default, Component)]
+#[require(Text)]
+pub struct MultiBallButtonText;
+
+#[derive(Default, Component)]
+#[require(Button, Focusable)]
+pub struct PowerUpsButton;
+
+impl PowerUpsButton {
+    const ENABLED_TEXT: &str = "Power-ups: On";
+    const DISABLED_TEXT: &str = "Power-ups: Off";
+
+    const fn get_text(power_ups: &PowerUps) -> &'static str {
+        if power_ups.enabled {
+            Self::ENABLED_TEXT
+        } else {
+            Self::DISABLED_TEXT
+        }
+    }
+}
+
+#[derive(Default, Component)]
+#[require(Text)]
+pub struct PowerUpsButtonText;
+
+#[derive(Default, Component)]
+#[require(Button, Focusable)]
+pub struct ArenaLayoutButton;
+
+impl ArenaLayoutButton {
+    fn get_text(layout: &ArenaLayout) -> String {
+        format!("Arena: {}", layout.name)
+    }
+}
+
+#[derive(Default, Component)]
+#[require(Text)]
+pub struct ArenaLayoutButtonText;
+
+#[derive(Default, Component)]
+#[require(Button, Focusable)]
+pub struct ControlsButton;
+
+#[derive(Default, Component)]
+#[require(Button, Focusable)]
 pub struct ExitGameButton;
 
 impl ExitGameButton {
@@ -49,12 +336,41 @@ impl ExitGameButton {
     }
 }
 
-pub fn spawn_main_menu(mut commands: Commands, second_player: Res<SecondPlayer>) {
+#[expect(clippy::too_many_arguments)]
+pub fn spawn_main_menu(
+    mut commands: Commands,
+    main_player: Res<MainPlayer>,
+    second_player: Res<SecondPlayer>,
+    four_players: Res<FourPlayers>,
+    difficulty: Res<AIDifficulty>,
+    match_rules: Res<MatchRules>,
+    paddle_bounce: Res<PaddleBounce>,
+    multi_ball: Res<MultiBall>,
+    power_ups: Res<PowerUps>,
+    layout: Res<ArenaLayout>,
+) {
     commands
         .spawn((MainMenu, screen::node(), BackgroundColor(screen::BG_COLOR)))
         .with_children(|builder| {
             build_play_button(builder);
-            build_change_player_button(builder, &second_player);
+            build_watch_replay_button(builder);
+            build_history_button(builder);
+            build_tournament_button(builder);
+            build_opponent_row(
+                builder,
+                &main_player,
+                &second_player,
+                &four_players,
+                *difficulty,
+            );
+            build_four_players_row(builder, &four_players);
+            build_points_to_win_button(builder, &match_rules);
+            build_win_by_two_button(builder, &match_rules);
+            build_bounce_mode_button(builder, &paddle_bounce);
+            build_multi_ball_button(builder, *multi_ball);
+            build_power_ups_button(builder, &power_ups);
+            build_arena_layout_button(builder, &layout);
+            build_controls_button(builder);
             build_exit_game_button(builder);
         });
 }
@@ -73,7 +389,53 @@ pub fn build_play_button(builder: &mut ChildBuilder<'_>) {
         ));
 }
 
-pub fn build_change_player_button(builder: &mut ChildBuilder<'_>, second_player: &SecondPlayer) {
+pub fn build_watch_replay_button(builder: &mut ChildBuilder<'_>) {
+    builder
+        .spawn((
+            WatchReplayButton,
+            button::node(),
+            BackgroundColor(button::BG_COLOR),
+        ))
+        .with_child((
+            Text::new(WatchReplayButton::TEXT),
+            button::text_font(),
+            TextColor(button::TEXT_COLOR),
+        ));
+}
+
+pub fn build_history_button(builder: &mut ChildBuilder<'_>) {
+    builder
+        .spawn((
+            HistoryButton,
+            button::node(),
+            BackgroundColor(button::BG_COLOR),
+        ))
+        .with_child((
+            Text::new(HistoryMenu::BUTTON_TEXT),
+            button::text_font(),
+            TextColor(button::TEXT_COLOR),
+        ));
+}
+
+pub fn build_tournament_button(builder: &mut ChildBuilder<'_>) {
+    builder
+        .spawn((
+            TournamentButton,
+            button::node(),
+            BackgroundColor(button::BG_COLOR),
+        ))
+        .with_child((
+            Text::new(TournamentMenu::BUTTON_TEXT),
+            button::text_font(),
+            TextColor(button::TEXT_COLOR),
+        ));
+}
+
+pub fn build_change_player_button(
+    builder: &mut ChildBuilder<'_>,
+    main_player: &MainPlayer,
+    second_player: &SecondPlayer,
+) {
     builder
         .spawn((
             ChangePlayerButton,
@@ -82,7 +444,188 @@ pub fn build_change_player_button(builder: &mut ChildBuilder<'_>, second_player:
         ))
         .with_child((
             ChangePlayerButtonText,
-            Text::new(ChangePlayerButton::get_text(second_player.opponent)),
+            Text::new(ChangePlayerButton::get_text(
+                main_player.kind,
+                second_player.opponent,
+            )),
+            button::text_font(),
+            TextColor(button::TEXT_COLOR),
+        ));
+}
+
+pub fn build_opponent_row(
+    builder: &mut ChildBuilder<'_>,
+    main_player: &MainPlayer,
+    second_player: &SecondPlayer,
+    four_players: &FourPlayers,
+    difficulty: AIDifficulty,
+) {
+    builder
+        .spawn((OpponentRow, OpponentRow::node()))
+        .with_children(|builder| {
+            build_change_player_button(builder, main_player, second_player);
+            build_difficulty_button(
+                builder,
+                main_player,
+                second_player,
+                four_players,
+                difficulty,
+            );
+        });
+}
+
+pub fn build_four_players_row(builder: &mut ChildBuilder<'_>, four_players: &FourPlayers) {
+    builder.spawn(OpponentRow::node()).with_children(|builder| {
+        builder
+            .spawn((
+                FourPlayersButton,
+                button::node(),
+                BackgroundColor(button::BG_COLOR),
+            ))
+            .with_child((
+                FourPlayersButtonText,
+                Text::new(FourPlayersButton::get_text(four_players)),
+                button::text_font(),
+                TextColor(button::TEXT_COLOR),
+            ));
+
+        for side in ExtraPlayerButton::SIDES {
+            builder
+                .spawn((
+                    ExtraPlayerButton(side),
+                    ExtraPlayerButton::node(four_players),
+                    BackgroundColor(button::BG_COLOR),
+                ))
+                .with_child((
+                    ExtraPlayerButtonText(side),
+                    Text::new(ExtraPlayerButton::get_text(side, four_players)),
+                    button::text_font(),
+                    TextColor(button::TEXT_COLOR),
+                ));
+        }
+    });
+}
+
+pub fn build_difficulty_button(
+    builder: &mut ChildBuilder<'_>,
+    main_player: &MainPlayer,
+    second_player: &SecondPlayer,
+    four_players: &FourPlayers,
+    difficulty: AIDifficulty,
+) {
+    builder
+        .spawn((
+            DifficultyButton,
+            DifficultyButton::node(main_player, second_player, four_players),
+            BackgroundColor(button::BG_COLOR),
+        ))
+        .with_child((
+            DifficultyButtonText,
+            Text::new(DifficultyButton::get_text(difficulty)),
+            button::text_font(),
+            TextColor(button::TEXT_COLOR),
+        ));
+}
+
+pub fn build_points_to_win_button(builder: &mut ChildBuilder<'_>, match_rules: &MatchRules) {
+    builder
+        .spawn((
+            PointsToWinButton,
+            button::node(),
+            BackgroundColor(button::BG_COLOR),
+        ))
+        .with_child((
+            PointsToWinButtonText,
+            Text::new(PointsToWinButton::get_text(match_rules)),
+            button::text_font(),
+            TextColor(button::TEXT_COLOR),
+        ));
+}
+
+pub fn build_win_by_two_button(builder: &mut ChildBuilder<'_>, match_rules: &MatchRules) {
+    builder
+        .spawn((
+            WinByTwoButton,
+            button::node(),
+            BackgroundColor(button::BG_COLOR),
+        ))
+        .with_child((
+            WinByTwoButtonText,
+            Text::new(WinByTwoButton::get_text(match_rules)),
+            button::text_font(),
+            TextColor(button::TEXT_COLOR),
+        ));
+}
+
+pub fn build_bounce_mode_button(builder: &mut ChildBuilder<'_>, paddle_bounce: &PaddleBounce) {
+    builder
+        .spawn((
+            BounceModeButton,
+            button::node(),
+            BackgroundColor(button::BG_COLOR),
+        ))
+        .with_child((
+            BounceModeButtonText,
+            Text::new(BounceModeButton::get_text(paddle_bounce.mode)),
+            button::text_font(),
+            TextColor(button::TEXT_COLOR),
+        ));
+}
+
+pub fn build_multi_ball_button(builder: &mut ChildBuilder<'_>, multi_ball: MultiBall) {
+    builder
+        .spawn((
+            MultiBallButton,
+            button::node(),
+            BackgroundColor(button::BG_COLOR),
+        ))
+        .with_child((
+            MultiBallButtonText,
+            Text::new(MultiBallButton::get_text(multi_ball)),
+            button::text_font(),
+            TextColor(button::TEXT_COLOR),
+        ));
+}
+
+pub fn build_power_ups_button(builder: &mut ChildBuilder<'_>, power_ups: &PowerUps) {
+    builder
+        .spawn((
+            PowerUpsButton,
+            button::node(),
+            BackgroundColor(button::BG_COLOR),
+        ))
+        .with_child((
+            PowerUpsButtonText,
+            Text::new(PowerUpsButton::get_text(power_ups)),
+            button::text_font(),
+            TextColor(button::TEXT_COLOR),
+        ));
+}
+
+pub fn build_arena_layout_button(builder: &mut ChildBuilder<'_>, layout: &ArenaLayout) {
+    builder
+        .spawn((
+            ArenaLayoutButton,
+            button::node(),
+            BackgroundColor(button::BG_COLOR),
+        ))
+        .with_child((
+            ArenaLayoutButtonText,
+            Text::new(ArenaLayoutButton::get_text(layout)),
+            button::text_font(),
+            TextColor(button::TEXT_COLOR),
+        ));
+}
+
+pub fn build_controls_button(builder: &mut ChildBuilder<'_>) {
+    builder
+        .spawn((
+            ControlsButton,
+            button::node(),
+            BackgroundColor(button::BG_COLOR),
+        ))
+        .with_child((
+            Text::new(controls::ControlsMenu::BUTTON_TEXT),
             button::text_font(),
             TextColor(button::TEXT_COLOR),
         ));
@@ -110,63 +653,242 @@ pub fn despawn_main_menu(query: Single<Entity, With<MainMenu>>, mut commands: Co
 pub fn change_player_button(
     button: Single<&Interaction, (Changed<Interaction>, With<ChangePlayerButton>)>,
     button_text: Single<&mut Text, With<ChangePlayerButtonText>>,
+    mut main_player: ResMut<MainPlayer>,
     mut second_player: ResMut<SecondPlayer>,
 ) {
     let interaction = button.into_inner();
     let mut text = button_text.into_inner();
 
     if *interaction == Interaction::Pressed {
-        let new_opponent = second_player.opponent.change_opponent();
-        second_player.opponent = new_opponent;
+        ChangePlayerButton::change_players(&mut main_player, &mut second_player);
 
-        text.0 = ChangePlayerButton::get_text(new_opponent).to_string();
+        text.0 = ChangePlayerButton::get_text(main_player.kind, second_player.opponent).to_string();
     }
 }
 
-pub fn change_player_with_gamepad(
-    button_text: Single<&mut Text, With<ChangePlayerButtonText>>,
-    gamepads: Query<&Gamepad>,
-    user_gamepad: Res<UserGamepad>,
-    mut second_player: ResMut<SecondPlayer>,
+pub fn difficulty_button(
+    button: Single<&Interaction, (Changed<Interaction>, With<DifficultyButton>)>,
+    button_text: Single<&mut Text, With<DifficultyButtonText>>,
+    mut difficulty: ResMut<AIDifficulty>,
 ) {
+    let interaction = button.into_inner();
     let mut text = button_text.into_inner();
 
-    let gamepad = user_gamepad
-        .get_main()
-        .and_then(|entity| gamepads.get(entity).ok());
+    if *interaction == Interaction::Pressed {
+        *difficulty = difficulty.next();
+
+        text.0 = DifficultyButton::get_text(*difficulty).to_string();
+    }
+}
+
+pub fn update_difficulty_button_display(
+    button: Single<&mut Node, With<DifficultyButton>>,
+    main_player: Res<MainPlayer>,
+    second_player: Res<SecondPlayer>,
+    four_players: Res<FourPlayers>,
+) {
+    let mut node = button.into_inner();
+    node.display = DifficultyButton::display(&main_player, &second_player, &four_players);
+}
+
+pub fn four_players_button(
+    button: Single<&Interaction, (Changed<Interaction>, With<FourPlayersButton>)>,
+    button_text: Single<&mut Text, With<FourPlayersButtonText>>,
+    mut extra_buttons: Query<&mut Node, With<ExtraPlayerButton>>,
+    mut four_players: ResMut<FourPlayers>,
+) {
+    let interaction = button.into_inner();
+    let mut text = button_text.into_inner();
 
-    if gamepad.is_some_and(|gpad| {
-        gpad.any_just_pressed([GamepadButton::DPadLeft, GamepadButton::DPadRight])
-    }) {
-        let new_opponent = second_player.opponent.change_opponent();
-        second_player.opponent = new_opponent;
+    if *interaction == Interaction::Pressed {
+        four_players.enabled = !four_players.enabled;
 
-        text.0 = ChangePlayerButton::get_text(new_opponent).to_string();
+        text.0 = FourPlayersButton::get_text(&four_players).to_string();
+        for mut node in &mut extra_buttons {
+            node.display = ExtraPlayerButton::display(&four_players);
+        }
     }
 }
 
+pub fn extra_player_button(
+    buttons: Query<(&Interaction, &ExtraPlayerButton), Changed<Interaction>>,
+    mut button_texts: Query<(&mut Text, &ExtraPlayerButtonText)>,
+    mut four_players: ResMut<FourPlayers>,
+) {
+    for (interaction, ExtraPlayerButton(side)) in &buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match side {
+            PlayerSide::Bottom => four_players.bottom = four_players.bottom.toggle(),
+            _ => four_players.top = four_players.top.toggle(),
+        }
+
+        for (mut text, ExtraPlayerButtonText(text_side)) in &mut button_texts {
+            if text_side == side {
+                text.0 = ExtraPlayerButton::get_text(*side, &four_players);
+            }
+        }
+    }
+}
+
+pub fn points_to_win_button(
+    button: Single<&Interaction, (Changed<Interaction>, With<PointsToWinButton>)>,
+    button_text: Single<&mut Text, With<PointsToWinButtonText>>,
+    mut match_rules: ResMut<MatchRules>,
+) {
+    let interaction = button.into_inner();
+    let mut text = button_text.into_inner();
+
+    if *interaction == Interaction::Pressed {
+        match_rules.points_to_win = match_rules.next_points_to_win();
+
+        text.0 = PointsToWinButton::get_text(&match_rules);
+    }
+}
+
+pub fn win_by_two_button(
+    button: Single<&Interaction, (Changed<Interaction>, With<WinByTwoButton>)>,
+    button_text: Single<&mut Text, With<WinByTwoButtonText>>,
+    mut match_rules: ResMut<MatchRules>,
+) {
+    let interaction = button.into_inner();
+    let mut text = button_text.into_inner();
+
+    if *interaction == Interaction::Pressed {
+        match_rules.win_by_two = !match_rules.win_by_two;
+
+        text.0 = WinByTwoButton::get_text(&match_rules).to_string();
+    }
+}
+
+pub fn bounce_mode_button(
+    button: Single<&Interaction, (Changed<Interaction>, With<BounceModeButton>)>,
+    button_text: Single<&mut Text, With<BounceModeButtonText>>,
+    mut paddle_bounce: ResMut<PaddleBounce>,
+) {
+    let interaction = button.into_inner();
+    let mut text = button_text.into_inner();
+
+    if *interaction == Interaction::Pressed {
+        paddle_bounce.mode = paddle_bounce.mode.change_mode();
+
+        text.0 = BounceModeButton::get_text(paddle_bounce.mode).to_string();
+    }
+}
+
+pub fn multi_ball_button(
+    button: Single<&Interaction, (Changed<Interaction>, With<MultiBallButton>)>,
+    button_text: Single<&mut Text, With<MultiBallButtonText>>,
+    mut multi_ball: ResMut<MultiBall>,
+) {
+    let interaction = button.into_inner();
+    let mut text = button_text.into_inner();
+
+    if *interaction == Interaction::Pressed {
+        *multi_ball = multi_ball.next();
+
+        text.0 = MultiBallButton::get_text(*multi_ball).to_string();
+    }
+}
+
+pub fn power_ups_button(
+    button: Single<&Interaction, (Changed<Interaction>, With<PowerUpsButton>)>,
+    button_text: Single<&mut Text, With<PowerUpsButtonText>>,
+    mut power_ups: ResMut<PowerUps>,
+) {
+    let interaction = button.into_inner();
+    let mut text = button_text.into_inner();
+
+    if *interaction == Interaction::Pressed {
+        power_ups.enabled = !power_ups.enabled;
+
+        text.0 = PowerUpsButton::get_text(&power_ups).to_string();
+    }
+}
+
+/// Changes to the next arena layout, from the classic layout and the loaded layout files.
+pub fn arena_layout_button(
+    button: Single<&Interaction, (Changed<Interaction>, With<ArenaLayoutButton>)>,
+    button_text: Single<&mut Text, With<ArenaLayoutButtonText>>,
+    available_layouts: Res<ArenaLayouts>,
+    folders: Res<Assets<LoadedFolder>>,
+    layouts: Res<Assets<ArenaLayout>>,
+    mut layout: ResMut<ArenaLayout>,
+) {
+    let interaction = button.into_inner();
+    let mut text = button_text.into_inner();
+
+    if *interaction == Interaction::Pressed {
+        *layout = available_layouts.next(&layout, &folders, &layouts);
+
+        text.0 = ArenaLayoutButton::get_text(&layout);
+    }
+}
+
+/// Opens the lobby for the human players to claim their side, or starts the match without them.
 pub fn play_button(
     button: Single<&Interaction, (Changed<Interaction>, With<PlayButton>)>,
+    main_player: Res<MainPlayer>,
+    second_player: Res<SecondPlayer>,
+    four_players: Res<FourPlayers>,
     mut next_game_state: ResMut<NextState<GameState>>,
 ) {
     let interaction = button.into_inner();
 
     if *interaction == Interaction::Pressed {
-        next_game_state.set(GameState::playing());
+        let has_players =
+            !LobbySlot::get_slots(&main_player, &second_player, &four_players).is_empty();
+        next_game_state.set(if has_players {
+            GameState::Lobby
+        } else {
+            GameState::playing()
+        });
     }
 }
 
-pub fn start_game_with_gamepad(
-    gamepads: Query<&Gamepad>,
-    user_gamepad: Res<UserGamepad>,
+pub fn watch_replay_button(
+    button: Single<&Interaction, (Changed<Interaction>, With<WatchReplayButton>)>,
+    mut watch_replay_events: EventWriter<WatchReplay>,
+) {
+    let interaction = button.into_inner();
+
+    if *interaction == Interaction::Pressed {
+        watch_replay_events.send(WatchReplay { path: None });
+    }
+}
+
+pub fn history_button(
+    button: Single<&Interaction, (Changed<Interaction>, With<HistoryButton>)>,
+    mut next_history_state: ResMut<NextState<HistoryMenuState>>,
+) {
+    let interaction = button.into_inner();
+
+    if *interaction == Interaction::Pressed {
+        next_history_state.set(HistoryMenuState::Open);
+    }
+}
+
+pub fn tournament_button(
+    button: Single<&Interaction, (Changed<Interaction>, With<TournamentButton>)>,
     mut next_game_state: ResMut<NextState<GameState>>,
 ) {
-    let gamepad = user_gamepad
-        .get_main()
-        .and_then(|entity| gamepads.get(entity).ok());
+    let interaction = button.into_inner();
 
-    if gamepad.is_some_and(|gpad| gpad.just_pressed(GamepadButton::Start)) {
-        next_game_state.set(GameState::playing());
+    if *interaction == Interaction::Pressed {
+        next_game_state.set(GameState::Tournament);
+    }
+}
+
+pub fn controls_button(
+    button: Single<&Interaction, (Changed<Interaction>, With<ControlsButton>)>,
+    mut next_controls_state: ResMut<NextState<ControlsMenuState>>,
+) {
+    let interaction = button.into_inner();
+
+    if *interaction == Interaction::Pressed {
+        next_controls_state.set(ControlsMenuState::Open);
     }
 }
 
@@ -182,24 +904,42 @@ pub fn exit_game_button(
 }
 
 pub fn plugin(app: &mut App) {
-    app.init_state::<GameState>();
-    app.add_computed_state::<GameActiveState>();
-    app.add_computed_state::<InGame>();
-
-    app.init_resource::<SecondPlayer>();
-
-    app.add_systems(OnEnter(GameState::MainMenu), spawn_main_menu);
+    // the settings changed by a replay are restored before showing them.
+    app.add_systems(
+        OnEnter(GameState::MainMenu),
+        spawn_main_menu.after(replay::stop_playback),
+    );
     app.add_systems(OnExit(GameState::MainMenu), despawn_main_menu);
 
     app.add_systems(
         Update,
         (
             change_player_button,
-            change_player_with_gamepad,
+            difficulty_button,
+            update_difficulty_button_display.run_if(
+                resource_changed::<MainPlayer>
+                    .or(resource_changed::<SecondPlayer>)
+                    .or(resource_changed::<FourPlayers>),
+            ),
+            four_players_button,
+            extra_player_button,
+            points_to_win_button,
+            win_by_two_button,
+            bounce_mode_button,
+            multi_ball_button,
+            power_ups_button,
+            arena_layout_button,
             play_button,
-            start_game_with_gamepad,
+            watch_replay_button,
+            history_button,
+            tournament_button,
+            controls_button,
             exit_game_button,
         )
-            .run_if(in_state(GameState::MainMenu)),
+            .run_if(
+                in_state(GameState::MainMenu)
+                    .and(in_state(ControlsMenuState::Closed))
+                    .and(in_state(HistoryMenuState::Closed)),
+            ),
     );
 }
@@ -0,0 +1,257 @@
+use bevy::prelude::*;
+
+use crate::game::{
+    player::{PlayerSide, PlayerType},
+    resource::{FourPlayers, GameActiveData, MainPlayer, SecondPlayer},
+    state::GameState,
+    stats::MatchStats,
+};
+use crate::tournament::{Tournament, TournamentMatch};
+use crate::ui::component::{
+    button,
+    focus::{FocusScope, Focusable},
+    screen,
+};
+
+#[derive(Default, Component)]
+#[require(Node, FocusScope)]
+pub struct MatchOverMenu;
+
+impl MatchOverMenu {
+    const TITLE_FONT_SIZE: f32 = 48.0;
+    const SCORE_FONT_SIZE: f32 = 32.0;
+    const STATS_FONT_SIZE: f32 = 20.0;
+
+    /// The side of a winner AI is only told when several AI players are playing.
+    const fn winner_text(side: PlayerSide, player: PlayerType, several_ai: bool) -> &'static str {
+        match (player, side) {
+            (PlayerType::Main, _) => "Player 1 wins",
+            (PlayerType::Second, _) => "Player 2 wins",
+            (PlayerType::Third, _) => "Player 3 wins",
+            (PlayerType::Fourth, _) => "Player 4 wins",
+            (PlayerType::Remote, _) => "Remote player wins",
+            (PlayerType::AI, PlayerSide::Main) if several_ai => "Left AI wins",
+            (PlayerType::AI, PlayerSide::Other) if several_ai => "Right AI wins",
+            (PlayerType::AI, PlayerSide::Top) if several_ai => "Top AI wins",
+            (PlayerType::AI, PlayerSide::Bottom) if several_ai => "Bottom AI wins",
+            (PlayerType::AI, _) => "AI wins",
+        }
+    }
+
+    fn score_node() -> Node {
+        Node {
+            margin: UiRect::bottom(Val::Px(32.0)),
+            ..default()
+        }
+    }
+
+    /// Statistics of the match, with the values of each side in the order of the `sides`.
+    fn stats_text(stats: &MatchStats, sides: &[PlayerSide]) -> String {
+        let per_side = |value: fn(&MatchStats, PlayerSide) -> u32| {
+            sides
+                .iter()
+                .map(|side| value(stats, *side).to_string())
+                .collect::<Vec<_>>()
+                .join(" - ")
+        };
+        let seconds = stats.duration().as_secs();
+
+        format!(
+            "Match time: {}:{:02}\n\
+             Longest rally: {} hits, average {:.1} hits\n\
+             Fastest ball: {:.1}\n\
+             Paddle hits: {}\n\
+             Points on serve: {}",
+            seconds / 60,
+            seconds % 60,
+            stats.longest_rally(),
+            stats.average_rally(),
+            stats.fastest_ball(),
+            per_side(MatchStats::paddle_hits),
+            per_side(MatchStats::serve_points),
+        )
+    }
+}
+
+#[derive(Default, Component)]
+#[require(Button, Focusable)]
+pub struct RematchButton;
+
+impl RematchButton {
+    const TEXT: &str = "Rematch";
+}
+
+/// Goes back to the bracket, replacing the rematch in a tournament.
+#[derive(Default, Component)]
+#[require(Button, Focusable)]
+pub struct ContinueTournamentButton;
+
+impl ContinueTournamentButton {
+    const TEXT: &str = "Continue";
+}
+
+#[derive(Default, Component)]
+#[require(Button, Focusable)]
+pub struct MainMenuButton;
+
+impl MainMenuButton {
+    const TEXT: &str = "Main menu";
+}
+
+#[expect(clippy::too_many_arguments)]
+pub fn spawn_match_over_menu(
+    game_data: Res<GameActiveData>,
+    stats: Res<MatchStats>,
+    main_player: Res<MainPlayer>,
+    second_player: Res<SecondPlayer>,
+    four_players: Res<FourPlayers>,
+    tournament: Option<Res<Tournament>>,
+    tournament_match: Option<Res<TournamentMatch>>,
+    mut commands: Commands,
+) {
+    let score = game_data.score();
+    let player_type = |side: PlayerSide| {
+        side.to_player_type(main_player.kind, second_player.opponent, *four_players)
+    };
+
+    let winner_side = game_data.match_winner().unwrap_or(PlayerSide::Main);
+    let winner = player_type(winner_side);
+    let several_ai = four_players
+        .sides()
+        .iter()
+        .filter(|side| player_type(**side) == PlayerType::AI)
+        .count()
+        > 1;
+
+    // the entrants of a tournament are told by their name.
+    let tournament_match = tournament
+        .as_deref()
+        .zip(tournament_match.as_deref().copied());
+    let title = tournament_match
+        .and_then(|(tournament, id)| {
+            let entrant = tournament.entrant(id, winner_side)?;
+            Some(format!("{} wins", tournament.entrants.get(entrant)?.name))
+        })
+        .unwrap_or_else(|| MatchOverMenu::winner_text(winner_side, winner, several_ai).to_string());
+
+    commands
+        .spawn((
+            MatchOverMenu,
+            screen::node(),
+            BackgroundColor(screen::BG_COLOR),
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                Text::new(title),
+                TextFont {
+                    font_size: MatchOverMenu::TITLE_FONT_SIZE,
+                    ..default()
+                },
+            ));
+            builder.spawn((
+                Text::new(score.format(four_players.sides())),
+                TextFont {
+                    font_size: MatchOverMenu::SCORE_FONT_SIZE,
+                    ..default()
+                },
+                MatchOverMenu::score_node(),
+            ));
+            // the matches of a spectated stream are not simulated, and have no statistics.
+            if !stats.rallies().is_empty() {
+                builder.spawn((
+                    Text::new(MatchOverMenu::stats_text(&stats, four_players.sides())),
+                    TextFont {
+                        font_size: MatchOverMenu::STATS_FONT_SIZE,
+                        ..default()
+                    },
+                    TextLayout::new_with_justify(JustifyText::Center),
+                    MatchOverMenu::score_node(),
+                ));
+            }
+            if tournament_match.is_some() {
+                builder
+                    .spawn((
+                        ContinueTournamentButton,
+                        button::node(),
+                        BackgroundColor(button::BG_COLOR),
+                    ))
+                    .with_child((
+                        Text::new(ContinueTournamentButton::TEXT),
+                        button::text_font(),
+                        TextColor(button::TEXT_COLOR),
+                    ));
+            } else {
+                builder
+                    .spawn((
+                        RematchButton,
+                        button::node(),
+                        BackgroundColor(button::BG_COLOR),
+                    ))
+                    .with_child((
+                        Text::new(RematchButton::TEXT),
+                        button::text_font(),
+                        TextColor(button::TEXT_COLOR),
+                    ));
+            }
+            builder
+                .spawn((
+                    MainMenuButton,
+                    button::node(),
+                    BackgroundColor(button::BG_COLOR),
+                ))
+                .with_child((
+                    Text::new(MainMenuButton::TEXT),
+                    button::text_font(),
+                    TextColor(button::TEXT_COLOR),
+                ));
+        });
+}
+
+pub fn despawn_match_over_menu(query: Single<Entity, With<MatchOverMenu>>, mut commands: Commands) {
+    let entity = query.into_inner();
+    commands.entity(entity).despawn_recursive();
+}
+
+pub fn rematch_button(
+    button: Single<&Interaction, (Changed<Interaction>, With<RematchButton>)>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    let interaction = button.into_inner();
+
+    if *interaction == Interaction::Pressed {
+        next_game_state.set(GameState::playing());
+    }
+}
+
+pub fn continue_tournament_button(
+    button: Single<&Interaction, (Changed<Interaction>, With<ContinueTournamentButton>)>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    let interaction = button.into_inner();
+
+    if *interaction == Interaction::Pressed {
+        next_game_state.set(GameState::Tournament);
+    }
+}
+
+pub fn main_menu_button(
+    button: Single<&Interaction, (Changed<Interaction>, With<MainMenuButton>)>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    let interaction = button.into_inner();
+
+    if *interaction == Interaction::Pressed {
+        next_game_state.set(GameState::MainMenu);
+    }
+}
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::MatchOver), spawn_match_over_menu);
+    app.add_systems(OnExit(GameState::MatchOver), despawn_match_over_menu);
+
+    app.add_systems(
+        Update,
+        (rematch_button, continue_tournament_button, main_menu_button)
+            .run_if(in_state(GameState::MatchOver)),
+    );
+}
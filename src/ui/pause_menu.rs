@@ -1,15 +1,22 @@
 use bevy::prelude::*;
 
-use crate::game::resource::UserGamepad;
+use crate::game::player::PlayerSide;
+use crate::game::resource::PlayerDevices;
 use crate::game::state::{GameActiveState, GameState, InGame};
-use crate::ui::component::{button, screen};
+use crate::settings::input::InputSettings;
+use crate::ui::component::{
+    button,
+    focus::{FocusScope, Focusable},
+    screen,
+};
+use crate::ui::controls::{self, ControlsMenuState};
 
 #[derive(Default, Component)]
-#[require(Node)]
+#[require(Node, FocusScope)]
 pub struct PauseMenu;
 
 #[derive(Default, Component)]
-#[require(Button)]
+#[require(Button, Focusable)]
 pub struct ResumeGameButton;
 
 impl ResumeGameButton {
@@ -17,7 +24,11 @@ impl ResumeGameButton {
 }
 
 #[derive(Default, Component)]
-#[require(Button)]
+#[require(Button, Focusable)]
+pub struct PauseControlsButton;
+
+#[derive(Default, Component)]
+#[require(Button, Focusable)]
 pub struct ExitToMainMenuButton;
 
 impl ExitToMainMenuButton {
@@ -26,20 +37,29 @@ impl ExitToMainMenuButton {
 
 pub fn toggle_game_pause(
     gamepads: Query<&Gamepad>,
-    user_gamepad: Res<UserGamepad>,
+    devices: Res<PlayerDevices>,
     keyboard: Res<ButtonInput<KeyCode>>,
+    settings: Res<InputSettings>,
     game_state: Res<State<GameState>>,
     mut next_game_state: ResMut<NextState<GameState>>,
 ) {
-    let gamepad = user_gamepad
-        .get_main()
-        .and_then(|entity| gamepads.get(entity).ok());
-
-    if keyboard.just_pressed(KeyCode::Escape)
-        || gamepad.is_some_and(|gpad| gpad.just_pressed(GamepadButton::Start))
-    {
+    let keyboard_pressed = InputSettings::KEYBOARD_SIDES
+        .into_iter()
+        .any(|half| keyboard.just_pressed(settings.keyboard(half).pause));
+    let gamepad_pressed = PlayerSide::ALL.into_iter().any(|side| {
+        devices
+            .get_gamepad(side)
+            .and_then(|entity| gamepads.get(entity).ok())
+            .is_some_and(|gpad| gpad.just_pressed(settings.gamepad(side).pause))
+    });
+    let pressed = keyboard_pressed || gamepad_pressed;
+
+    if pressed {
         match game_state.get() {
-            GameState::MainMenu => {}
+            GameState::MainMenu
+            | GameState::Lobby
+            | GameState::Tournament
+            | GameState::MatchOver => {}
             GameState::GameActive { playing } => {
                 next_game_state.set(GameState::GameActive { playing: !playing });
             }
@@ -62,6 +82,17 @@ pub fn spawn_pause_menu(mut commands: Commands) {
                     button::text_font(),
                     TextColor(button::TEXT_COLOR),
                 ));
+            builder
+                .spawn((
+                    PauseControlsButton,
+                    button::node(),
+                    BackgroundColor(button::BG_COLOR),
+                ))
+                .with_child((
+                    Text::new(controls::ControlsMenu::BUTTON_TEXT),
+                    button::text_font(),
+                    TextColor(button::TEXT_COLOR),
+                ));
             builder
                 .spawn((
                     ExitToMainMenuButton,
@@ -92,6 +123,17 @@ pub fn resume_game_button(
     }
 }
 
+pub fn pause_controls_button(
+    button: Single<&Interaction, (Changed<Interaction>, With<PauseControlsButton>)>,
+    mut next_controls_state: ResMut<NextState<ControlsMenuState>>,
+) {
+    let interaction = button.into_inner();
+
+    if *interaction == Interaction::Pressed {
+        next_controls_state.set(ControlsMenuState::Open);
+    }
+}
+
 pub fn exit_to_main_menu_button(
     button: Single<&Interaction, (Changed<Interaction>, With<ExitToMainMenuButton>)>,
     mut next_game_state: ResMut<NextState<GameState>>,
@@ -107,9 +149,17 @@ pub fn plugin(app: &mut App) {
     app.add_systems(OnEnter(GameActiveState::Pause), spawn_pause_menu);
     app.add_systems(OnExit(GameActiveState::Pause), despawn_pause_menu);
 
-    app.add_systems(Update, toggle_game_pause.run_if(in_state(InGame)));
     app.add_systems(
         Update,
-        (resume_game_button, exit_to_main_menu_button).run_if(in_state(GameActiveState::Pause)),
+        toggle_game_pause.run_if(in_state(InGame).and(in_state(ControlsMenuState::Closed))),
+    );
+    app.add_systems(
+        Update,
+        (
+            resume_game_button,
+            pause_controls_button,
+            exit_to_main_menu_button,
+        )
+            .run_if(in_state(GameActiveState::Pause).and(in_state(ControlsMenuState::Closed))),
     );
 }
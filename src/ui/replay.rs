@@ -0,0 +1,138 @@
+use bevy::prelude::*;
+
+use crate::{
+    game::state::{GameActiveState, InGame},
+    replay::ReplayPlayback,
+};
+
+#[derive(Default, Component)]
+#[require(Node)]
+pub struct ReplayControls;
+
+impl ReplayControls {
+    pub const FONT_SIZE: f32 = 14.0;
+    pub const HINT_FONT_SIZE: f32 = 12.0;
+    pub const HINT_COLOR: Color = Color::srgb(0.5, 0.5, 0.5);
+    pub const HINT_TEXT: &str = "Space: pause   Left/Right: seek   Up/Down: speed";
+
+    pub fn node() -> Node {
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::ZERO,
+            right: Val::ZERO,
+            bottom: Val::Px(8.0),
+            display: Display::Flex,
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(4.0),
+            ..Node::DEFAULT
+        }
+    }
+
+    fn format_time(seconds: f32) -> String {
+        let seconds = seconds as u64;
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+
+    fn get_text(playback: &ReplayPlayback) -> String {
+        let status = if playback.is_finished() {
+            "  Ended"
+        } else if playback.is_paused() {
+            "  Paused"
+        } else {
+            ""
+        };
+
+        format!(
+            "Replay  {} / {}  x{}{status}",
+            Self::format_time(playback.seconds(playback.tick())),
+            Self::format_time(playback.seconds(playback.ticks())),
+            playback.speed(),
+        )
+    }
+}
+
+#[derive(Default, Component)]
+#[require(Text)]
+pub struct ReplayControlsText;
+
+pub fn spawn_replay_controls(playback: Res<ReplayPlayback>, mut commands: Commands) {
+    commands
+        .spawn((ReplayControls, ReplayControls::node()))
+        .with_children(|builder| {
+            builder.spawn((
+                ReplayControlsText,
+                Text::new(ReplayControls::get_text(&playback)),
+                TextFont {
+                    font_size: ReplayControls::FONT_SIZE,
+                    ..default()
+                },
+            ));
+            builder.spawn((
+                Text::new(ReplayControls::HINT_TEXT),
+                TextFont {
+                    font_size: ReplayControls::HINT_FONT_SIZE,
+                    ..default()
+                },
+                TextColor(ReplayControls::HINT_COLOR),
+            ));
+        });
+}
+
+pub fn despawn_replay_controls(query: Query<Entity, With<ReplayControls>>, mut commands: Commands) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+pub fn update_replay_controls(
+    text: Single<&mut Text, With<ReplayControlsText>>,
+    playback: Res<ReplayPlayback>,
+) {
+    let mut text = text.into_inner();
+    text.0 = ReplayControls::get_text(&playback);
+}
+
+pub fn replay_controls(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut playback: ResMut<ReplayPlayback>,
+) {
+    let just_pressed = |key: KeyCode, button: GamepadButton| {
+        keyboard.just_pressed(key) || gamepads.iter().any(|gpad| gpad.just_pressed(button))
+    };
+
+    if just_pressed(KeyCode::Space, GamepadButton::South) {
+        playback.toggle_pause();
+    }
+    if just_pressed(KeyCode::ArrowRight, GamepadButton::DPadRight) {
+        playback.seek(true);
+    }
+    if just_pressed(KeyCode::ArrowLeft, GamepadButton::DPadLeft) {
+        playback.seek(false);
+    }
+    if just_pressed(KeyCode::ArrowUp, GamepadButton::DPadUp) {
+        playback.change_speed(true);
+    }
+    if just_pressed(KeyCode::ArrowDown, GamepadButton::DPadDown) {
+        playback.change_speed(false);
+    }
+}
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(
+        OnEnter(InGame),
+        spawn_replay_controls.run_if(resource_exists::<ReplayPlayback>),
+    );
+    app.add_systems(OnExit(InGame), despawn_replay_controls);
+
+    app.add_systems(
+        Update,
+        (
+            replay_controls.run_if(in_state(GameActiveState::Playing)),
+            update_replay_controls,
+        )
+            .chain()
+            .run_if(resource_exists::<ReplayPlayback>),
+    );
+}
@@ -0,0 +1,717 @@
+use bevy::{
+    input::{
+        keyboard::{Key, KeyboardInput},
+        ButtonState,
+    },
+    prelude::*,
+};
+
+use crate::{
+    game::{
+        resource::{FourPlayers, MainPlayer, SecondPlayer},
+        state::GameState,
+    },
+    tournament::{load_tournament, start_tournament_match, BracketMatch, Entrant, Tournament},
+    ui::component::{
+        button,
+        focus::{FocusScope, FocusSystems, Focusable},
+        screen,
+    },
+};
+
+/// Entrants of the next tournament, kept while the game runs.
+#[derive(Resource)]
+pub struct TournamentSetup {
+    pub entrants: Vec<Entrant>,
+}
+
+impl Default for TournamentSetup {
+    fn default() -> Self {
+        Self {
+            entrants: (1..=Tournament::MIN_ENTRANTS)
+                .map(|number| Entrant::numbered(number, number > 2))
+                .collect(),
+        }
+    }
+}
+
+/// Entrant whose name is being typed.
+#[derive(Resource)]
+pub struct NameCapture(pub usize);
+
+/// The tournament or its entrants changed, so the menu is built again.
+#[derive(Event)]
+pub struct TournamentMenuChanged;
+
+#[derive(Default, Component)]
+#[require(Node, FocusScope)]
+pub struct TournamentMenu;
+
+impl TournamentMenu {
+    pub const BUTTON_TEXT: &str = "Tournament";
+
+    const TITLE_FONT_SIZE: f32 = 32.0;
+    const HEADER_FONT_SIZE: f32 = 20.0;
+    const BRACKET_FONT_SIZE: f32 = 14.0;
+    const NAME_WIDTH: f32 = 200.0;
+    const KIND_WIDTH: f32 = 96.0;
+    /// Entrants in each column of the setup.
+    const ENTRANTS_PER_COLUMN: usize = 8;
+    const ROUND_WIDTH: f32 = 144.0;
+    const TYPING_SUFFIX: &str = "_";
+    const BYE_TEXT: &str = "Bye";
+    const UNKNOWN_TEXT: &str = "-";
+
+    const HEADER_COLOR: Color = Color::srgb(1.0, 0.8, 0.0);
+    const WINNER_COLOR: Color = Color::srgb(1.0, 0.8, 0.0);
+    const LOSER_COLOR: Color = Color::srgb(0.5, 0.5, 0.5);
+    const MATCH_BG_COLOR: Color = Color::srgb(0.15, 0.15, 0.15);
+
+    fn row_node() -> Node {
+        Node {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(8.0),
+            ..default()
+        }
+    }
+
+    fn entrants_node() -> Node {
+        Node {
+            flex_direction: FlexDirection::Column,
+            flex_wrap: FlexWrap::Wrap,
+            align_content: AlignContent::Center,
+            height: Val::Px((button::HEIGHT + 8.0) * Self::ENTRANTS_PER_COLUMN as f32),
+            row_gap: Val::Px(8.0),
+            column_gap: Val::Px(32.0),
+            ..default()
+        }
+    }
+
+    fn bracket_node() -> Node {
+        Node {
+            flex_direction: FlexDirection::Row,
+            column_gap: Val::Px(16.0),
+            margin: UiRect::vertical(Val::Px(8.0)),
+            ..default()
+        }
+    }
+
+    fn round_node() -> Node {
+        Node {
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::SpaceAround,
+            align_items: AlignItems::Stretch,
+            width: Val::Px(Self::ROUND_WIDTH),
+            row_gap: Val::Px(4.0),
+            ..default()
+        }
+    }
+
+    fn match_node() -> Node {
+        Node {
+            flex_direction: FlexDirection::Column,
+            padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+            ..default()
+        }
+    }
+
+    fn kind_text(entrant: &Entrant) -> &'static str {
+        if entrant.ai {
+            "AI"
+        } else {
+            "Player"
+        }
+    }
+
+    fn entrant_count_text(count: usize) -> String {
+        format!(
+            "{count} entrants, {} to {} can play",
+            Tournament::MIN_ENTRANTS,
+            Tournament::MAX_ENTRANTS
+        )
+    }
+}
+
+/// Button typing the name of an entrant.
+#[derive(Component)]
+#[require(Button, Focusable)]
+pub struct EntrantNameButton(pub usize);
+
+#[derive(Component)]
+#[require(Text)]
+pub struct EntrantNameText(pub usize);
+
+/// Button changing an entrant between a human player and the AI.
+#[derive(Component)]
+#[require(Button, Focusable)]
+pub struct EntrantKindButton(pub usize);
+
+#[derive(Component)]
+#[require(Text)]
+pub struct EntrantKindText(pub usize);
+
+/// Adds a human player, or an AI, to the entrants.
+#[derive(Component)]
+#[require(Button, Focusable)]
+pub struct AddEntrantButton {
+    pub ai: bool,
+}
+
+impl AddEntrantButton {
+    const fn get_text(&self) -> &'static str {
+        if self.ai {
+            "Add AI"
+        } else {
+            "Add player"
+        }
+    }
+}
+
+#[derive(Default, Component)]
+#[require(Button, Focusable)]
+pub struct RemoveEntrantButton;
+
+impl RemoveEntrantButton {
+    const TEXT: &str = "Remove last";
+}
+
+#[derive(Default, Component)]
+#[require(Button, Focusable)]
+pub struct StartTournamentButton;
+
+impl StartTournamentButton {
+    const TEXT: &str = "Start tournament";
+}
+
+#[derive(Default, Component)]
+#[require(Button, Focusable)]
+pub struct PlayTournamentMatchButton;
+
+impl PlayTournamentMatchButton {
+    const TEXT: &str = "Play next match";
+}
+
+/// Ends the tournament, to start a new one with the same entrants.
+#[derive(Default, Component)]
+#[require(Button, Focusable)]
+pub struct EndTournamentButton;
+
+impl EndTournamentButton {
+    const ABANDON_TEXT: &str = "Abandon tournament";
+    const NEW_TEXT: &str = "New tournament";
+}
+
+#[derive(Default, Component)]
+#[require(Button, Focusable)]
+pub struct TournamentMainMenuButton;
+
+impl TournamentMainMenuButton {
+    const TEXT: &str = "Main menu";
+}
+
+fn spawn_button(builder: &mut ChildBuilder<'_>, component: impl Bundle, text: impl Into<String>) {
+    builder
+        .spawn((component, button::node(), BackgroundColor(button::BG_COLOR)))
+        .with_child((
+            Text::new(text),
+            button::text_font(),
+            TextColor(button::TEXT_COLOR),
+        ));
+}
+
+pub fn spawn_tournament_menu(
+    tournament: Option<Res<Tournament>>,
+    setup: Res<TournamentSetup>,
+    mut commands: Commands,
+) {
+    commands
+        .spawn((
+            TournamentMenu,
+            screen::node(),
+            BackgroundColor(screen::BG_COLOR),
+        ))
+        .with_children(|builder| {
+            build_tournament_menu(builder, tournament.as_deref(), &setup);
+        });
+}
+
+pub fn rebuild_tournament_menu(
+    menu: Single<Entity, With<TournamentMenu>>,
+    tournament: Option<Res<Tournament>>,
+    setup: Res<TournamentSetup>,
+    mut commands: Commands,
+) {
+    commands
+        .entity(menu.into_inner())
+        .despawn_descendants()
+        .with_children(|builder| {
+            build_tournament_menu(builder, tournament.as_deref(), &setup);
+        });
+}
+
+/// Builds the bracket of the tournament, or the entrants of the next one.
+pub fn build_tournament_menu(
+    builder: &mut ChildBuilder<'_>,
+    tournament: Option<&Tournament>,
+    setup: &TournamentSetup,
+) {
+    builder.spawn((
+        Text::new(TournamentMenu::BUTTON_TEXT),
+        TextFont {
+            font_size: TournamentMenu::TITLE_FONT_SIZE,
+            ..default()
+        },
+    ));
+
+    match tournament {
+        Some(tournament) => build_bracket(builder, tournament),
+        None => build_setup(builder, setup),
+    }
+
+    spawn_button(
+        builder,
+        TournamentMainMenuButton,
+        TournamentMainMenuButton::TEXT,
+    );
+}
+
+pub fn build_setup(builder: &mut ChildBuilder<'_>, setup: &TournamentSetup) {
+    builder
+        .spawn(TournamentMenu::entrants_node())
+        .with_children(|builder| {
+            for (index, entrant) in setup.entrants.iter().enumerate() {
+                builder
+                    .spawn(TournamentMenu::row_node())
+                    .with_children(|builder| {
+                        builder
+                            .spawn((
+                                EntrantNameButton(index),
+                                Node {
+                                    width: Val::Px(TournamentMenu::NAME_WIDTH),
+                                    ..button::node()
+                                },
+                                BackgroundColor(button::BG_COLOR),
+                            ))
+                            .with_child((
+                                EntrantNameText(index),
+                                Text::new(entrant.name.clone()),
+                                button::text_font(),
+                                TextColor(button::TEXT_COLOR),
+                            ));
+                        builder
+                            .spawn((
+                                EntrantKindButton(index),
+                                Node {
+                                    width: Val::Px(TournamentMenu::KIND_WIDTH),
+                                    ..button::node()
+                                },
+                                BackgroundColor(button::BG_COLOR),
+                            ))
+                            .with_child((
+                                EntrantKindText(index),
+                                Text::new(TournamentMenu::kind_text(entrant)),
+                                button::text_font(),
+                                TextColor(button::TEXT_COLOR),
+                            ));
+                    });
+            }
+        });
+
+    builder
+        .spawn(TournamentMenu::row_node())
+        .with_children(|builder| {
+            let count = setup.entrants.len();
+            if count < Tournament::MAX_ENTRANTS {
+                for ai in [false, true] {
+                    let add_button = AddEntrantButton { ai };
+                    let text = add_button.get_text();
+                    spawn_button(builder, add_button, text);
+                }
+            }
+            if count > 0 {
+                spawn_button(builder, RemoveEntrantButton, RemoveEntrantButton::TEXT);
+            }
+        });
+
+    let count = setup.entrants.len();
+    builder.spawn((
+        Text::new(TournamentMenu::entrant_count_text(count)),
+        button::text_font(),
+    ));
+    if (Tournament::MIN_ENTRANTS..=Tournament::MAX_ENTRANTS).contains(&count) {
+        spawn_button(builder, StartTournamentButton, StartTournamentButton::TEXT);
+    }
+}
+
+pub fn build_bracket(builder: &mut ChildBuilder<'_>, tournament: &Tournament) {
+    builder
+        .spawn(TournamentMenu::bracket_node())
+        .with_children(|builder| {
+            for (round, matches) in tournament.rounds.iter().enumerate() {
+                builder
+                    .spawn(Node {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(8.0),
+                        ..default()
+                    })
+                    .with_children(|builder| {
+                        builder.spawn((
+                            Text::new(tournament.round_name(round)),
+                            button::text_font(),
+                            TextColor(TournamentMenu::HEADER_COLOR),
+                        ));
+                        builder
+                            .spawn(TournamentMenu::round_node())
+                            .with_children(|builder| {
+                                for bracket_match in matches {
+                                    build_bracket_match(builder, tournament, round, bracket_match);
+                                }
+                            });
+                    });
+            }
+        });
+
+    let status = match (tournament.champion(), tournament.next_match()) {
+        (Some(champion), _) => format!("{} wins the tournament!", champion.name),
+        (None, Some(id)) => {
+            let name = |entrant: Option<usize>| {
+                entrant
+                    .and_then(|entrant| tournament.entrants.get(entrant))
+                    .map_or(TournamentMenu::UNKNOWN_TEXT, |entrant| &entrant.name)
+            };
+            let [left, right] = tournament
+                .get(id)
+                .map(|bracket_match| bracket_match.entrants)
+                .unwrap_or_default();
+            format!(
+                "{}: {} (left) vs {} (right)",
+                tournament.round_name(id.round),
+                name(left),
+                name(right)
+            )
+        }
+        (None, None) => String::new(),
+    };
+    builder.spawn((
+        Text::new(status),
+        TextFont {
+            font_size: TournamentMenu::HEADER_FONT_SIZE,
+            ..default()
+        },
+    ));
+
+    if tournament.next_match().is_some() {
+        spawn_button(
+            builder,
+            PlayTournamentMatchButton,
+            PlayTournamentMatchButton::TEXT,
+        );
+        spawn_button(
+            builder,
+            EndTournamentButton,
+            EndTournamentButton::ABANDON_TEXT,
+        );
+    } else {
+        spawn_button(builder, EndTournamentButton, EndTournamentButton::NEW_TEXT);
+    }
+}
+
+/// Builds a match of the bracket, with the winner highlighted.
+pub fn build_bracket_match(
+    builder: &mut ChildBuilder<'_>,
+    tournament: &Tournament,
+    round: usize,
+    bracket_match: &BracketMatch,
+) {
+    builder
+        .spawn((
+            TournamentMenu::match_node(),
+            BackgroundColor(TournamentMenu::MATCH_BG_COLOR),
+        ))
+        .with_children(|builder| {
+            for entrant in bracket_match.entrants {
+                let text = match entrant.and_then(|entrant| tournament.entrants.get(entrant)) {
+                    Some(entrant) => entrant.name.clone(),
+                    // only the first round has byes, the other matches wait for their entrants.
+                    None if round == 0 => TournamentMenu::BYE_TEXT.to_string(),
+                    None => TournamentMenu::UNKNOWN_TEXT.to_string(),
+                };
+                let color = match bracket_match.winner {
+                    Some(winner) if entrant == Some(winner) => TournamentMenu::WINNER_COLOR,
+                    Some(_) => TournamentMenu::LOSER_COLOR,
+                    None => Color::WHITE,
+                };
+
+                builder.spawn((
+                    Text::new(text),
+                    TextFont {
+                        font_size: TournamentMenu::BRACKET_FONT_SIZE,
+                        ..default()
+                    },
+                    TextColor(color),
+                ));
+            }
+        });
+}
+
+pub fn despawn_tournament_menu(
+    query: Single<Entity, With<TournamentMenu>>,
+    mut commands: Commands,
+) {
+    let entity = query.into_inner();
+    commands.entity(entity).despawn_recursive();
+    commands.remove_resource::<NameCapture>();
+}
+
+pub fn entrant_name_button(
+    buttons: Query<(&Interaction, &EntrantNameButton), Changed<Interaction>>,
+    capture: Option<Res<NameCapture>>,
+    mut commands: Commands,
+) {
+    for (interaction, EntrantNameButton(index)) in &buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        // pressing the button again stops typing.
+        if capture.as_ref().is_some_and(|capture| capture.0 == *index) {
+            commands.remove_resource::<NameCapture>();
+        } else {
+            commands.insert_resource(NameCapture(*index));
+        }
+    }
+}
+
+/// Types the name of the entrant with the keyboard, until `Enter` or `Escape` is pressed.
+///
+/// It reads the keys even when no name is typed, so the key starting to type is not typed.
+pub fn type_entrant_name(
+    mut keys: EventReader<KeyboardInput>,
+    capture: Option<Res<NameCapture>>,
+    mut setup: ResMut<TournamentSetup>,
+    mut commands: Commands,
+) {
+    let Some(index) = capture.map(|capture| capture.0) else {
+        keys.clear();
+        return;
+    };
+    let Some(entrant) = setup.entrants.get_mut(index) else {
+        commands.remove_resource::<NameCapture>();
+        return;
+    };
+
+    for key in keys.read().filter(|key| key.state == ButtonState::Pressed) {
+        match &key.logical_key {
+            Key::Character(text) => {
+                for character in text.chars().filter(|character| !character.is_control()) {
+                    if entrant.name.chars().count() < Entrant::MAX_NAME_LEN {
+                        entrant.name.push(character);
+                    }
+                }
+            }
+            Key::Space if entrant.name.chars().count() < Entrant::MAX_NAME_LEN => {
+                entrant.name.push(' ');
+            }
+            Key::Backspace => {
+                entrant.name.pop();
+            }
+            Key::Enter | Key::Escape => {
+                commands.remove_resource::<NameCapture>();
+                break;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Names left empty go back to the default name of the entrant.
+pub fn fill_empty_names(mut setup: ResMut<TournamentSetup>) {
+    for (index, entrant) in setup.entrants.iter_mut().enumerate() {
+        if entrant.name.trim().is_empty() {
+            *entrant = Entrant::numbered(index + 1, entrant.ai);
+        }
+    }
+}
+
+pub fn entrant_kind_button(
+    buttons: Query<(&Interaction, &EntrantKindButton), Changed<Interaction>>,
+    mut setup: ResMut<TournamentSetup>,
+) {
+    for (interaction, EntrantKindButton(index)) in &buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        if let Some(entrant) = setup.entrants.get_mut(*index) {
+            entrant.ai = !entrant.ai;
+        }
+    }
+}
+
+pub fn update_entrant_texts(
+    mut names: Query<(&EntrantNameText, &mut Text), Without<EntrantKindText>>,
+    mut kinds: Query<(&EntrantKindText, &mut Text), Without<EntrantNameText>>,
+    setup: Res<TournamentSetup>,
+    capture: Option<Res<NameCapture>>,
+) {
+    for (EntrantNameText(index), mut text) in &mut names {
+        let Some(entrant) = setup.entrants.get(*index) else {
+            continue;
+        };
+        let typing = capture.as_ref().is_some_and(|capture| capture.0 == *index);
+
+        text.0 = if typing {
+            format!("{}{}", entrant.name, TournamentMenu::TYPING_SUFFIX)
+        } else {
+            entrant.name.clone()
+        };
+    }
+
+    for (EntrantKindText(index), mut text) in &mut kinds {
+        if let Some(entrant) = setup.entrants.get(*index) {
+            text.0 = TournamentMenu::kind_text(entrant).to_string();
+        }
+    }
+}
+
+pub fn add_entrant_button(
+    buttons: Query<(&Interaction, &AddEntrantButton), Changed<Interaction>>,
+    mut setup: ResMut<TournamentSetup>,
+    mut changed_events: EventWriter<TournamentMenuChanged>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction == Interaction::Pressed && setup.entrants.len() < Tournament::MAX_ENTRANTS {
+            let number = setup.entrants.len() + 1;
+            setup.entrants.push(Entrant::numbered(number, button.ai));
+            changed_events.send(TournamentMenuChanged);
+        }
+    }
+}
+
+pub fn remove_entrant_button(
+    button: Single<&Interaction, (Changed<Interaction>, With<RemoveEntrantButton>)>,
+    mut setup: ResMut<TournamentSetup>,
+    mut changed_events: EventWriter<TournamentMenuChanged>,
+    mut commands: Commands,
+) {
+    let interaction = button.into_inner();
+
+    if *interaction == Interaction::Pressed {
+        setup.entrants.pop();
+        commands.remove_resource::<NameCapture>();
+        changed_events.send(TournamentMenuChanged);
+    }
+}
+
+pub fn start_tournament_button(
+    button: Single<&Interaction, (Changed<Interaction>, With<StartTournamentButton>)>,
+    setup: Res<TournamentSetup>,
+    mut changed_events: EventWriter<TournamentMenuChanged>,
+    mut commands: Commands,
+) {
+    let interaction = button.into_inner();
+
+    if *interaction == Interaction::Pressed {
+        let Some(tournament) = Tournament::new(setup.entrants.clone()) else {
+            return;
+        };
+
+        tournament.save_or_warn();
+        commands.insert_resource(tournament);
+        commands.remove_resource::<NameCapture>();
+        changed_events.send(TournamentMenuChanged);
+    }
+}
+
+pub fn play_tournament_match_button(
+    button: Single<&Interaction, (Changed<Interaction>, With<PlayTournamentMatchButton>)>,
+    tournament: Res<Tournament>,
+    (mut main_player, mut second_player, mut four_players): (
+        ResMut<MainPlayer>,
+        ResMut<SecondPlayer>,
+        ResMut<FourPlayers>,
+    ),
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut commands: Commands,
+) {
+    let interaction = button.into_inner();
+
+    if *interaction == Interaction::Pressed {
+        start_tournament_match(
+            &tournament,
+            &mut main_player,
+            &mut second_player,
+            &mut four_players,
+            &mut next_game_state,
+            &mut commands,
+        );
+    }
+}
+
+/// Deletes the saved tournament, and goes back to its entrants.
+pub fn end_tournament_button(
+    button: Single<&Interaction, (Changed<Interaction>, With<EndTournamentButton>)>,
+    tournament: Res<Tournament>,
+    mut setup: ResMut<TournamentSetup>,
+    mut changed_events: EventWriter<TournamentMenuChanged>,
+    mut commands: Commands,
+) {
+    let interaction = button.into_inner();
+
+    if *interaction == Interaction::Pressed {
+        setup.entrants = tournament.entrants.clone();
+        Tournament::remove_saved();
+        commands.remove_resource::<Tournament>();
+        changed_events.send(TournamentMenuChanged);
+    }
+}
+
+pub fn tournament_main_menu_button(
+    button: Single<&Interaction, (Changed<Interaction>, With<TournamentMainMenuButton>)>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    let interaction = button.into_inner();
+
+    if *interaction == Interaction::Pressed {
+        next_game_state.set(GameState::MainMenu);
+    }
+}
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<TournamentSetup>();
+    app.add_event::<TournamentMenuChanged>();
+
+    // the keys typed in a name do not move the focus.
+    app.configure_sets(
+        PreUpdate,
+        FocusSystems.run_if(not(resource_exists::<NameCapture>)),
+    );
+
+    app.add_systems(
+        OnEnter(GameState::Tournament),
+        spawn_tournament_menu.after(load_tournament),
+    );
+    app.add_systems(OnExit(GameState::Tournament), despawn_tournament_menu);
+
+    app.add_systems(
+        Update,
+        (
+            type_entrant_name,
+            entrant_name_button,
+            fill_empty_names.run_if(not(resource_exists::<NameCapture>)),
+            entrant_kind_button,
+            add_entrant_button,
+            remove_entrant_button,
+            start_tournament_button,
+            play_tournament_match_button.run_if(resource_exists::<Tournament>),
+            end_tournament_button.run_if(resource_exists::<Tournament>),
+            tournament_main_menu_button,
+            rebuild_tournament_menu.run_if(on_event::<TournamentMenuChanged>),
+            update_entrant_texts,
+        )
+            .chain()
+            .run_if(in_state(GameState::Tournament)),
+    );
+}